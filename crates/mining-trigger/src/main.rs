@@ -11,7 +11,6 @@ use actix_web::{
 };
 use clap::Parser;
 use once_cell::sync::Lazy;
-use rand;
 use reqwest::{Client, header};
 use tokio::sync::mpsc;
 use tokio::time::sleep;
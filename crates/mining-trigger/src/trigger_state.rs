@@ -18,6 +18,12 @@ pub struct TriggerState {
     pub pending_flush: bool,
 }
 
+impl Default for TriggerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TriggerState {
     pub fn new() -> Self {
         Self { chain_map: HashMap::new(), scheduled_trigger: None, pending_flush: false }
@@ -40,7 +46,7 @@ impl TriggerState {
         now: Instant,
         confirmation_period: Duration,
     ) -> (Vec<ChainId>, Confirmations) {
-        if self.scheduled_trigger.map_or(true, |st| now < st) {
+        if self.scheduled_trigger.is_none_or(|st| now < st) {
             return (vec![], Confirmations(0));
         }
 
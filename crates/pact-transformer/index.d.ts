@@ -6,14 +6,35 @@
 export interface PactModule {
   name: string
   namespace?: string
+  /** Namespace expression evaluated at deploy time, e.g. `(read-msg 'ns)` */
+  dynamicNamespace?: string
   doc?: string
   governance: string
   functions: Array<PactFunction>
   capabilities: Array<PactCapability>
   schemas: Array<PactSchema>
   constants: Array<PactConstant>
-  uses: Array<string>
+  tables: Array<PactTable>
+  uses: Array<PactUse>
   implements: Array<string>
+  span?: SourceSpan
+}
+/** A Pact interface declaration */
+export interface PactInterface {
+  name: string
+  namespace?: string
+  /** Namespace expression evaluated at deploy time, e.g. `(read-msg 'ns)` */
+  dynamicNamespace?: string
+  doc?: string
+  /** Function and pact signatures; bodies are always empty */
+  functions: Array<PactFunction>
+  capabilities: Array<PactCapability>
+  schemas: Array<PactSchema>
+  constants: Array<PactConstant>
+  /** Entries of the interface-level `@model [...]` blocks */
+  models: Array<string>
+  uses: Array<PactUse>
+  span?: SourceSpan
 }
 export interface PactFunction {
   name: string
@@ -22,6 +43,55 @@ export interface PactFunction {
   returnType?: string
   body: string
   isDefun: boolean
+  /** Ordered `step`/`step-with-rollback` entries; empty for defuns */
+  steps: Array<PactStep>
+  /** What calling the function does to chain state, including through the functions it calls */
+  effect: PactEffect
+  /** Capabilities the body acquires or requires, in source order */
+  capabilities: Array<CapabilityRequirement>
+  /** Typed body, only populated when parsing with `expressions` */
+  bodyExprs?: Array<PactExpr>
+  span?: SourceSpan
+}
+/** A capability a function body brings into scope or checks for */
+export interface CapabilityRequirement {
+  /** Name as written, e.g. `TRANSFER` or `coin.TRANSFER` */
+  name: string
+  /** Argument expressions as written, e.g. `sender` or `1.0` */
+  args: Array<string>
+  /** `with-capability`, which the signer must sign for, rather than `require-capability` */
+  acquired: boolean
+  span?: SourceSpan
+}
+/** How a function touches chain state, from least to most */
+export const enum PactEffect {
+  /** Computes from its arguments and the transaction alone */
+  Pure = 'pure',
+  /** Reads tables or keysets but never writes; can run with `local` or `dirtyRead` */
+  ReadOnly = 'read-only',
+  /**
+   * Writes tables, acquires or requires capabilities, emits events or calls other modules;
+   * needs a signed transaction
+   */
+  Mutating = 'mutating'
+}
+/** A single step of a defpact */
+export interface PactStep {
+  /** Entity hint for private pacts, e.g. `(step alice ...)` */
+  entity?: string
+  expr: string
+  rollback?: string
+  /** Whether the step calls `yield` */
+  yields: boolean
+  /** Keys of the object literal passed to `yield` */
+  yieldFields: Array<string>
+  /** Target chain argument of a cross-chain `yield` */
+  yieldChain?: string
+  /** Whether the step calls `resume` */
+  resumes: boolean
+  /** Keys bound by `resume` */
+  resumeFields: Array<string>
+  span?: SourceSpan
 }
 export interface PactCapability {
   name: string
@@ -30,6 +100,14 @@ export interface PactCapability {
   returnType?: string
   managed?: ManagedInfo
   isEvent: boolean
+  /**
+   * Whether the body checks a guard, itself or through the functions and capabilities it
+   * uses, so that a signer has to scope the capability
+   */
+  guarded: boolean
+  /** Typed body, only populated when parsing with `expressions` */
+  bodyExprs?: Array<PactExpr>
+  span?: SourceSpan
 }
 export interface ManagedInfo {
   parameter: string
@@ -39,20 +117,112 @@ export interface PactSchema {
   name: string
   doc?: string
   fields: Array<SchemaField>
+  span?: SourceSpan
 }
 export interface SchemaField {
   name: string
   fieldType: string
+  span?: SourceSpan
 }
 export interface PactConstant {
   name: string
   doc?: string
   constantType?: string
   value: string
+  span?: SourceSpan
 }
 export interface PactParameter {
   name: string
   parameterType?: string
+  span?: SourceSpan
+}
+/**
+ * A node of a function, pact or capability body
+ *
+ * How `value`, `bindings` and `children` are used depends on `kind`:
+ * - `Application`: `value` is the head (`coin.transfer`), `children` the arguments
+ * - `Let`/`LetStar`: `bindings` carry the bound values, `children` the body
+ * - `Bind`: `children[0]` is the source object, then the body; `bindings` from the binding map
+ * - `WithCapability`: `children[0]` is the capability application, then the body
+ * - `WithRead`: `value` is the table, `children[0]` the key, then the body
+ * - `WithDefaultRead`: like `WithRead` with the default object as `children[1]`
+ * - `Lambda`/`Resume`: `bindings` are the parameters or resumed keys, `children` the body
+ * - `Step`: `value` is the entity if any, `children` the step and optional rollback expression
+ * - `Object`: one binding per pair, keyed by the property name
+ * - literals and `Reference`: `value` is the source text
+ */
+export interface PactExpr {
+  kind: PactExprKind
+  value?: string
+  bindings: Array<PactBinding>
+  children: Array<PactExpr>
+  span?: SourceSpan
+}
+export const enum PactExprKind {
+  Application = 'Application',
+  Let = 'Let',
+  LetStar = 'LetStar',
+  Bind = 'Bind',
+  WithCapability = 'WithCapability',
+  WithRead = 'WithRead',
+  WithDefaultRead = 'WithDefaultRead',
+  If = 'If',
+  Cond = 'Cond',
+  Try = 'Try',
+  Lambda = 'Lambda',
+  Resume = 'Resume',
+  EmitEvent = 'EmitEvent',
+  Step = 'Step',
+  Integer = 'Integer',
+  Decimal = 'Decimal',
+  String = 'String',
+  Boolean = 'Boolean',
+  Symbol = 'Symbol',
+  List = 'List',
+  Object = 'Object',
+  Reference = 'Reference',
+  /** Anything the grammar produced that has no typed form, e.g. error nodes */
+  Unknown = 'Unknown'
+}
+/** A name introduced by a binding form, or a property of an object literal */
+export interface PactBinding {
+  name: string
+  /** Object key the name is read from, e.g. `"balance"` in `{ "balance" := bal }` */
+  key?: string
+  bindingType?: string
+  value?: PactExpr
+  span?: SourceSpan
+}
+export interface PactTable {
+  name: string
+  /** Schema name from `(deftable name:{schema})` */
+  schema?: string
+  doc?: string
+  span?: SourceSpan
+}
+/** A `(use ...)` import */
+export interface PactUse {
+  /** Module name without its namespace */
+  module: string
+  namespace?: string
+  /** Pinned module hash from `(use coin "hash")` */
+  hash?: string
+  /** Symbols from an import list; `None` imports everything */
+  imports?: Array<string>
+  span?: SourceSpan
+}
+/**
+ * Location of a node in the Pact source
+ *
+ * Lines are 1-based and columns are 0-based, matching the source map conventions.
+ */
+export interface SourceSpan {
+  startByte: number
+  endByte: number
+  startLine: number
+  startColumn: number
+  endLine: number
+  endColumn: number
 }
 export interface PactConfig {
   /** Transformation options */
@@ -109,6 +279,19 @@ export interface ConfigLoadResult {
   /** Whether default config was used */
   isDefault: boolean
 }
+export const enum Severity {
+  Error = 'Error',
+  Warning = 'Warning',
+  Info = 'Info'
+}
+/** A problem found in Pact source */
+export interface Diagnostic {
+  /** Stable identifier such as `P0002` */
+  code: string
+  severity: Severity
+  message: string
+  span: SourceSpan
+}
 export interface FileOutputOptions {
   /** Output directory for generated files */
   outputDir: string
@@ -165,12 +348,28 @@ export interface SourceMapOptions {
   declarationMap?: boolean
 }
 /** Transformation result */
+/** What a module's definitions are called in the generated code, keyed by Pact name */
+export interface JsNames {
+  /** Module reference, e.g. `free.coin` */
+  module: string
+  functions: Record<string, string>
+  constants: Record<string, string>
+  /** Capability builders, e.g. `TRANSFER` to `transferCapability` */
+  capabilities: Record<string, string>
+  /** Schema interfaces, e.g. `account-details` to `AccountDetails` */
+  schemas: Record<string, string>
+}
 export interface TransformationResult {
   modules: Array<PactModule>
+  interfaces: Array<PactInterface>
   code: string
   types: string
   sourceMap?: string
   declarationMap?: string
+  /** Parse errors kept in recovery mode, and warnings such as constants left out of the output */
+  diagnostics: Array<Diagnostic>
+  /** What each module's definitions are called in the generated code */
+  names: Array<JsNames>
 }
 /** Transform options */
 export interface TransformOptions {
@@ -179,6 +378,49 @@ export interface TransformOptions {
   sourceMaps?: boolean
   sourceFilePath?: string
   declarationMaps?: boolean
+  /** Namespace substituted for modules declared under a dynamic `(namespace ...)` */
+  namespace?: string
+  /** How parse errors are reported: `text` code frames (default) or `json` */
+  errorFormat?: string
+  /** Generate bindings for everything that parsed cleanly instead of failing on errors */
+  recover?: boolean
+  /** Also emit runtime validators and type guards for each `defschema` */
+  validators?: boolean
+  /** Module the generated code imports its runtime from (default `@pact-toolbox/transaction`) */
+  runtimeImport?: string
+  /** Name under which the runtime exports the execution builder (default `execution`) */
+  executionBuilder?: string
+  /** Name under which the runtime exports the continuation builder (default `continuation`) */
+  continuationBuilder?: string
+  /** Module syntax of the generated JavaScript: `esm` (default) or `cjs` */
+  moduleFormat?: string
+  /** How modules are exported: `flat` declarations (default) or one `namespaced` object per module */
+  exportMode?: string
+  /** TypeScript types to use for Pact types and schemas, and the imports they need */
+  typeMappings?: TypeMappings
+}
+/**
+ * User overrides of the Pact-to-TypeScript type mapping. Mapped values are passed through
+ * as Pact sends them unless `codecs` names functions converting them.
+ */
+export interface TypeMappings {
+  /** TypeScript type per Pact type, e.g. `decimal` to `Amount` */
+  types?: Record<string, string>
+  /** TypeScript type per schema wherever it is referenced, or per field as `schema.field` */
+  schemas?: Record<string, string>
+  /** Import statements added to the header of generated declarations, e.g. for `Amount` */
+  imports?: Array<string>
+  /** Conversions of mapped types, under the same keys as `types` and `schemas` */
+  codecs?: Record<string, TypeCodec>
+}
+/** Functions converting a mapped type to and from Pact JSON, imported by the generated code */
+export interface TypeCodec {
+  /** Module exporting the functions, e.g. `./amounts` */
+  from: string
+  /** Takes a value as Pact returns it and gives the mapped type */
+  decode?: string
+  /** Takes the mapped type and gives a value the Pact type's encoding accepts */
+  encode?: string
 }
 export interface WatchOptions {
   /** Glob patterns to watch - examples: all pact files, src folder pact files */
@@ -227,6 +469,11 @@ export interface PactTransformerConfig {
   fileOutput?: FileOutputOptions
   watch?: WatchOptions
 }
+/** Options for the parse method */
+export interface ParseOptions {
+  /** Also build the typed body of each function, pact and capability as `bodyExprs` */
+  expressions?: boolean
+}
 /** Options for transformFile method */
 export interface TransformFileOptions {
   transformOptions?: TransformOptions
@@ -245,6 +492,8 @@ export interface TransformResult {
   typescript?: string
   sourceMap?: string
   declarationMap?: string
+  /** Recovered parse errors and generation warnings reported alongside the output */
+  diagnostics: Array<Diagnostic>
 }
 /** File operation result */
 export interface FileResult {
@@ -271,12 +520,20 @@ export interface ModuleInfo {
   schemaCount: number
   capabilityCount: number
   constantCount: number
+  tableCount: number
+  tables: Array<TableInfo>
+  uses: Array<PactUse>
+  functions: Array<PactFunction>
+  capabilities: Array<PactCapability>
 }
-/** Error information */
-export interface ErrorInfo {
-  message: string
-  line: number
-  column: number
+/** Table information */
+export interface TableInfo {
+  name: string
+  schema?: string
+  /** Whether the schema is declared in the same module */
+  schemaResolved: boolean
+  doc?: string
+  span?: SourceSpan
 }
 /** Watch statistics */
 export interface WatchStatsResult {
@@ -296,7 +553,7 @@ export declare class PactTransformer {
   /** Transform multiple files with options and optional watch mode */
   transformFiles(patterns: Array<string>, options?: TransformFilesOptions | undefined | null): Promise<BatchResult>
   /** Get parsing errors for source code */
-  getErrors(source: string): Array<ErrorInfo>
+  getErrors(source: string): Array<Diagnostic>
   /** Parse Pact source and return module AST */
-  parse(source: string): Array<ModuleInfo>
+  parse(source: string, options?: ParseOptions | undefined | null): Array<ModuleInfo>
 }
@@ -0,0 +1,537 @@
+//! Effect and capability analysis of definition bodies
+//!
+//! The analysis walks the syntax tree directly, so a parse without `expressions` never
+//! builds `PactExpr` trees.
+
+use crate::ast::{CapabilityRequirement, PactEffect, PactModule, SourceSpan};
+use crate::parser::node_span;
+use tree_sitter::Node;
+
+/// Builtins that write to the database, need capabilities in scope or drive pacts
+const MUTATING_BUILTINS: &[&str] = &[
+  "insert",
+  "write",
+  "update",
+  "create-table",
+  "define-keyset",
+  "define-namespace",
+  "install-capability",
+  "compose-capability",
+  "require-capability",
+  "emit-event",
+  "yield",
+  "continue",
+];
+
+/// Builtins that read the database without writing to it
+const READ_BUILTINS: &[&str] = &[
+  "read",
+  "select",
+  "keys",
+  "fold-db",
+  "txids",
+  "txlog",
+  "keylog",
+  "describe-table",
+  "describe-keyset",
+  "describe-namespace",
+  "describe-module",
+  "list-modules",
+];
+
+/// Builtins that only compute from their arguments and the transaction
+const PURE_BUILTINS: &[&str] = &[
+  "+",
+  "-",
+  "*",
+  "/",
+  "^",
+  "<",
+  "<=",
+  "=",
+  "!=",
+  ">",
+  ">=",
+  "&",
+  "|",
+  "~",
+  "abs",
+  "add-time",
+  "and",
+  "and?",
+  "at",
+  "base64-decode",
+  "base64-encode",
+  "ceiling",
+  "chain-data",
+  "compose",
+  "concat",
+  "constantly",
+  "contains",
+  "create-capability-guard",
+  "create-capability-pact-guard",
+  "create-module-guard",
+  "create-pact-guard",
+  "create-principal",
+  "create-user-guard",
+  "days",
+  "dec",
+  "diff-time",
+  "distinct",
+  "drop",
+  "enforce",
+  "enforce-guard",
+  "enforce-keyset",
+  "enforce-one",
+  "enforce-pact-version",
+  "enumerate",
+  "exp",
+  "filter",
+  "floor",
+  "fold",
+  "format",
+  "format-time",
+  "hash",
+  "hash-keccak256",
+  "hours",
+  "identity",
+  "int-to-str",
+  "is-charset",
+  "is-principal",
+  "keys-2",
+  "keys-all",
+  "keys-any",
+  "keyset-ref-guard",
+  "length",
+  "list",
+  "ln",
+  "log",
+  "make-list",
+  "map",
+  "minutes",
+  "mod",
+  "not",
+  "not?",
+  "or",
+  "or?",
+  "pact-id",
+  "pact-version",
+  "parse-time",
+  "read-decimal",
+  "read-integer",
+  "read-keyset",
+  "read-msg",
+  "read-string",
+  "remove",
+  "reverse",
+  "round",
+  "shift",
+  "sort",
+  "sqrt",
+  "str-to-int",
+  "str-to-list",
+  "take",
+  "time",
+  "tx-hash",
+  "typeof",
+  "typeof-principal",
+  "validate-principal",
+  "where",
+  "xor",
+  "zip",
+];
+
+/// Builtins that check a guard, which a signer satisfies by scoping the capability in force
+const GUARD_BUILTINS: &[&str] = &["enforce-guard", "enforce-keyset"];
+
+/// Effect of a defun body, and the names it calls whose effect depends on other definitions
+pub fn body_effect(body: &[Node], source: &str) -> (PactEffect, Vec<String>) {
+  let mut effect = PactEffect::Pure;
+  let mut calls = Vec::new();
+  walk(body, &mut |node| {
+    let found = match node.kind() {
+      "with_capability" | "emit_event" | "resume" => PactEffect::Mutating,
+      "with_read" | "with_default_read" => PactEffect::ReadOnly,
+      "s_expression" => {
+        let head = head(node, source);
+        calls.extend(function_arguments(head, &arguments(node), source));
+        builtin_effect(head).unwrap_or_else(|| {
+          calls.push(head.to_string());
+          PactEffect::Pure
+        })
+      }
+      _ => PactEffect::Pure,
+    };
+    effect = effect.max(found);
+  });
+  (effect, calls)
+}
+
+fn builtin_effect(name: &str) -> Option<PactEffect> {
+  if MUTATING_BUILTINS.contains(&name) {
+    Some(PactEffect::Mutating)
+  } else if READ_BUILTINS.contains(&name) {
+    Some(PactEffect::ReadOnly)
+  } else if PURE_BUILTINS.contains(&name) {
+    Some(PactEffect::Pure)
+  } else {
+    None
+  }
+}
+
+/// Functions passed by name to a higher-order builtin, e.g. `debit` in `(map debit accounts)`
+fn function_arguments(head: &str, args: &[Node], source: &str) -> Vec<String> {
+  let positions: &[usize] = match head {
+    "map" | "filter" | "fold" | "zip" | "not?" => &[0],
+    "compose" | "and?" | "or?" => &[0, 1],
+    "where" => &[1],
+    _ => &[],
+  };
+  positions
+    .iter()
+    .filter_map(|&i| args.get(i))
+    .filter(|arg| matches!(arg.kind(), "reference" | "ident"))
+    .map(|arg| text(*arg, source))
+    .filter(|name| builtin_effect(name) != Some(PactEffect::Pure))
+    .map(ToString::to_string)
+    .collect()
+}
+
+/// Capabilities a body acquires with `with-capability` or checks with `require-capability`,
+/// with their arguments as written in `source`
+pub fn capability_requirements(body: &[Node], source: &str) -> Vec<CapabilityRequirement> {
+  let mut requirements = Vec::new();
+  walk(body, &mut |node| {
+    let (capability, acquired) = match node.kind() {
+      "with_capability" => (field(node, "capability"), true),
+      "s_expression" if head(node, source) == "require-capability" => {
+        (arguments(node).first().copied(), false)
+      }
+      _ => return,
+    };
+    let Some(capability) = capability.filter(|capability| capability.kind() == "s_expression")
+    else {
+      return;
+    };
+    let name = head(capability, source);
+    if name.is_empty() {
+      return;
+    }
+    requirements.push(CapabilityRequirement {
+      name: name.to_string(),
+      args: arguments(capability)
+        .into_iter()
+        .map(|arg| text(arg, source).to_string())
+        .collect(),
+      acquired,
+      span: Some(node_span(capability)),
+    });
+  });
+  requirements
+}
+
+/// A call of something other than a builtin, with its arguments as written
+#[derive(Debug, Clone)]
+pub struct Call {
+  pub name: String,
+  pub args: Vec<String>,
+  pub span: SourceSpan,
+}
+
+/// Calls a body makes, in source order, for following what the callees require
+pub fn body_calls(body: &[Node], source: &str) -> Vec<Call> {
+  let mut calls = Vec::new();
+  walk(body, &mut |node| {
+    if node.kind() != "s_expression" {
+      return;
+    }
+    let name = head(node, source);
+    if name.is_empty() || builtin_effect(name).is_some() {
+      return;
+    }
+    calls.push(Call {
+      name: name.to_string(),
+      args: arguments(node)
+        .into_iter()
+        .map(|arg| text(arg, source).to_string())
+        .collect(),
+      span: node_span(node),
+    });
+  });
+  calls
+}
+
+/// Whether a body checks a guard itself, and the names it calls or composes, whose bodies may
+/// check one in turn
+pub fn body_guard(body: &[Node], source: &str) -> (bool, Vec<String>) {
+  let mut guarded = false;
+  let mut calls = Vec::new();
+  walk(body, &mut |node| {
+    if node.kind() != "s_expression" {
+      return;
+    }
+    let head = head(node, source);
+    if GUARD_BUILTINS.contains(&head) {
+      guarded = true;
+    } else if head == "compose-capability" {
+      let composed = arguments(node)
+        .first()
+        .filter(|capability| capability.kind() == "s_expression")
+        .map(|capability| self::head(*capability, source).to_string());
+      calls.extend(composed);
+    } else if builtin_effect(head).is_none() {
+      calls.push(head.to_string());
+    }
+  });
+  (guarded, calls)
+}
+
+/// Visit the named nodes of `body` and everything under them, in source order
+fn walk<'a>(body: &[Node<'a>], visit: &mut impl FnMut(Node<'a>)) {
+  for node in body {
+    // The cursor can't leave the node it starts at, so the walk ends back there
+    let mut cursor = node.walk();
+    'nodes: loop {
+      if cursor.node().is_named() {
+        visit(cursor.node());
+      }
+      if cursor.goto_first_child() {
+        continue;
+      }
+      while !cursor.goto_next_sibling() {
+        if !cursor.goto_parent() {
+          break 'nodes;
+        }
+      }
+    }
+  }
+}
+
+fn text<'s>(node: Node, source: &'s str) -> &'s str {
+  node.utf8_text(source.as_bytes()).unwrap_or_default()
+}
+
+/// Function or capability an application calls, e.g. `coin.transfer`
+fn head<'s>(node: Node, source: &'s str) -> &'s str {
+  node
+    .child_by_field_name("head")
+    .map_or("", |head| text(head, source))
+}
+
+/// Arguments of an application
+fn arguments(node: Node<'_>) -> Vec<Node<'_>> {
+  let mut cursor = node.walk();
+  node
+    .children_by_field_name("tail", &mut cursor)
+    .filter(|arg| arg.is_named() && arg.kind() != "comment")
+    .collect()
+}
+
+/// First expression of a field, e.g. the capability of a `with-capability`
+fn field<'a>(node: Node<'a>, name: &str) -> Option<Node<'a>> {
+  let mut cursor = node.walk();
+  let found = node
+    .children_by_field_name(name, &mut cursor)
+    .find(|child| child.is_named() && child.kind() != "comment");
+  found
+}
+
+/// Mark the capabilities that check a guard, following the functions and capabilities of
+/// the same module that their bodies use. `functions` and `capabilities` hold what
+/// [`body_guard`] found for each definition, in module order.
+pub fn resolve_guards(
+  module: &mut PactModule,
+  functions: &[(bool, Vec<String>)],
+  capabilities: &[(bool, Vec<String>)],
+) {
+  let qualifiers = module_qualifiers(module);
+  // Functions come first, then capabilities
+  let index_of = |name: &str| {
+    let name = match name.rsplit_once('.') {
+      Some((qualifier, name)) if qualifiers.iter().flatten().any(|q| q == qualifier) => name,
+      Some(_) => return None,
+      None => name,
+    };
+    module
+      .functions
+      .iter()
+      .position(|function| function.name == name)
+      .or_else(|| {
+        module
+          .capabilities
+          .iter()
+          .position(|capability| capability.name == name)
+          .map(|index| module.functions.len() + index)
+      })
+  };
+  let definitions: Vec<(bool, Vec<usize>)> = functions
+    .iter()
+    .chain(capabilities)
+    .map(|(guarded, calls)| {
+      (
+        *guarded,
+        calls.iter().filter_map(|name| index_of(name)).collect(),
+      )
+    })
+    .collect();
+
+  let mut guarded: Vec<bool> = definitions.iter().map(|(guarded, _)| *guarded).collect();
+  let mut changed = true;
+  while changed {
+    changed = false;
+    for (index, (_, callees)) in definitions.iter().enumerate() {
+      if !guarded[index] && callees.iter().any(|&callee| guarded[callee]) {
+        guarded[index] = true;
+        changed = true;
+      }
+    }
+  }
+
+  let offset = module.functions.len();
+  for (capability, guarded) in module.capabilities.iter_mut().zip(&guarded[offset..]) {
+    capability.guarded = *guarded;
+  }
+}
+
+/// Add the capabilities each function's local callees acquire and require to its own, with
+/// the callee's parameters replaced by the arguments of the call. `calls` holds what
+/// [`body_calls`] found for each function, in module order. Inherited requirements point at
+/// the call, so they sort into the caller's source order.
+pub fn resolve_requirements(module: &mut PactModule, calls: &[Vec<Call>]) {
+  let qualifiers = module_qualifiers(module);
+  let local_calls: Vec<Vec<(usize, &Call)>> = calls
+    .iter()
+    .map(|called| {
+      called
+        .iter()
+        .filter_map(|call| {
+          let name = match call.name.rsplit_once('.') {
+            Some((qualifier, name)) if qualifiers.iter().flatten().any(|q| q == qualifier) => name,
+            Some(_) => return None,
+            None => call.name.as_str(),
+          };
+          let callee = module.functions.iter().position(|f| f.name == name)?;
+          Some((callee, call))
+        })
+        .collect()
+    })
+    .collect();
+
+  // Every round follows calls one level deeper. Arguments are only ever replaced by ones
+  // written at a call, so there are finitely many requirements and recursion ends too.
+  let mut changed = true;
+  while changed {
+    changed = false;
+    for (caller, called) in local_calls.iter().enumerate() {
+      for &(callee, call) in called {
+        let inherited: Vec<CapabilityRequirement> = module.functions[callee]
+          .capabilities
+          .iter()
+          .map(|requirement| CapabilityRequirement {
+            args: requirement
+              .args
+              .iter()
+              .map(|arg| {
+                module.functions[callee]
+                  .parameters
+                  .iter()
+                  .position(|param| &param.name == arg)
+                  .and_then(|index| call.args.get(index))
+                  .unwrap_or(arg)
+                  .clone()
+              })
+              .collect(),
+            span: Some(call.span),
+            ..requirement.clone()
+          })
+          .collect();
+        let requirements = &mut module.functions[caller].capabilities;
+        for requirement in inherited {
+          let known = requirements.iter().any(|r| {
+            r.name == requirement.name
+              && r.args == requirement.args
+              && r.acquired == requirement.acquired
+          });
+          if !known {
+            requirements.push(requirement);
+            changed = true;
+          }
+        }
+      }
+    }
+  }
+
+  for function in &mut module.functions {
+    function
+      .capabilities
+      .sort_by_key(|requirement| requirement.span.map(|span| span.start_byte));
+  }
+}
+
+/// Names a module's own definitions can be qualified with, e.g. `coin` and `free.coin`
+fn module_qualifiers(module: &PactModule) -> [Option<String>; 2] {
+  [
+    Some(module.name.clone()),
+    module
+      .namespace
+      .as_ref()
+      .map(|namespace| format!("{namespace}.{}", module.name)),
+  ]
+}
+
+/// Raise each function to the effect of the functions it calls. Functions of the same
+/// module are followed; anything else, such as a call into another module or a name
+/// imported with `use`, can't be, so it counts as mutating.
+pub fn resolve_effects(module: &mut PactModule, calls: &[Vec<String>]) {
+  let qualifiers = module_qualifiers(module);
+  let index_of = |name: &str| {
+    module
+      .functions
+      .iter()
+      .position(|function| function.name == name)
+  };
+  let mut callees = Vec::with_capacity(calls.len());
+  let mut external = Vec::with_capacity(calls.len());
+  for called in calls {
+    let mut local = Vec::new();
+    let mut calls_out = false;
+    for name in called {
+      let name = match name.rsplit_once('.') {
+        Some((qualifier, name)) if qualifiers.iter().flatten().any(|q| q == qualifier) => name,
+        Some(_) => {
+          calls_out = true;
+          continue;
+        }
+        None => name.as_str(),
+      };
+      match index_of(name) {
+        Some(index) => local.push(index),
+        None if builtin_effect(name).is_some() => {}
+        None => calls_out = true,
+      }
+    }
+    callees.push(local);
+    external.push(calls_out);
+  }
+
+  for (function, calls_out) in module.functions.iter_mut().zip(external) {
+    if calls_out {
+      function.effect = PactEffect::Mutating;
+    }
+  }
+  // Propagate through local calls until nothing changes; effects only ever rise
+  let mut changed = true;
+  while changed {
+    changed = false;
+    for (caller, callees) in callees.iter().enumerate() {
+      let effect = callees
+        .iter()
+        .map(|&callee| module.functions[callee].effect)
+        .fold(module.functions[caller].effect, PactEffect::max);
+      if effect != module.functions[caller].effect {
+        module.functions[caller].effect = effect;
+        changed = true;
+      }
+    }
+  }
+}
@@ -6,14 +6,38 @@ use serde::{Deserialize, Serialize};
 pub struct PactModule {
   pub name: String,
   pub namespace: Option<String>,
+  /// Namespace expression evaluated at deploy time, e.g. `(read-msg 'ns)`
+  pub dynamic_namespace: Option<String>,
   pub doc: Option<String>,
   pub governance: String,
   pub functions: Vec<PactFunction>,
   pub capabilities: Vec<PactCapability>,
   pub schemas: Vec<PactSchema>,
   pub constants: Vec<PactConstant>,
-  pub uses: Vec<String>,
+  pub tables: Vec<PactTable>,
+  pub uses: Vec<PactUse>,
   pub implements: Vec<String>,
+  pub span: Option<SourceSpan>,
+}
+
+/// A Pact interface declaration
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactInterface {
+  pub name: String,
+  pub namespace: Option<String>,
+  /// Namespace expression evaluated at deploy time, e.g. `(read-msg 'ns)`
+  pub dynamic_namespace: Option<String>,
+  pub doc: Option<String>,
+  /// Function and pact signatures; bodies are always empty
+  pub functions: Vec<PactFunction>,
+  pub capabilities: Vec<PactCapability>,
+  pub schemas: Vec<PactSchema>,
+  pub constants: Vec<PactConstant>,
+  /// Entries of the interface-level `@model [...]` blocks
+  pub models: Vec<String>,
+  pub uses: Vec<PactUse>,
+  pub span: Option<SourceSpan>,
 }
 
 #[napi(object)]
@@ -25,6 +49,63 @@ pub struct PactFunction {
   pub return_type: Option<String>,
   pub body: String,
   pub is_defun: bool,
+  /// Ordered `step`/`step-with-rollback` entries; empty for defuns
+  pub steps: Vec<PactStep>,
+  /// What calling the function does to chain state, including through the functions it calls
+  pub effect: PactEffect,
+  /// Capabilities the body acquires or requires, in source order
+  pub capabilities: Vec<CapabilityRequirement>,
+  /// Typed body, only populated when parsing with `expressions`
+  pub body_exprs: Option<Vec<PactExpr>>,
+  pub span: Option<SourceSpan>,
+}
+
+/// A capability a function body brings into scope or checks for
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityRequirement {
+  /// Name as written, e.g. `TRANSFER` or `coin.TRANSFER`
+  pub name: String,
+  /// Argument expressions as written, e.g. `sender` or `1.0`
+  pub args: Vec<String>,
+  /// `with-capability`, which the signer must sign for, rather than `require-capability`
+  pub acquired: bool,
+  pub span: Option<SourceSpan>,
+}
+
+/// How a function touches chain state, from least to most
+#[napi(string_enum = "kebab-case")]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PactEffect {
+  /// Computes from its arguments and the transaction alone
+  Pure,
+  /// Reads tables or keysets but never writes; can run with `local` or `dirtyRead`
+  ReadOnly,
+  /// Writes tables, acquires or requires capabilities, emits events or calls other modules;
+  /// needs a signed transaction
+  Mutating,
+}
+
+/// A single step of a defpact
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactStep {
+  /// Entity hint for private pacts, e.g. `(step alice ...)`
+  pub entity: Option<String>,
+  pub expr: String,
+  pub rollback: Option<String>,
+  /// Whether the step calls `yield`
+  pub yields: bool,
+  /// Keys of the object literal passed to `yield`
+  pub yield_fields: Vec<String>,
+  /// Target chain argument of a cross-chain `yield`
+  pub yield_chain: Option<String>,
+  /// Whether the step calls `resume`
+  pub resumes: bool,
+  /// Keys bound by `resume`
+  pub resume_fields: Vec<String>,
+  pub span: Option<SourceSpan>,
 }
 
 #[napi(object)]
@@ -36,6 +117,12 @@ pub struct PactCapability {
   pub return_type: Option<String>,
   pub managed: Option<ManagedInfo>,
   pub is_event: bool,
+  /// Whether the body checks a guard, itself or through the functions and capabilities it
+  /// uses, so that a signer has to scope the capability
+  pub guarded: bool,
+  /// Typed body, only populated when parsing with `expressions`
+  pub body_exprs: Option<Vec<PactExpr>>,
+  pub span: Option<SourceSpan>,
 }
 
 #[napi(object)]
@@ -51,6 +138,7 @@ pub struct PactSchema {
   pub name: String,
   pub doc: Option<String>,
   pub fields: Vec<SchemaField>,
+  pub span: Option<SourceSpan>,
 }
 
 #[napi(object)]
@@ -58,6 +146,7 @@ pub struct PactSchema {
 pub struct SchemaField {
   pub name: String,
   pub field_type: String,
+  pub span: Option<SourceSpan>,
 }
 
 #[napi(object)]
@@ -67,6 +156,7 @@ pub struct PactConstant {
   pub doc: Option<String>,
   pub constant_type: Option<String>,
   pub value: String,
+  pub span: Option<SourceSpan>,
 }
 
 #[napi(object)]
@@ -74,6 +164,193 @@ pub struct PactConstant {
 pub struct PactParameter {
   pub name: String,
   pub parameter_type: Option<String>,
+  pub span: Option<SourceSpan>,
+}
+
+/// A node of a function, pact or capability body
+///
+/// How `value`, `bindings` and `children` are used depends on `kind`:
+/// - `Application`: `value` is the head (`coin.transfer`), `children` the arguments
+/// - `Let`/`LetStar`: `bindings` carry the bound values, `children` the body
+/// - `Bind`: `children[0]` is the source object, then the body; `bindings` from the binding map
+/// - `WithCapability`: `children[0]` is the capability application, then the body
+/// - `WithRead`: `value` is the table, `children[0]` the key, then the body
+/// - `WithDefaultRead`: like `WithRead` with the default object as `children[1]`
+/// - `Lambda`/`Resume`: `bindings` are the parameters or resumed keys, `children` the body
+/// - `Step`: `value` is the entity if any, `children` the step and optional rollback expression
+/// - `Object`: one binding per pair, keyed by the property name
+/// - literals and `Reference`: `value` is the source text
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactExpr {
+  pub kind: PactExprKind,
+  pub value: Option<String>,
+  pub bindings: Vec<PactBinding>,
+  pub children: Vec<PactExpr>,
+  pub span: Option<SourceSpan>,
+}
+
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PactExprKind {
+  Application,
+  Let,
+  LetStar,
+  Bind,
+  WithCapability,
+  WithRead,
+  WithDefaultRead,
+  If,
+  Cond,
+  Try,
+  Lambda,
+  Resume,
+  EmitEvent,
+  Step,
+  Integer,
+  Decimal,
+  String,
+  Boolean,
+  Symbol,
+  List,
+  Object,
+  Reference,
+  /// Anything the grammar produced that has no typed form, e.g. error nodes
+  Unknown,
+}
+
+/// A name introduced by a binding form, or a property of an object literal
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactBinding {
+  pub name: String,
+  /// Object key the name is read from, e.g. `"balance"` in `{ "balance" := bal }`
+  pub key: Option<String>,
+  pub binding_type: Option<String>,
+  pub value: Option<PactExpr>,
+  pub span: Option<SourceSpan>,
+}
+
+impl PactEffect {
+  /// Name used in generated code, e.g. `read-only`
+  pub fn as_str(self) -> &'static str {
+    match self {
+      PactEffect::Pure => "pure",
+      PactEffect::ReadOnly => "read-only",
+      PactEffect::Mutating => "mutating",
+    }
+  }
+}
+
+impl PactExpr {
+  /// Visit this expression and all of its descendants, including binding values
+  pub fn walk<'a>(&'a self, visit: &mut impl FnMut(&'a PactExpr)) {
+    visit(self);
+    for binding in &self.bindings {
+      if let Some(value) = &binding.value {
+        value.walk(visit);
+      }
+    }
+    for child in &self.children {
+      child.walk(visit);
+    }
+  }
+}
+
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactTable {
+  pub name: String,
+  /// Schema name from `(deftable name:{schema})`
+  pub schema: Option<String>,
+  pub doc: Option<String>,
+  pub span: Option<SourceSpan>,
+}
+
+/// A `(use ...)` import
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactUse {
+  /// Module name without its namespace
+  pub module: String,
+  pub namespace: Option<String>,
+  /// Pinned module hash from `(use coin "hash")`
+  pub hash: Option<String>,
+  /// Symbols from an import list; `None` imports everything
+  pub imports: Option<Vec<String>>,
+  pub span: Option<SourceSpan>,
+}
+
+/// Location of a node in the Pact source
+///
+/// Lines are 1-based and columns are 0-based, matching the source map conventions.
+#[napi(object)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceSpan {
+  pub start_byte: u32,
+  pub end_byte: u32,
+  pub start_line: u32,
+  pub start_column: u32,
+  pub end_line: u32,
+  pub end_column: u32,
+}
+
+impl PactInterface {
+  pub fn new(name: String, namespace: Option<String>) -> Self {
+    Self {
+      name,
+      namespace,
+      dynamic_namespace: None,
+      doc: None,
+      functions: Vec::new(),
+      capabilities: Vec::new(),
+      schemas: Vec::new(),
+      constants: Vec::new(),
+      models: Vec::new(),
+      uses: Vec::new(),
+      span: None,
+    }
+  }
+
+  /// Whether an `implements` reference such as `fungible-v2` or
+  /// `free.fungible-v2` in `module` points at this interface
+  ///
+  /// Qualified references need the same namespace; bare ones resolve in the
+  /// module's own namespace and then the root one, as Pact does.
+  pub fn matches_reference(&self, reference: &str, module: &PactModule) -> bool {
+    if let Some((namespace, name)) = reference.rsplit_once('.') {
+      return name == self.name && self.namespace.as_deref() == Some(namespace);
+    }
+    let root = self.namespace.is_none() && self.dynamic_namespace.is_none();
+    let same_scope =
+      self.namespace == module.namespace && self.dynamic_namespace == module.dynamic_namespace;
+    reference == self.name && (root || same_scope)
+  }
+}
+
+impl PactUse {
+  /// Unpinned, unrestricted import of a reference such as `coin` or `free.util`
+  pub fn new(reference: &str) -> Self {
+    let (namespace, module) = match reference.rsplit_once('.') {
+      Some((namespace, module)) => (Some(namespace.to_string()), module.to_string()),
+      None => (None, reference.to_string()),
+    };
+    Self {
+      module,
+      namespace,
+      hash: None,
+      imports: None,
+      span: None,
+    }
+  }
+
+  /// Fully qualified reference as written in the source
+  pub fn reference(&self) -> String {
+    match &self.namespace {
+      Some(namespace) => format!("{namespace}.{}", self.module),
+      None => self.module.clone(),
+    }
+  }
 }
 
 impl PactModule {
@@ -82,14 +359,17 @@ impl PactModule {
     Self {
       name,
       namespace: None,
+      dynamic_namespace: None,
       doc: None,
       governance,
       functions: Vec::new(),
       capabilities: Vec::new(),
       schemas: Vec::new(),
       constants: Vec::new(),
+      tables: Vec::new(),
       uses: Vec::new(),
       implements: Vec::new(),
+      span: None,
     }
   }
 
@@ -97,14 +377,17 @@ impl PactModule {
     Self {
       name,
       namespace,
+      dynamic_namespace: None,
       doc: None,
       governance,
       functions: Vec::new(),
       capabilities: Vec::new(),
       schemas: Vec::new(),
       constants: Vec::new(),
+      tables: Vec::new(),
       uses: Vec::new(),
       implements: Vec::new(),
+      span: None,
     }
   }
 
@@ -129,8 +412,22 @@ impl PactModule {
   }
 
   #[cfg(test)]
-  pub fn add_use(&mut self, module: String) {
-    self.uses.push(module);
+  pub fn add_table(&mut self, table: PactTable) {
+    self.tables.push(table);
+  }
+
+  /// Resolve the schema a table is declared with
+  pub fn table_schema(&self, table: &PactTable) -> Option<&PactSchema> {
+    let schema_name = table.schema.as_deref()?;
+    self
+      .schemas
+      .iter()
+      .find(|schema| schema.name == schema_name)
+  }
+
+  #[cfg(test)]
+  pub fn add_use(&mut self, reference: &str) {
+    self.uses.push(PactUse::new(reference));
   }
 
   #[cfg(test)]
@@ -155,6 +452,7 @@ mod tests {
     assert_eq!(module.capabilities.len(), 0);
     assert_eq!(module.schemas.len(), 0);
     assert_eq!(module.constants.len(), 0);
+    assert_eq!(module.tables.len(), 0);
     assert_eq!(module.uses.len(), 0);
     assert_eq!(module.implements.len(), 0);
   }
@@ -193,6 +491,11 @@ mod tests {
       return_type: Some("string".to_string()),
       body: "test".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
 
     module.add_function(function);
@@ -215,6 +518,9 @@ mod tests {
       return_type: Some("bool".to_string()),
       managed: None,
       is_event: false,
+      guarded: false,
+      span: None,
+      body_exprs: None,
     };
 
     module.add_capability(capability);
@@ -240,12 +546,15 @@ mod tests {
         SchemaField {
           name: "id".to_string(),
           field_type: "string".to_string(),
+          span: None,
         },
         SchemaField {
           name: "name".to_string(),
           field_type: "string".to_string(),
+          span: None,
         },
       ],
+      span: None,
     };
 
     module.add_schema(schema);
@@ -269,6 +578,7 @@ mod tests {
       doc: Some("Maximum supply".to_string()),
       constant_type: Some("integer".to_string()),
       value: "1000000".to_string(),
+      span: None,
     };
 
     module.add_constant(constant);
@@ -283,16 +593,67 @@ mod tests {
     assert_eq!(module.constants[0].value, "1000000");
   }
 
+  #[test]
+  fn test_interface_matches_reference() {
+    let root_module = PactModule::new("coin".to_string(), "governance".to_string());
+    let mut free_module = PactModule::new("token".to_string(), "governance".to_string());
+    free_module.namespace = Some("free".to_string());
+
+    let global = PactInterface::new("fungible-v2".to_string(), None);
+    assert!(global.matches_reference("fungible-v2", &root_module));
+    assert!(global.matches_reference("fungible-v2", &free_module));
+    assert!(!global.matches_reference("free.fungible-v2", &root_module));
+    assert!(!global.matches_reference("fungible-xchain-v1", &root_module));
+
+    let namespaced = PactInterface::new("token".to_string(), Some("free".to_string()));
+    assert!(namespaced.matches_reference("token", &free_module));
+    assert!(!namespaced.matches_reference("token", &root_module));
+    assert!(namespaced.matches_reference("free.token", &root_module));
+    assert!(!namespaced.matches_reference("user.token", &free_module));
+  }
+
+  #[test]
+  fn test_table_schema_resolution() {
+    let mut module = PactModule::new("test".to_string(), "GOVERNANCE".to_string());
+
+    module.add_schema(PactSchema {
+      name: "account".to_string(),
+      doc: None,
+      fields: vec![],
+      span: None,
+    });
+    module.add_table(PactTable {
+      name: "accounts".to_string(),
+      schema: Some("account".to_string()),
+      doc: None,
+      span: None,
+    });
+    module.add_table(PactTable {
+      name: "orphans".to_string(),
+      schema: Some("missing".to_string()),
+      doc: None,
+      span: None,
+    });
+
+    assert_eq!(
+      module
+        .table_schema(&module.tables[0])
+        .map(|schema| schema.name.as_str()),
+      Some("account")
+    );
+    assert!(module.table_schema(&module.tables[1]).is_none());
+  }
+
   #[test]
   fn test_add_use() {
     let mut module = PactModule::new("test".to_string(), "GOVERNANCE".to_string());
 
-    module.add_use("coin".to_string());
-    module.add_use("util.guards".to_string());
+    module.add_use("coin");
+    module.add_use("util.guards");
 
     assert_eq!(module.uses.len(), 2);
-    assert_eq!(module.uses[0], "coin");
-    assert_eq!(module.uses[1], "util.guards");
+    assert_eq!(module.uses[0].reference(), "coin");
+    assert_eq!(module.uses[1].reference(), "util.guards");
   }
 
   #[test]
@@ -316,19 +677,27 @@ mod tests {
         PactParameter {
           name: "from".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "to".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "amount".to_string(),
           parameter_type: Some("decimal".to_string()),
+          span: None,
         },
       ],
       return_type: Some("string".to_string()),
       body: "(transfer-create from to amount)".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
 
     assert_eq!(function.name, "transfer");
@@ -361,6 +730,7 @@ mod tests {
       parameters: vec![PactParameter {
         name: "from".to_string(),
         parameter_type: Some("string".to_string()),
+        span: None,
       }],
       return_type: Some("bool".to_string()),
       managed: Some(ManagedInfo {
@@ -368,6 +738,9 @@ mod tests {
         manager_function: Some("transfer-mgr".to_string()),
       }),
       is_event: false,
+      guarded: false,
+      span: None,
+      body_exprs: None,
     };
 
     assert_eq!(capability.name, "TRANSFER");
@@ -391,6 +764,9 @@ mod tests {
       return_type: None,
       managed: None,
       is_event: true,
+      guarded: false,
+      span: None,
+      body_exprs: None,
     };
 
     assert_eq!(capability.name, "TRANSFER_EVENT");
@@ -406,6 +782,7 @@ mod tests {
     let parameter = PactParameter {
       name: "untyped".to_string(),
       parameter_type: None,
+      span: None,
     };
 
     assert_eq!(parameter.name, "untyped");
@@ -417,6 +794,7 @@ mod tests {
     let parameter = PactParameter {
       name: "typed".to_string(),
       parameter_type: Some("string".to_string()),
+      span: None,
     };
 
     assert_eq!(parameter.name, "typed");
@@ -428,6 +806,7 @@ mod tests {
     let field = SchemaField {
       name: "user-id".to_string(),
       field_type: "string".to_string(),
+      span: None,
     };
 
     assert_eq!(field.name, "user-id");
@@ -441,6 +820,7 @@ mod tests {
       doc: Some("Admin keyset".to_string()),
       constant_type: None,
       value: "\"admin-keyset\"".to_string(),
+      span: None,
     };
 
     assert_eq!(constant.name, "ADMIN_KEYSET");
@@ -456,6 +836,7 @@ mod tests {
       doc: None,
       constant_type: Some("integer".to_string()),
       value: "1000000".to_string(),
+      span: None,
     };
 
     assert_eq!(constant.name, "MAX_SUPPLY");
@@ -495,8 +876,8 @@ mod tests {
     );
 
     // Add uses
-    module.add_use("coin".to_string());
-    module.add_use("util.guards".to_string());
+    module.add_use("coin");
+    module.add_use("util.guards");
 
     // Add implements
     module.add_implements("fungible-v2".to_string());
@@ -509,12 +890,15 @@ mod tests {
         SchemaField {
           name: "balance".to_string(),
           field_type: "decimal".to_string(),
+          span: None,
         },
         SchemaField {
           name: "guard".to_string(),
           field_type: "guard".to_string(),
+          span: None,
         },
       ],
+      span: None,
     };
     module.add_schema(account_schema);
 
@@ -524,6 +908,7 @@ mod tests {
       doc: Some("Token decimals".to_string()),
       constant_type: Some("integer".to_string()),
       value: "12".to_string(),
+      span: None,
     };
     module.add_constant(decimals_constant);
 
@@ -535,14 +920,17 @@ mod tests {
         PactParameter {
           name: "from".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "to".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "amount".to_string(),
           parameter_type: Some("decimal".to_string()),
+          span: None,
         },
       ],
       return_type: Some("bool".to_string()),
@@ -551,6 +939,9 @@ mod tests {
         manager_function: Some("transfer-mgr".to_string()),
       }),
       is_event: false,
+      guarded: false,
+      span: None,
+      body_exprs: None,
     };
     module.add_capability(transfer_cap);
 
@@ -562,19 +953,27 @@ mod tests {
         PactParameter {
           name: "from".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "to".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "amount".to_string(),
           parameter_type: Some("decimal".to_string()),
+          span: None,
         },
       ],
       return_type: Some("string".to_string()),
       body: "(transfer-create from to amount)".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module.add_function(transfer_function);
 
@@ -590,8 +989,8 @@ mod tests {
     assert_eq!(module.functions.len(), 1);
 
     // Verify specific details
-    assert_eq!(module.uses[0], "coin");
-    assert_eq!(module.uses[1], "util.guards");
+    assert_eq!(module.uses[0].reference(), "coin");
+    assert_eq!(module.uses[1].reference(), "util.guards");
     assert_eq!(module.implements[0], "fungible-v2");
     assert_eq!(module.schemas[0].name, "account");
     assert_eq!(module.schemas[0].fields.len(), 2);
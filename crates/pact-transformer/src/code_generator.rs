@@ -1,14 +1,104 @@
 use crate::ast::*;
+use crate::emitter::{Emitter, ExportKind};
+use crate::error::{Diagnostic, DiagnosticCode};
+use crate::identifier::{
+  doc_with_pact_name, field_names, js_identifier, parameter_names, type_identifier, JsNames,
+  ModuleNames,
+};
+use crate::literal::PactLiteral;
 use crate::plugin::get_plugin_manager;
-use crate::source_map::{SourceMapGenerator, SourceMapOptions};
-use crate::types::{convert_to_jsdoc, pact_type_to_typescript};
+use crate::source_map::{utf16_column, SourceMapGenerator, SourceMapOptions, SourceMark};
+use crate::transformer::TransformOptions;
+use crate::types::{convert_to_jsdoc, TypeMapper};
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 
+#[allow(clippy::struct_excessive_bools)]
 pub struct CodeGenerator {
   generate_types: bool,
   source_map_gen: Option<SourceMapGenerator>,
   source_file_path: Option<String>,
+  interfaces: Vec<PactInterface>,
+  namespace: Option<String>,
+  validators: bool,
+  runtime: RuntimeOptions,
+  namespaced_exports: bool,
+  typescript: bool,
+  type_mapper: TypeMapper,
+  diagnostics: Vec<Diagnostic>,
+  names: Vec<JsNames>,
+}
+
+/// Where generated code imports the transaction runtime from and how it exports its bindings
+#[derive(Debug, Clone)]
+pub struct RuntimeOptions {
+  /// Module specifier of the runtime package
+  pub import: String,
+  /// Runtime export that builds an execution transaction
+  pub execution: String,
+  /// Runtime export that builds a continuation transaction
+  pub continuation: String,
+  /// Emit `require`/`module.exports` instead of ES module syntax
+  pub commonjs: bool,
+}
+
+impl Default for RuntimeOptions {
+  fn default() -> Self {
+    Self {
+      import: "@pact-toolbox/transaction".to_string(),
+      execution: "execution".to_string(),
+      continuation: "continuation".to_string(),
+      commonjs: false,
+    }
+  }
+}
+
+impl RuntimeOptions {
+  pub fn from_transform_options(options: Option<&TransformOptions>) -> Self {
+    let defaults = Self::default();
+    let Some(options) = options else {
+      return defaults;
+    };
+    Self {
+      import: options.runtime_import.clone().unwrap_or(defaults.import),
+      execution: options
+        .execution_builder
+        .clone()
+        .unwrap_or(defaults.execution),
+      continuation: options
+        .continuation_builder
+        .clone()
+        .unwrap_or(defaults.continuation),
+      commonjs: options.module_format.as_deref() == Some("cjs"),
+    }
+  }
+
+  /// Import of the runtime helpers, renaming builders the runtime exports under other names
+  fn import_statement(&self, names: &[&str]) -> String {
+    let specifier = serde_json::to_string(&self.import).unwrap_or_default();
+    let bindings = names
+      .iter()
+      .map(|&name| {
+        let imported = match name {
+          "execution" => self.execution.as_str(),
+          "continuation" => self.continuation.as_str(),
+          _ => name,
+        };
+        match (imported == name, self.commonjs) {
+          (true, _) => name.to_string(),
+          (false, false) => format!("{imported} as {name}"),
+          (false, true) => format!("{imported}: {name}"),
+        }
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+    if self.commonjs {
+      format!("const {{ {bindings} }} = require({specifier});")
+    } else {
+      format!("import {{ {bindings} }} from {specifier};")
+    }
+  }
 }
 
 /// Generate JavaScript code from modules
@@ -19,23 +109,100 @@ pub fn generate_js(modules: &[PactModule]) -> String {
   code
 }
 
-/// Generate TypeScript types from modules
+/// Generate TypeScript types from modules and the interfaces declared alongside them
 #[allow(dead_code)]
-pub fn generate_types(modules: &[PactModule]) -> String {
-  let mut generator = CodeGenerator::new(true);
+pub fn generate_types(modules: &[PactModule], interfaces: &[PactInterface]) -> String {
+  let mut generator = CodeGenerator::new(true).with_interfaces(interfaces);
   let (_, types, _, _) = generator.generate(modules);
   types
 }
 
+/// Warnings for the constants left out of the output because their values are computed
+pub fn skipped_constants(modules: &[PactModule]) -> Vec<Diagnostic> {
+  modules
+    .iter()
+    .flat_map(|module| &module.constants)
+    .filter(|constant| PactLiteral::parse(&constant.value).is_none())
+    .map(|constant| {
+      Diagnostic::warning(
+        DiagnosticCode::NonLiteralConstant,
+        format!(
+          "`{}` is not exported because its value is not a literal",
+          constant.name
+        ),
+        constant.span.unwrap_or_default(),
+      )
+    })
+    .collect()
+}
+
 impl CodeGenerator {
   pub fn new(generate_types: bool) -> Self {
     Self {
       generate_types,
       source_map_gen: None,
       source_file_path: None,
+      interfaces: Vec::new(),
+      namespace: None,
+      validators: false,
+      runtime: RuntimeOptions::default(),
+      namespaced_exports: false,
+      typescript: false,
+      type_mapper: TypeMapper::default(),
+      diagnostics: Vec::new(),
+      names: Vec::new(),
     }
   }
 
+  /// Interfaces to emit as TypeScript interfaces and to resolve `implements` against
+  #[must_use]
+  pub fn with_interfaces(mut self, interfaces: &[PactInterface]) -> Self {
+    self.interfaces = interfaces.to_vec();
+    self
+  }
+
+  /// Namespace for modules whose `(namespace ...)` is only known at deploy time
+  #[must_use]
+  pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+    self.namespace = namespace;
+    self
+  }
+
+  /// Also emit a runtime validator and type guard for every `defschema`
+  #[must_use]
+  pub fn with_validators(mut self, validators: bool) -> Self {
+    self.validators = validators;
+    self
+  }
+
+  /// Runtime import specifier, builder names and module format of the generated JavaScript
+  #[must_use]
+  pub fn with_runtime(mut self, runtime: RuntimeOptions) -> Self {
+    self.runtime = runtime;
+    self
+  }
+
+  /// Export each module as one object, `free.coin.transfer`, instead of flat declarations
+  #[must_use]
+  pub fn with_namespaced_exports(mut self, namespaced_exports: bool) -> Self {
+    self.namespaced_exports = namespaced_exports;
+    self
+  }
+
+  /// Emit one annotated TypeScript file in place of JavaScript and declarations
+  #[must_use]
+  pub fn with_typescript(mut self, typescript: bool) -> Self {
+    self.typescript = typescript;
+    self
+  }
+
+  /// Pact-to-TypeScript type mapping, with the imports its custom types need
+  #[must_use]
+  pub fn with_type_mapper(mut self, type_mapper: TypeMapper) -> Self {
+    self.type_mapper = type_mapper;
+    self
+  }
+
   pub fn new_with_source_maps(
     generate_types: bool,
     source_map_options: SourceMapOptions,
@@ -57,6 +224,15 @@ impl CodeGenerator {
       generate_types,
       source_map_gen: Some(source_map_gen),
       source_file_path: Some(source_file_path.to_string()),
+      interfaces: Vec::new(),
+      namespace: None,
+      validators: false,
+      runtime: RuntimeOptions::default(),
+      namespaced_exports: false,
+      typescript: false,
+      type_mapper: TypeMapper::default(),
+      diagnostics: Vec::new(),
+      names: Vec::new(),
     }
   }
 
@@ -91,38 +267,131 @@ impl CodeGenerator {
 
     drop(manager_lock); // Release lock before generating code
 
+    self.resolve_dynamic_namespaces(&mut transformed_modules);
+
+    let (names, clashes): (Vec<ModuleNames>, Vec<Vec<Diagnostic>>) =
+      transformed_modules.iter().map(ModuleNames::module).unzip();
+
     // Generate code and types in parallel
-    let (code_parts, type_parts): (Vec<String>, Vec<String>) = transformed_modules
+    let parts: Vec<(Emitter, Emitter)> = transformed_modules
       .par_iter()
-      .map(|module| {
-        let code = Self::generate_module_code(module);
-        let types = if self.generate_types {
-          Self::generate_module_types(module)
-        } else {
-          String::new()
-        };
-        (code, types)
-      })
-      .unzip();
+      .zip(names.par_iter())
+      .map(|(module, names)| self.generate_module(module, names))
+      .collect();
+
+    self.diagnostics = clashes.into_iter().flatten().collect();
+    self
+      .diagnostics
+      .extend(unknown_capabilities(&transformed_modules));
+    if !self.namespaced_exports {
+      self
+        .diagnostics
+        .extend(export_collisions(&transformed_modules, &parts));
+    }
+    self.names = transformed_modules
+      .iter()
+      .zip(&names)
+      .map(|(module, names)| names.to_js_names(module_ref(module)))
+      .collect();
+
+    let (mut code_parts, type_parts): (Vec<Emitter>, Vec<Emitter>) = if self.namespaced_exports {
+      parts
+        .into_iter()
+        .zip(&transformed_modules)
+        .map(|((code, types), module)| {
+          let types = if types.is_empty() {
+            types
+          } else {
+            wrap_module_types(module, types)
+          };
+          (wrap_module_code(module, code, self.commonjs()), types)
+        })
+        .unzip()
+    } else {
+      parts.into_iter().unzip()
+    };
+    if self.namespaced_exports {
+      code_parts.extend(namespace_exports(&transformed_modules, self.commonjs()));
+    }
 
-    let mut code = if code_parts.is_empty() {
-      String::new()
+    let mapper = &self.type_mapper;
+    let interface_parts: Vec<Emitter> = if self.generate_types || self.typescript {
+      self
+        .interfaces
+        .iter()
+        .map(|interface| {
+          let mut types = Emitter::new();
+          Self::generate_interface_types(&mut types, interface, mapper);
+          types
+        })
+        .collect()
     } else {
-      format!(
-                "// This file was generated by the Pact Toolbox\nimport {{ execution, continuation }} from \"@pact-toolbox/transaction\";\n\n{}",
-                code_parts.join("\n\n")
-            )
+      Vec::new()
     };
 
-    let mut types = if self.generate_types && !type_parts.is_empty() {
-      format!(
-                "// This file was generated by the Pact Toolbox\nimport {{ PactTransactionBuilder, PactExecPayload }} from \"@pact-toolbox/transaction\";\n\n{}",
-                type_parts.join("\n\n")
-            )
+    if self.typescript {
+      return self.finish_typescript(
+        &transformed_modules,
+        interface_parts,
+        type_parts,
+        code_parts,
+        js_filename,
+      );
+    }
+
+    let (mut code, js_marks) = if code_parts.is_empty() {
+      (String::new(), Vec::new())
     } else {
-      String::new()
+      let mut helpers = vec![
+        "execution",
+        "continuation",
+        "encodePactArg",
+        "toPactValue",
+        "fromPactValue",
+      ];
+      if self.validators {
+        helpers.push("validatePactObject");
+      }
+      let mut code = Emitter::new().with_scoped_exports(self.commonjs());
+      write!(
+        code,
+        "// This file was generated by the Pact Toolbox\n{}\n{}\n",
+        self.runtime.import_statement(&helpers),
+        self.codec_imports(self.runtime.commonjs),
+      )
+      .unwrap();
+      append_joined(&mut code, code_parts, "\n\n");
+      if self.runtime.commonjs {
+        // The declarations were written without `export`; export what they recorded
+        let names: Vec<&str> = code.exports().iter().map(|e| e.name.as_str()).collect();
+        let exports = format!("\nmodule.exports = {{ {} }};\n", names.join(", "));
+        code.end_line();
+        code.push_str(&exports);
+      }
+      code.finish()
     };
 
+    let (mut types, declaration_marks) =
+      if self.generate_types && !(type_parts.is_empty() && interface_parts.is_empty()) {
+        let mut types = Emitter::new();
+        write!(
+          types,
+          "// This file was generated by the Pact Toolbox\nimport {{ {} }} from {};\n{}\n",
+          self.runtime_types().join(", "),
+          serde_json::to_string(&self.runtime.import).unwrap_or_default(),
+          self.mapped_type_imports(),
+        )
+        .unwrap();
+        append_joined(
+          &mut types,
+          interface_parts.into_iter().chain(type_parts),
+          "\n\n",
+        );
+        types.finish()
+      } else {
+        (String::new(), Vec::new())
+      };
+
     // Apply plugin post-processing
     let plugin_manager = get_plugin_manager();
     let manager_lock = plugin_manager.lock().unwrap();
@@ -131,544 +400,1916 @@ impl CodeGenerator {
       log::warn!("Plugin post-generation error: {e}");
     }
 
-    // Choose generation method based on source map availability
     if self.source_map_gen.is_some() {
-      self.generate_with_source_maps(&transformed_modules, code, types, js_filename, ts_filename)
+      let (source_map, declaration_map) =
+        self.generate_source_maps(&js_marks, &declaration_marks, js_filename, ts_filename);
+      (code, types, source_map, declaration_map)
     } else {
       (code, types, None, None)
     }
   }
 
-  fn generate_with_source_maps(
-    &mut self,
-    modules: &[PactModule],
-    mut js_code: String,
-    mut ts_types: String,
-    js_filename: Option<&str>,
-    ts_filename: Option<&str>,
-  ) -> (String, String, Option<String>, Option<String>) {
-    // If we have source maps, we need to regenerate with position tracking
-    // Clear the existing code and regenerate with source map tracking
-    js_code.clear();
-    ts_types.clear();
+  /// Warnings about the names and capabilities the last [`CodeGenerator::generate`] emitted
+  pub fn diagnostics(&self) -> &[Diagnostic] {
+    &self.diagnostics
+  }
 
-    // Add import statement and track its position
-    let import_line = "// This file was generated by the Pact Toolbox\nimport { execution, continuation } from \"@pact-toolbox/transaction\";\n\n";
-    js_code.push_str(import_line);
+  /// JavaScript names the last [`CodeGenerator::generate`] gave each module's definitions
+  pub fn names(&self) -> &[JsNames] {
+    &self.names
+  }
 
-    if let Some(ref mut gen) = self.source_map_gen {
-      gen.advance_generated_position(import_line);
-    }
+  /// Whether JavaScript output exports through `module.exports`; TypeScript never does
+  fn commonjs(&self) -> bool {
+    self.runtime.commonjs && !self.typescript
+  }
 
-    if self.generate_types {
-      let types_import = "// This file was generated by the Pact Toolbox\nimport { PactTransactionBuilder, PactExecPayload } from \"@pact-toolbox/transaction\";\n\n";
-      ts_types.push_str(types_import);
-      // Note: Don't advance position tracker here - we'll track TypeScript separately
+  /// A module's code and its declarations; inside a namespaced module the code is indented
+  /// for the scope it is wrapped in and the declarations for their `declare namespace`
+  fn generate_module(&self, module: &PactModule, names: &ModuleNames) -> (Emitter, Emitter) {
+    let mapper = &self.type_mapper;
+    let namespaced = self.namespaced_exports;
+
+    let mut types = Emitter::new()
+      .with_indent(if namespaced { "  " } else { "" })
+      .with_ambient(namespaced);
+    if self.typescript {
+      Self::generate_module_declarations(&mut types, module, names, &self.interfaces, mapper);
+    } else if self.generate_types {
+      Self::generate_module_types(
+        &mut types,
+        module,
+        names,
+        &self.interfaces,
+        self.validators,
+        mapper,
+      );
     }
 
-    // Generate code for each module with source mapping
-    for module in modules {
-      self.generate_module_with_source_map(module, &mut js_code, &mut ts_types);
-    }
-
-    // Generate source map and declaration map
-    let (source_map_json, declaration_map_json) = if let Some(ref mut gen) = self.source_map_gen {
-      let source_map = if gen.get_options().generate.unwrap_or(true) {
-        let js_file = js_filename.unwrap_or("generated.js");
-        match gen.generate_json(js_file) {
-          Ok(json) => Some(json),
-          Err(e) => {
-            log::warn!("Failed to generate source map JSON: {e}");
-            None
-          }
-        }
-      } else {
-        None
-      };
-
-      let declaration_map =
-        if self.generate_types && gen.get_options().declaration_map.unwrap_or(false) {
-          let ts_file = ts_filename.unwrap_or("generated.d.ts");
-          match gen.generate_declaration_map(ts_file) {
-            Ok(json) => Some(json),
-            Err(e) => {
-              log::warn!("Failed to generate declaration map JSON: {e}");
-              None
-            }
-          }
-        } else {
-          None
-        };
+    let mut code = Emitter::new()
+      .with_indent(if namespaced { "    " } else { "" })
+      .with_scoped_exports(namespaced || self.commonjs());
+    if namespaced && self.typescript {
+      // Declarations sit beside the code, so the wrapped code aliases the ones it uses
+      let path = module_path(module);
+      let aliases: Vec<&str> = types
+        .exports()
+        .iter()
+        .filter(|export| export.kind == ExportKind::Type)
+        .map(|export| export.name.as_str())
+        .collect();
+      for name in &aliases {
+        writeln!(code, "type {name} = {path}.{name};").unwrap();
+      }
+      if !aliases.is_empty() {
+        code.push('\n');
+      }
+    }
+    Self::generate_module_code(
+      &mut code,
+      module,
+      names,
+      self.validators,
+      self.typescript,
+      mapper,
+    );
 
-      (source_map, declaration_map)
-    } else {
-      (None, None)
-    };
+    (code, types)
+  }
 
-    (js_code, ts_types, source_map_json, declaration_map_json)
+  /// Types the declarations import from the runtime
+  fn runtime_types(&self) -> Vec<&'static str> {
+    let mut names = vec![
+      "PactTransactionBuilder",
+      "PactExecPayload",
+      "PactContPayload",
+      "PactEnvData",
+      "PactValue",
+      "PactCapability",
+      "PactSignerLike",
+      "PactEvent",
+      "PactInteger",
+      "PactDecimal",
+      "PactTimeInput",
+      "PactKeyset",
+      "PactGuard",
+    ];
+    if self.validators {
+      names.push("PactValidationError");
+    }
+    names
   }
 
-  fn generate_module_with_source_map(
-    &mut self,
-    module: &PactModule,
-    js_code: &mut String,
-    ts_types: &mut String,
-  ) {
-    // Generate schemas first with source mapping
-    for schema in &module.schemas {
-      self.generate_schema_with_source_map(module, schema, ts_types);
+  /// Import statements configured for mapped types, one per line
+  fn mapped_type_imports(&self) -> String {
+    let mut imports = String::new();
+    for import in self.type_mapper.imports() {
+      writeln!(imports, "{};", import.trim().trim_end_matches(';')).unwrap();
     }
+    imports
+  }
 
-    // Generate functions with source mapping
-    for function in &module.functions {
-      self.generate_function_with_source_map(module, function, js_code, ts_types);
+  /// Imports of the functions converting mapped types, one per line
+  fn codec_imports(&self, commonjs: bool) -> String {
+    let mut imports = String::new();
+    for (from, functions) in self.type_mapper.codec_imports() {
+      let specifier = serde_json::to_string(from).unwrap_or_default();
+      let functions = functions.join(", ");
+      if commonjs {
+        writeln!(imports, "const {{ {functions} }} = require({specifier});").unwrap();
+      } else {
+        writeln!(imports, "import {{ {functions} }} from {specifier};").unwrap();
+      }
     }
+    imports
   }
 
-  fn generate_schema_with_source_map(
+  /// Assemble the single `.ts` file: runtime imports, type declarations, then annotated code
+  fn finish_typescript(
     &mut self,
-    _module: &PactModule,
-    schema: &PactSchema,
-    ts_types: &mut String,
-  ) {
-    if !self.generate_types {
-      return;
+    modules: &[PactModule],
+    interface_parts: Vec<Emitter>,
+    type_parts: Vec<Emitter>,
+    code_parts: Vec<Emitter>,
+    ts_filename: Option<&str>,
+  ) -> (String, String, Option<String>, Option<String>) {
+    let mut helpers = vec![
+      "execution",
+      "continuation",
+      "encodePactArg",
+      "toPactValue",
+      "fromPactValue",
+    ];
+    if self.validators {
+      helpers.push("validatePactObject");
     }
-
-    // Generate schema with detailed source mapping
-    // Generate JSDoc if present
-    if let Some(doc) = &schema.doc {
-      let doc_comment = format!("/**\n * {doc}\n */\n");
-      ts_types.push_str(&doc_comment);
-    }
-
-    // Generate interface with precise source mapping
-    let pascal_case_name = to_pascal_case(&schema.name);
-
-    // Calculate the correct TypeScript line position for interface
-    let ts_lines = ts_types.matches('\n').count() as u32 + 1;
-    let ts_current_line = &ts_types.lines().last().unwrap_or("");
-    let ts_column = ts_current_line.len() as u32;
-
-    ts_types.push_str("export interface ");
-
-    // Map interface name precisely at its position in TypeScript
-    let interface_name_column = ts_column + "export interface ".len() as u32;
-
-    // Direct position lookup for schema
-    if let Some(ref mut gen) = self.source_map_gen {
-      if let Some(ref file_path) = self.source_file_path {
-        // Use relative path to match how we stored the content
-        let relative_file_path = std::path::Path::new(file_path)
-          .file_name()
-          .and_then(|name| name.to_str())
-          .unwrap_or(file_path);
-
-        if let Some(source_content) = gen.get_source_content(relative_file_path) {
-          let search_text = format!("defschema {}", schema.name);
-          let lines: Vec<&str> = source_content.lines().collect();
-
-          for (line_idx, line) in lines.iter().enumerate() {
-            if let Some(col_idx) = line.find(&search_text) {
-              let source_line = (line_idx + 1) as u32;
-              let source_column = col_idx as u32;
-
-              // Convert to relative path for VSCode compatibility
-              let relative_file_path = std::path::Path::new(file_path)
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or(file_path);
-
-              gen.add_mapping(
-                ts_lines,
-                interface_name_column,
-                relative_file_path,
-                source_line,
-                source_column,
-                Some(&pascal_case_name),
-              );
-              break;
-            }
-          }
-        }
-      }
+    // TypeScript is always written as an ES module; the compiler picks the module format
+    let runtime = RuntimeOptions {
+      commonjs: false,
+      ..self.runtime.clone()
+    };
+    let mut code = Emitter::new();
+    write!(
+      code,
+      "// This file was generated by the Pact Toolbox\n{}\n{}import type {{ {} }} from {};\n{}",
+      runtime.import_statement(&helpers),
+      self.codec_imports(false),
+      self.runtime_types().join(", "),
+      serde_json::to_string(&self.runtime.import).unwrap_or_default(),
+      self.mapped_type_imports(),
+    )
+    .unwrap();
+    let declarations = interface_parts
+      .into_iter()
+      .chain(type_parts)
+      .filter_map(non_blank);
+    let mut declared = code.part();
+    append_joined(&mut declared, declarations, "\n\n");
+    code.push('\n');
+    if !declared.is_empty() {
+      code.append(declared);
+      code.push_str("\n\n");
     }
+    append_joined(&mut code, code_parts, "\n\n");
+    let (mut code, marks) = code.finish();
 
-    let interface_rest = format!("{pascal_case_name} {{\n");
-    ts_types.push_str(&interface_rest);
-
-    // Generate fields
-    for field in &schema.fields {
-      let ts_type = crate::types::pact_type_to_typescript(&field.field_type);
-      let camel_case_field = to_camel_case(&field.name);
-      let field_line = format!("  {camel_case_field}: {ts_type};\n");
-      ts_types.push_str(&field_line);
+    let plugin_manager = get_plugin_manager();
+    let manager_lock = plugin_manager.lock().unwrap();
+    let mut types = String::new();
+    if let Err(e) = manager_lock.post_generate(&mut code, &mut types, modules) {
+      log::warn!("Plugin post-generation error: {e}");
     }
+    drop(manager_lock);
 
-    let interface_end = "}\n\n";
-    ts_types.push_str(interface_end);
+    let source_map = if self.source_map_gen.is_some() {
+      self.generate_source_maps(&marks, &[], ts_filename, None).0
+    } else {
+      None
+    };
+    (code, String::new(), source_map, None)
   }
 
-  fn generate_function_with_source_map(
-    &mut self,
-    module: &PactModule,
-    function: &PactFunction,
-    js_code: &mut String,
-    ts_types: &mut String,
-  ) {
-    let camel_case_name = to_camel_case(&function.name);
-
-    // Generate JSDoc for JavaScript
-    if let Some(doc) = &function.doc {
-      let doc_comment = format!("/**\n * {doc}\n */\n");
-      js_code.push_str(&doc_comment);
-      if let Some(ref mut gen) = self.source_map_gen {
-        gen.advance_generated_position(&doc_comment);
+  /// Fill the configured namespace into modules declared under a dynamic `(namespace ...)`
+  fn resolve_dynamic_namespaces(&self, modules: &mut [PactModule]) {
+    for module in modules {
+      let Some(expression) = &module.dynamic_namespace else {
+        continue;
+      };
+      if module.namespace.is_some() {
+        continue;
+      }
+      match &self.namespace {
+        Some(namespace) => module.namespace = Some(namespace.clone()),
+        None => log::warn!(
+          "Module `{}` is declared in namespace {expression} but no namespace was configured",
+          module.name
+        ),
       }
     }
+  }
 
-    // Generate function parameters
-    let params = function
-      .parameters
-      .iter()
-      .map(|p| &p.name)
-      .cloned()
-      .collect::<Vec<_>>()
-      .join(", ");
-
-    // Generate JavaScript function with detailed source mapping
-    js_code.push_str("export function ");
-    if let Some(ref mut gen) = self.source_map_gen {
-      gen.advance_generated_position("export function ");
-    }
+  /// Map the generated output back to the Pact source at the marks recorded while emitting it
+  fn generate_source_maps(
+    &mut self,
+    js_marks: &[SourceMark],
+    declaration_marks: &[SourceMark],
+    js_filename: Option<&str>,
+    ts_filename: Option<&str>,
+  ) -> (Option<String>, Option<String>) {
+    let Some(gen) = self.source_map_gen.as_mut() else {
+      return (None, None);
+    };
 
-    // Old mapping code removed - using direct lookup in TypeScript generation instead
+    // Use the relative path the source content was registered under
+    let file_path = self.source_file_path.as_deref().unwrap_or_default();
+    let source_file = std::path::Path::new(file_path)
+      .file_name()
+      .and_then(|name| name.to_str())
+      .unwrap_or(file_path);
 
-    let func_rest = format!("{camel_case_name}({params}) {{\n");
-    js_code.push_str(&func_rest);
-    if let Some(ref mut gen) = self.source_map_gen {
-      gen.advance_generated_position(&func_rest);
+    // Spans count bytes; source maps count UTF-16 code units
+    let source_lines: Vec<&str> = gen
+      .source_content(source_file)
+      .unwrap_or_default()
+      .lines()
+      .collect();
+    let source_column = |mark: &SourceMark| {
+      source_lines
+        .get(mark.source_line.saturating_sub(1) as usize)
+        .map_or(mark.source_column, |line| {
+          utf16_column(line, mark.source_column)
+        })
+    };
+    let js_columns: Vec<u32> = js_marks.iter().map(source_column).collect();
+    let declaration_columns: Vec<u32> = declaration_marks.iter().map(source_column).collect();
+
+    for (mark, column) in js_marks.iter().zip(js_columns) {
+      gen.add_mapping(
+        mark.generated_line,
+        mark.generated_column,
+        source_file,
+        mark.source_line,
+        column,
+        Some(&mark.name),
+      );
+    }
+    for (mark, column) in declaration_marks.iter().zip(declaration_columns) {
+      gen.add_declaration_mapping(
+        mark.generated_line,
+        mark.generated_column,
+        source_file,
+        mark.source_line,
+        column,
+        Some(&mark.name),
+      );
     }
 
-    // Create Pact command string with proper module reference including namespace
-    let module_ref = if let Some(namespace) = &module.namespace {
-      format!("{}.{}", namespace, module.name)
+    let source_map = if gen.get_options().generate.unwrap_or(true) {
+      let js_file = js_filename.unwrap_or("generated.js");
+      match gen.generate_json(js_file) {
+        Ok(json) => Some(json),
+        Err(e) => {
+          log::warn!("Failed to generate source map JSON: {e}");
+          None
+        }
+      }
     } else {
-      module.name.to_string()
+      None
     };
-    let mut pact_cmd = format!("({}.{}", module_ref, function.name);
-
-    // Add parameters with JSON.stringify for proper escaping
-    for param in &function.parameters {
-      let param_ref = format!(" ${{JSON.stringify({})}}", param.name);
-      pact_cmd.push_str(&param_ref);
-    }
-    pact_cmd.push(')');
 
-    // Use execution for defuns, continuation for defpacts
-    let builder = if function.is_defun {
-      "execution"
+    let declaration_map = if self.generate_types
+      && !self.typescript
+      && gen.get_options().declaration_map.unwrap_or(false)
+    {
+      let ts_file = ts_filename.unwrap_or("generated.d.ts");
+      match gen.generate_declaration_map(ts_file) {
+        Ok(json) => Some(json),
+        Err(e) => {
+          log::warn!("Failed to generate declaration map JSON: {e}");
+          None
+        }
+      }
     } else {
-      "continuation"
+      None
     };
-    let return_line = format!("    return {builder}(`{pact_cmd}`);\n");
-    js_code.push_str(&return_line);
-    if let Some(ref mut gen) = self.source_map_gen {
-      gen.advance_generated_position(&return_line);
-    }
 
-    let func_end = "}\n\n";
-    js_code.push_str(func_end);
-    if let Some(ref mut gen) = self.source_map_gen {
-      gen.advance_generated_position(func_end);
-    }
+    (source_map, declaration_map)
+  }
 
-    // Generate TypeScript declaration if requested
-    if self.generate_types {
-      if let Some(doc) = &function.doc {
-        let doc_comment = format!("/**\n * {doc}\n */\n");
-        ts_types.push_str(&doc_comment);
+  /// Bindings for a module; `typed` annotates them for the single-file TypeScript output
+  fn generate_module_code(
+    code: &mut Emitter,
+    module: &PactModule,
+    names: &ModuleNames,
+    validators: bool,
+    typed: bool,
+    mapper: &TypeMapper,
+  ) {
+    let mut constants = false;
+    for constant in &module.constants {
+      if let Some(literal) = PactLiteral::parse(&constant.value) {
+        if let Some(doc) = &constant.doc {
+          code.push_str(&convert_to_jsdoc(Some(doc)));
+        }
+        // `as const` keeps the readonly literal types the declarations give lists and objects
+        let assertion = if typed && literal.is_compound() {
+          " as const"
+        } else {
+          ""
+        };
+        let name = names.constant(&constant.name);
+        code.export(ExportKind::Value, &name);
+        code.push_str("const ");
+        code.mark(constant.span, &constant.name);
+        writeln!(code, "{name} = {}{assertion};", literal.to_js()).unwrap();
+        constants = true;
       }
+    }
+    if constants {
+      code.push('\n');
+    }
 
-      // Generate parameter types with camelCase names
-      let param_types = if function.parameters.is_empty() {
-        String::new()
-      } else {
-        function
-          .parameters
-          .iter()
-          .map(|p| {
-            let ts_type = p.parameter_type.as_ref().map_or_else(
-              || "any".to_string(),
-              |t| crate::types::pact_type_to_typescript(t),
-            );
-            let camel_case_param = to_camel_case(&p.name);
-            format!("{camel_case_param}: {ts_type}")
-          })
-          .collect::<Vec<_>>()
-          .join(", ")
-      };
-
-      // Generate return type
-      let return_type = function.return_type.as_ref().map_or_else(
-        || "unknown".to_string(),
-        |t| crate::types::pact_type_to_typescript(t),
-      );
-
-      // Generate TypeScript declaration with precise source mapping
-      // Calculate the correct TypeScript line position
-      let ts_lines = ts_types.matches('\n').count() as u32 + 1;
-      let ts_current_line = &ts_types.lines().last().unwrap_or("");
-      let ts_column = ts_current_line.len() as u32;
-
-      ts_types.push_str("export function ");
-
-      // Map function name precisely at its position in TypeScript
-      let function_name_column = ts_column + "export function ".len() as u32;
-
-      // Direct position lookup to avoid any state issues
-      if let Some(ref mut gen) = self.source_map_gen {
-        if let Some(ref file_path) = self.source_file_path {
-          // Use relative path to match how we stored the content
-          let relative_file_path = std::path::Path::new(file_path)
-            .file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or(file_path);
-
-          // Look up position directly using the exact function name
-          if let Some(source_content) = gen.get_source_content(relative_file_path) {
-            let search_text = format!("defun {}", function.name);
-            let lines: Vec<&str> = source_content.lines().collect();
-
-            for (line_idx, line) in lines.iter().enumerate() {
-              if let Some(col_idx) = line.find(&search_text) {
-                let source_line = (line_idx + 1) as u32;
-                let source_column = col_idx as u32;
-
-                // Convert to relative path for VSCode compatibility
-                let relative_file_path = std::path::Path::new(file_path)
-                  .file_name()
-                  .and_then(|name| name.to_str())
-                  .unwrap_or(file_path);
-
-                gen.add_mapping(
-                  ts_lines,
-                  function_name_column,
-                  relative_file_path,
-                  source_line,
-                  source_column,
-                  Some(&camel_case_name),
-                );
-                break;
-              }
-            }
-          }
-        }
+    if validators {
+      for schema in &module.schemas {
+        Self::generate_schema_validator(code, schema, names, typed);
+        code.push('\n');
       }
+    }
 
-      let ts_rest = format!(
-        "{camel_case_name}({param_types}): PactTransactionBuilder<PactExecPayload, {return_type}>;\n"
-      );
-      ts_types.push_str(&ts_rest);
+    for schema in decoded_schemas(module, mapper) {
+      Self::generate_schema_decoder(code, schema, module, names, typed, mapper);
+      code.push('\n');
     }
-  }
 
-  fn generate_module_code(module: &PactModule) -> String {
-    let mut code = String::with_capacity(4096);
+    for schema in encoded_schemas(module, mapper) {
+      Self::generate_schema_encoder(code, schema, module, names, typed, mapper);
+      code.push('\n');
+    }
 
     // Generate functions as standalone exports
-    let function_codes: Vec<String> = module
+    let function_codes: Vec<Emitter> = module
       .functions
       .par_iter()
-      .map(|func| Self::generate_function_code(func, module))
+      .map(|func| {
+        let mut function_code = code.part();
+        Self::generate_function_code(&mut function_code, func, module, names, typed, mapper);
+        function_code
+      })
       .collect();
 
     for func_code in function_codes {
-      code.push_str(&func_code);
+      code.append(func_code);
       code.push('\n');
     }
 
-    code
-  }
-
-  fn generate_function_code(function: &PactFunction, module: &PactModule) -> String {
-    let mut code = String::with_capacity(1024);
-
-    // Generate JSDoc
-    if let Some(doc) = &function.doc {
-      code.push_str(&convert_to_jsdoc(Some(doc)));
+    for capability in &module.capabilities {
+      Self::generate_capability_code(code, capability, module, names, typed, mapper);
+      code.push('\n');
     }
 
-    // Convert function name to camelCase
-    let camel_case_name = to_camel_case(&function.name);
+    Self::generate_event_code(code, module, names, typed, mapper);
+  }
 
-    // Generate function parameters
-    let params = function
-      .parameters
-      .iter()
-      .map(|p| &p.name)
-      .cloned()
-      .collect::<Vec<_>>()
-      .join(", ");
+  fn generate_function_code(
+    code: &mut Emitter,
+    function: &PactFunction,
+    module: &PactModule,
+    names: &ModuleNames,
+    typed: bool,
+    mapper: &TypeMapper,
+  ) {
+    // Generate JSDoc
+    code.push_str(&convert_to_jsdoc(function_doc(function, names).as_deref()));
 
     // Generate function
-    writeln!(&mut code, "export function {camel_case_name}({params}) {{").unwrap();
-
-    // Create Pact command string with proper module reference including namespace
-    let module_ref = if let Some(namespace) = &module.namespace {
-      format!("{}.{}", namespace, module.name)
+    let binding = names.function(&function.name);
+    let params = parameter_names(&function.parameters);
+    code.export(ExportKind::Value, &binding);
+    code.push_str("function ");
+    if typed {
+      Self::write_function_signature(code, function, &binding, mapper);
     } else {
-      module.name.to_string()
-    };
-    let mut pact_cmd = format!("({}.{}", module_ref, function.name);
-
-    // Add parameters with JSON.stringify for proper escaping
-    for param in &function.parameters {
-      write!(&mut pact_cmd, " ${{JSON.stringify({})}}", param.name).unwrap();
+      code.mark(function.span, &function.name);
+      write!(code, "{binding}(").unwrap();
+      for (index, (p, name)) in function.parameters.iter().zip(&params).enumerate() {
+        if index > 0 {
+          code.push_str(", ");
+        }
+        code.mark(p.span, &p.name);
+        code.push_str(name);
+      }
+      code.push(')');
+    }
+    writeln!(code, " {{").unwrap();
+
+    let mut pact_cmd = format!("({}.{}", module_ref(module), function.name);
+    let mut data = String::new();
+
+    // Encode each argument as the Pact literal its declared type expects
+    for (param, arg) in function.parameters.iter().zip(&params) {
+      match param.parameter_type.as_deref().map(str::trim) {
+        // Guards can't be written inline, they travel in the transaction data
+        Some("keyset") => {
+          write!(&mut pact_cmd, " (read-keyset \"{}\")", param.name).unwrap();
+          let keyset = unmapped_value("keyset", arg, mapper);
+          write!(&mut data, ".withKeyset(\"{}\", {keyset})", param.name).unwrap();
+        }
+        // `read-msg` reads any guard, where `read-keyset` only reads keysets
+        Some("guard") => {
+          write!(&mut pact_cmd, " (read-msg \"{}\")", param.name).unwrap();
+          let guard = unmapped_value("guard", arg, mapper);
+          write!(&mut data, ".withGuard(\"{}\", {guard})", param.name).unwrap();
+        }
+        pact_type => write!(
+          &mut pact_cmd,
+          " ${{{}}}",
+          encode_expression(pact_type.unwrap_or_default(), arg, module, names, mapper)
+        )
+        .unwrap(),
+      }
     }
     pact_cmd.push(')');
 
-    // Use execution for defuns, continuation for defpacts
-    let builder = if function.is_defun {
-      "execution"
-    } else {
-      "continuation"
-    };
-
-    writeln!(&mut code, "    return {builder}(`{pact_cmd}`);").unwrap();
-    writeln!(&mut code, "}}").unwrap();
+    // Results arrive as Pact JSON; convert them to the declared return type
+    let decoder = function
+      .return_type
+      .as_deref()
+      .filter(|_| function.is_defun)
+      .and_then(|return_type| decode_expression(return_type, "value", module, names, typed, mapper))
+      .map(|expression| {
+        let decoder = expression
+          .strip_suffix("(value)")
+          .filter(|name| !name.contains(['(', ' ']))
+          .map_or_else(|| format!("(value) => {expression}"), ToString::to_string);
+        format!(".withDecoder({decoder})")
+      })
+      .unwrap_or_default();
 
-    code
-  }
+    // Defpacts are started with an execution too; later steps go through the helpers below
+    code.push_str("    ");
+    code.mark(function.span, &function.name);
+    writeln!(code, "return execution(`{pact_cmd}`){data}{decoder};").unwrap();
+    writeln!(code, "}}").unwrap();
 
-  fn generate_module_types(module: &PactModule) -> String {
-    let mut types = String::with_capacity(4096);
+    // Lets callers pick `local`/`dirtyRead` for functions that never need a signed transaction
+    writeln!(
+      code,
+      "{binding}.effect = \"{}\"{};",
+      function.effect.as_str(),
+      if typed { " as const" } else { "" }
+    )
+    .unwrap();
 
-    // Generate schema types first
-    for schema in &module.schemas {
-      types.push_str(&Self::generate_schema_type(schema));
+    if let Some(capabilities) = signer_capabilities(function, module, names, mapper) {
+      Self::generate_signer_helper(code, function, names, &capabilities, typed, mapper);
     }
 
-    // Generate function types
-    for function in &module.functions {
-      types.push_str(&Self::generate_function_type(function));
+    if !function.is_defun {
+      Self::generate_pact_helpers(code, function, names, typed);
     }
-
-    types
   }
 
-  fn generate_schema_type(schema: &PactSchema) -> String {
-    let mut types = String::with_capacity(512);
-
-    if let Some(doc) = &schema.doc {
-      types.push_str(&convert_to_jsdoc(Some(doc)));
+  /// Builder with a signer that already signs for the capabilities the function acquires
+  fn generate_signer_helper(
+    code: &mut Emitter,
+    function: &PactFunction,
+    names: &ModuleNames,
+    capabilities: &[String],
+    typed: bool,
+    mapper: &TypeMapper,
+  ) {
+    let args = parameter_names(&function.parameters).join(", ");
+    let helper = signer_helper_name(function, names);
+
+    code.push_str(&convert_to_jsdoc(Some(&signer_helper_doc(function, names))));
+    code.export(ExportKind::Value, &helper);
+    code.push_str("function ");
+    if typed {
+      Self::write_signer_helper_signature(code, function, &helper, mapper);
+    } else if args.is_empty() {
+      write!(code, "{helper}(signer)").unwrap();
+    } else {
+      write!(code, "{helper}(signer, {args})").unwrap();
     }
+    writeln!(
+      code,
+      " {{\n    return {}({args}).withSigner(signer, () => [{}]);\n}}",
+      names.function(&function.name),
+      capabilities.join(", ")
+    )
+    .unwrap();
+  }
 
-    // Use PascalCase for interface names
-    let pascal_case_name = to_pascal_case(&schema.name);
-    writeln!(&mut types, "export interface {pascal_case_name} {{").unwrap();
-
-    for field in &schema.fields {
-      let ts_type = pact_type_to_typescript(&field.field_type);
-      // Use camelCase for field names
-      let camel_case_field = to_camel_case(&field.name);
-      writeln!(&mut types, "  {camel_case_field}: {ts_type};").unwrap();
+  /// Step count and continue/rollback builders for a defpact
+  fn generate_pact_helpers(
+    code: &mut Emitter,
+    function: &PactFunction,
+    names: &ModuleNames,
+    typed: bool,
+  ) {
+    let steps = function.steps.len();
+    if steps == 0 {
+      return;
     }
 
-    writeln!(&mut types, "}}").unwrap();
-    writeln!(&mut types).unwrap();
+    let pascal_case_name = names.function_type(&function.name);
 
-    types
-  }
+    let steps_name = format!("{}Steps", names.function_base(&function.name));
+    code.push('\n');
+    code.export(ExportKind::Value, &steps_name);
+    writeln!(code, "const {steps_name} = {steps};").unwrap();
 
-  fn generate_function_type(function: &PactFunction) -> String {
-    let mut types = String::with_capacity(256);
+    let mut builders = Vec::new();
+    if steps > 1 {
+      builders.push(("continue", false));
+    }
+    if !rollback_steps(function).is_empty() {
+      builders.push(("rollback", true));
+    }
+    for (verb, rollback) in builders {
+      code.push('\n');
+      let name = format!("{verb}{pascal_case_name}");
+      // The step-typed signatures become overloads of the implementation
+      let params = if typed {
+        for signature in pact_helper_signatures(function, names, verb) {
+          code.export(ExportKind::Value, &name);
+          writeln!(code, "function {signature};").unwrap();
+        }
+        "pactId: string, step: number, options: { proof?: string; data?: PactEnvData } = {}): PactTransactionBuilder<PactContPayload>"
+      } else {
+        "pactId, step, options = {})"
+      };
+      code.export(ExportKind::Value, &name);
+      writeln!(
+        code,
+        "function {name}({params} {{\n    return continuation({{ pactId, step, rollback: {rollback}, data: options.data ?? {{}}, proof: options.proof ?? null }});\n}}"
+      )
+      .unwrap();
+    }
+  }
 
-    if let Some(doc) = &function.doc {
-      types.push_str(&convert_to_jsdoc(Some(doc)));
+  /// Signer capability builder, e.g. `transferCapability(sender, receiver, amount)`
+  fn generate_capability_code(
+    code: &mut Emitter,
+    capability: &PactCapability,
+    module: &PactModule,
+    names: &ModuleNames,
+    typed: bool,
+    mapper: &TypeMapper,
+  ) {
+    if let Some(doc) = &capability.doc {
+      code.push_str(&convert_to_jsdoc(Some(doc)));
     }
 
-    // Convert function name to camelCase
-    let camel_case_name = to_camel_case(&function.name);
+    let builder_name = names.capability(&capability.name);
+    let params = parameter_names(&capability.parameters);
+    let args = capability
+      .parameters
+      .iter()
+      .zip(&params)
+      .map(|(p, arg)| pact_value_expression(p.parameter_type.as_deref(), arg, mapper))
+      .collect::<Vec<_>>()
+      .join(", ");
 
-    // Generate parameter types with camelCase names
-    let param_types = if function.parameters.is_empty() {
-      String::new()
+    code.export(ExportKind::Value, &builder_name);
+    code.push_str("function ");
+    code.mark(capability.span, &capability.name);
+    write!(code, "{builder_name}(").unwrap();
+    if typed {
+      Self::write_parameter_types(code, &capability.parameters, mapper);
+      code.push_str("): PactCapability {\n");
     } else {
-      function
-        .parameters
-        .iter()
-        .map(|p| {
-          let ts_type = p
-            .parameter_type
-            .as_ref()
-            .map_or_else(|| "any".to_string(), |t| pact_type_to_typescript(t));
-          let camel_case_param = to_camel_case(&p.name);
-          format!("{camel_case_param}: {ts_type}")
-        })
-        .collect::<Vec<_>>()
-        .join(", ")
+      writeln!(code, "{}) {{", params.join(", ")).unwrap();
+    }
+    writeln!(
+      code,
+      "    return {{ name: \"{}.{}\", args: [{args}] }};",
+      module_ref(module),
+      capability.name
+    )
+    .unwrap();
+    writeln!(code, "}}").unwrap();
+
+    if let Some(info) = &capability.managed {
+      let manager = info
+        .manager_function
+        .as_ref()
+        .map(|manager| format!(", manager: \"{manager}\""))
+        .unwrap_or_default();
+      let assertion = if typed { " as const" } else { "" };
+      writeln!(
+        code,
+        "{builder_name}.managed = {{ parameter: \"{}\"{manager} }}{assertion};",
+        info.parameter
+      )
+      .unwrap();
+    }
+  }
+
+  /// Validator checking each field's presence and Pact type, plus a type guard built on it
+  fn generate_schema_validator(
+    code: &mut Emitter,
+    schema: &PactSchema,
+    names: &ModuleNames,
+    typed: bool,
+  ) {
+    let name = names.schema(&schema.name);
+    let fields = schema
+      .fields
+      .iter()
+      .zip(field_names(&schema.fields))
+      .map(|(field, key)| format!("{key}: \"{}\"", field.field_type.trim()))
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    let (value, validation, guard) = if typed {
+      (
+        "value: unknown",
+        ": PactValidationError[]",
+        format!(": value is {name}"),
+      )
+    } else {
+      ("value", "", String::new())
     };
+    code.export(ExportKind::Value, &format!("validate{name}"));
+    writeln!(
+      code,
+      "function validate{name}({value}){validation} {{\n    return validatePactObject(value, {{ {fields} }});\n}}\n"
+    )
+    .unwrap();
+    code.export(ExportKind::Value, &format!("is{name}"));
+    writeln!(
+      code,
+      "function is{name}({value}){guard} {{\n    return validate{name}(value).length === 0;\n}}"
+    )
+    .unwrap();
+  }
 
-    // Generate return type - map Pact types to TypeScript and handle special cases
+  /// Conversion of a schema object from Pact JSON to its interface, renaming fields to camelCase
+  fn generate_schema_decoder(
+    code: &mut Emitter,
+    schema: &PactSchema,
+    module: &PactModule,
+    names: &ModuleNames,
+    typed: bool,
+    mapper: &TypeMapper,
+  ) {
+    let name = names.schema(&schema.name);
+    let source = if typed { "fields" } else { "value" };
+    let fields = schema
+      .fields
+      .iter()
+      .zip(field_names(&schema.fields))
+      .map(|(field, key)| {
+        let raw = format!(
+          "{source}[{}]",
+          serde_json::to_string(&field.name).unwrap_or_default()
+        );
+        let decoded = if mapper.overrides_field(&schema.name, &field.name) {
+          mapper
+            .field_decoder(&schema.name, &field.name)
+            .map(|decoder| format!("{decoder}({raw})"))
+        } else {
+          decode_expression(&field.field_type, &raw, module, names, typed, mapper)
+        };
+        let value = match decoded {
+          Some(expression) => expression,
+          None if typed => format!(
+            "{raw} as {}",
+            mapper.field_to_typescript(&schema.name, &field.name, &field.field_type)
+          ),
+          None => raw,
+        };
+        format!("{key}: {value}")
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    code.export(ExportKind::Value, &format!("decode{name}"));
+    if typed {
+      writeln!(
+        code,
+        "function decode{name}(value: unknown): {name} {{\n    const fields = value as Record<string, unknown>;\n    return {{ {fields} }};\n}}"
+      )
+      .unwrap();
+    } else {
+      writeln!(
+        code,
+        "function decode{name}(value) {{\n    return {{ {fields} }};\n}}"
+      )
+      .unwrap();
+    }
+  }
+
+  /// Encoder writing a schema object as a Pact object literal, the inverse of its decoder
+  fn generate_schema_encoder(
+    code: &mut Emitter,
+    schema: &PactSchema,
+    module: &PactModule,
+    names: &ModuleNames,
+    typed: bool,
+    mapper: &TypeMapper,
+  ) {
+    let name = names.schema(&schema.name);
+    let fields = schema
+      .fields
+      .iter()
+      .zip(field_names(&schema.fields))
+      .map(|(field, key)| {
+        let value = format!("value.{key}");
+        let encoded = if mapper.overrides_field(&schema.name, &field.name) {
+          let value = match mapper.field_encoder(&schema.name, &field.name) {
+            Some(encoder) => format!("{encoder}({value})"),
+            None => value,
+          };
+          format!("encodePactArg({value}, \"{}\")", field.field_type.trim())
+        } else {
+          encode_expression(&field.field_type, &value, module, names, mapper)
+        };
+        format!(
+          "{}: ${{{encoded}}}",
+          serde_json::to_string(&field.name).unwrap_or_default()
+        )
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    code.export(ExportKind::Value, &format!("encode{name}"));
+    if typed {
+      writeln!(
+        code,
+        "function encode{name}(value: {name}): string {{\n    return `{{{fields}}}`;\n}}"
+      )
+      .unwrap();
+    } else {
+      writeln!(
+        code,
+        "function encode{name}(value) {{\n    return `{{{fields}}}`;\n}}"
+      )
+      .unwrap();
+    }
+  }
+
+  /// Decoder and type guards for the events a module emits
+  fn generate_event_code(
+    code: &mut Emitter,
+    module: &PactModule,
+    names: &ModuleNames,
+    typed: bool,
+    mapper: &TypeMapper,
+  ) {
+    let events: Vec<&PactCapability> = module_events(module).collect();
+    if events.is_empty() {
+      return;
+    }
+
+    let module_name = type_identifier(&module.name);
+    let qualified = module_ref(module);
+
+    // Modules in an unresolved dynamic namespace can only be matched by name
+    let namespace_check = module
+      .namespace
+      .as_ref()
+      .map(|namespace| format!(" || event.module.namespace !== \"{namespace}\""))
+      .unwrap_or_default();
+
+    code.export(ExportKind::Value, &format!("decode{module_name}Events"));
+    if typed {
+      writeln!(
+        code,
+        "function decode{module_name}Events(events: PactEvent[] | undefined): {module_name}Event[] {{\n    const decoded: {module_name}Event[] = [];"
+      )
+      .unwrap();
+    } else {
+      writeln!(
+        code,
+        "function decode{module_name}Events(events) {{\n    const decoded = [];"
+      )
+      .unwrap();
+    }
+    writeln!(code, "    for (const event of events ?? []) {{").unwrap();
+    writeln!(
+      code,
+      "        if (event.module.name !== \"{}\"{namespace_check}) continue;",
+      module.name
+    )
+    .unwrap();
+    writeln!(code, "        switch (event.name) {{").unwrap();
+    for event in &events {
+      let params = event
+        .parameters
+        .iter()
+        .zip(parameter_names(&event.parameters))
+        .enumerate()
+        .map(|(i, (p, name))| {
+          let raw = format!("event.params[{i}]");
+          let decoded = match p.parameter_type.as_deref() {
+            Some(pact_type) => decode_expression(pact_type, &raw, module, names, typed, mapper)
+              .unwrap_or_else(|| {
+                // Pact sends these as they are declared
+                if typed {
+                  format!("{raw} as {}", mapper.to_typescript(pact_type))
+                } else {
+                  raw
+                }
+              }),
+            None => format!("fromPactValue({raw})"),
+          };
+          format!("{name}: {decoded}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+      writeln!(code, "            case \"{}\":", event.name).unwrap();
+      writeln!(
+        code,
+        "                decoded.push({{ name: \"{}\", module: \"{qualified}\", moduleHash: event.moduleHash, params: {{ {params} }} }});",
+        event.name
+      )
+      .unwrap();
+      writeln!(code, "                break;").unwrap();
+    }
+    writeln!(code, "        }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code, "    return decoded;").unwrap();
+    writeln!(code, "}}").unwrap();
+
+    for event in &events {
+      let type_name = event_type_name(module, names, event);
+      let (signature, subject) = if typed {
+        (
+          format!("is{type_name}(event: unknown): event is {type_name} {{\n    const candidate = event as {type_name} | undefined;"),
+          "candidate",
+        )
+      } else {
+        (format!("is{type_name}(event) {{"), "event")
+      };
+      code.push('\n');
+      code.export(ExportKind::Value, &format!("is{type_name}"));
+      writeln!(
+        code,
+        "function {signature}\n    return {subject}?.module === \"{qualified}\" && {subject}?.name === \"{}\";\n}}",
+        event.name
+      )
+      .unwrap();
+    }
+  }
+
+  fn generate_module_types(
+    types: &mut Emitter,
+    module: &PactModule,
+    names: &ModuleNames,
+    interfaces: &[PactInterface],
+    validators: bool,
+    mapper: &TypeMapper,
+  ) {
+    // Generate schema types first
+    for schema in &module.schemas {
+      Self::generate_schema_type(types, schema, names, mapper);
+      if validators {
+        let name = names.schema(&schema.name);
+        types.export(ExportKind::Value, &format!("validate{name}"));
+        writeln!(
+          types,
+          "function validate{name}(value: unknown): PactValidationError[];"
+        )
+        .unwrap();
+        types.export(ExportKind::Value, &format!("is{name}"));
+        writeln!(
+          types,
+          "function is{name}(value: unknown): value is {name};\n"
+        )
+        .unwrap();
+      }
+    }
+
+    for schema in decoded_schemas(module, mapper) {
+      let name = names.schema(&schema.name);
+      types.export(ExportKind::Value, &format!("decode{name}"));
+      writeln!(types, "function decode{name}(value: unknown): {name};\n").unwrap();
+    }
+
+    for schema in encoded_schemas(module, mapper) {
+      let name = names.schema(&schema.name);
+      types.export(ExportKind::Value, &format!("encode{name}"));
+      writeln!(types, "function encode{name}(value: {name}): string;\n").unwrap();
+    }
+
+    for constant in &module.constants {
+      if let Some(literal) = PactLiteral::parse(&constant.value) {
+        if let Some(doc) = &constant.doc {
+          types.push_str(&convert_to_jsdoc(Some(doc)));
+        }
+        let name = names.constant(&constant.name);
+        types.export(ExportKind::Value, &name);
+        types.push_str("const ");
+        types.mark(constant.span, &constant.name);
+        writeln!(types, "{name}: {};", literal.to_ts_type()).unwrap();
+      }
+    }
+
+    // Generate function types
+    for function in &module.functions {
+      Self::generate_function_type(types, function, names, mapper);
+      if signer_capabilities(function, module, names, mapper).is_some() {
+        let helper = signer_helper_name(function, names);
+        types.push_str(&convert_to_jsdoc(Some(&signer_helper_doc(function, names))));
+        types.export(ExportKind::Value, &helper);
+        types.push_str("function ");
+        Self::write_signer_helper_signature(types, function, &helper, mapper);
+        types.push_str(";\n");
+      }
+      if !function.is_defun {
+        Self::generate_pact_helper_types(types, function, names);
+      }
+    }
+
+    for capability in &module.capabilities {
+      Self::generate_capability_type(types, capability, names, mapper);
+    }
+
+    Self::generate_event_types(types, module, names, mapper);
+
+    Self::generate_implements_type(types, module, names, interfaces, mapper);
+  }
+
+  /// The type-only part of a module's declarations, placed ahead of its code in TypeScript output
+  fn generate_module_declarations(
+    types: &mut Emitter,
+    module: &PactModule,
+    names: &ModuleNames,
+    interfaces: &[PactInterface],
+    mapper: &TypeMapper,
+  ) {
+    let mut parts = Vec::new();
+    for schema in &module.schemas {
+      let mut part = types.part();
+      Self::generate_schema_type(&mut part, schema, names, mapper);
+      parts.push(part);
+    }
+    for function in &module.functions {
+      let mut part = types.part();
+      Self::generate_yields_type(&mut part, function, names);
+      parts.push(part);
+    }
+    let events: Vec<&PactCapability> = module_events(module).collect();
+    if !events.is_empty() {
+      let mut part = types.part();
+      Self::generate_event_interfaces(&mut part, module, names, &events, mapper);
+      parts.push(part);
+    }
+    let mut part = types.part();
+    Self::generate_implements_type(&mut part, module, names, interfaces, mapper);
+    parts.push(part);
+
+    append_joined(types, parts.into_iter().filter_map(non_blank), "\n\n");
+  }
+
+  fn generate_interface_types(types: &mut Emitter, interface: &PactInterface, mapper: &TypeMapper) {
+    let names = ModuleNames::interface(interface);
+    for schema in &interface.schemas {
+      Self::generate_schema_type(types, schema, &names, mapper);
+    }
+
+    if let Some(doc) = &interface.doc {
+      types.push_str(&convert_to_jsdoc(Some(doc)));
+    }
+
+    let name = type_identifier(&interface.name);
+    types.export(ExportKind::Type, &name);
+    types.push_str("interface ");
+    types.mark(interface.span, &interface.name);
+    writeln!(types, "{name} {{").unwrap();
+    Self::write_member_signatures(types, &interface.functions, &names, mapper);
+    writeln!(types, "}}").unwrap();
+  }
+
+  /// Type the module against the interfaces it implements, so a mismatching
+  /// signature surfaces as a TypeScript error
+  fn generate_implements_type(
+    types: &mut Emitter,
+    module: &PactModule,
+    names: &ModuleNames,
+    interfaces: &[PactInterface],
+    mapper: &TypeMapper,
+  ) {
+    let implemented: Vec<String> = module
+      .implements
+      .iter()
+      .filter_map(|reference| {
+        interfaces
+          .iter()
+          .find(|i| i.matches_reference(reference, module))
+      })
+      .map(|interface| type_identifier(&interface.name))
+      .collect();
+
+    if implemented.is_empty() {
+      return;
+    }
+
+    let name = format!("{}Module", type_identifier(&module.name));
+    types.push('\n');
+    types.export(ExportKind::Type, &name);
+    types.push_str("interface ");
+    types.mark(module.span, &module.name);
+    writeln!(types, "{name} extends {} {{", implemented.join(", ")).unwrap();
+    Self::write_member_signatures(types, &module.functions, names, mapper);
+    writeln!(types, "}}").unwrap();
+  }
+
+  fn write_member_signatures(
+    types: &mut Emitter,
+    functions: &[PactFunction],
+    names: &ModuleNames,
+    mapper: &TypeMapper,
+  ) {
+    for function in functions {
+      let doc = function_doc(function, names);
+      for line in convert_to_jsdoc(doc.as_deref()).lines() {
+        writeln!(types, "  {line}").unwrap();
+      }
+      types.push_str("  ");
+      Self::write_function_signature(types, function, &names.function(&function.name), mapper);
+      types.push_str(";\n");
+    }
+  }
+
+  fn generate_schema_type(
+    types: &mut Emitter,
+    schema: &PactSchema,
+    names: &ModuleNames,
+    mapper: &TypeMapper,
+  ) {
+    if let Some(doc) = &schema.doc {
+      types.push_str(&convert_to_jsdoc(Some(doc)));
+    }
+
+    // Use PascalCase for interface names
+    let pascal_case_name = names.schema(&schema.name);
+    types.export(ExportKind::Type, &pascal_case_name);
+    types.push_str("interface ");
+    types.mark(schema.span, &schema.name);
+    writeln!(types, "{pascal_case_name} {{").unwrap();
+
+    for (field, camel_case_field) in schema.fields.iter().zip(field_names(&schema.fields)) {
+      let ts_type = mapper.field_to_typescript(&schema.name, &field.name, &field.field_type);
+      types.push_str("  ");
+      types.mark(field.span, &field.name);
+      writeln!(types, "{camel_case_field}: {ts_type};").unwrap();
+    }
+
+    writeln!(types, "}}").unwrap();
+    writeln!(types).unwrap();
+  }
+
+  fn generate_function_type(
+    types: &mut Emitter,
+    function: &PactFunction,
+    names: &ModuleNames,
+    mapper: &TypeMapper,
+  ) {
+    types.push_str(&convert_to_jsdoc(function_doc(function, names).as_deref()));
+
+    let name = names.function(&function.name);
+    types.export(ExportKind::Value, &name);
+    types.push_str("function ");
+    Self::write_function_signature(types, function, &name, mapper);
+    types.push_str(";\n");
+    types.export(ExportKind::Value, &name);
+    types.declare();
+    writeln!(
+      types,
+      "namespace {name} {{ export const effect: \"{}\"; }}",
+      function.effect.as_str()
+    )
+    .unwrap();
+  }
+
+  /// Step count, yield payloads and step-typed continue/rollback signatures for a defpact
+  fn generate_pact_helper_types(types: &mut Emitter, function: &PactFunction, names: &ModuleNames) {
+    let steps = function.steps.len();
+    if steps == 0 {
+      return;
+    }
+
+    let steps_name = format!("{}Steps", names.function_base(&function.name));
+    types.export(ExportKind::Value, &steps_name);
+    writeln!(types, "const {steps_name}: {steps};").unwrap();
+
+    Self::generate_yields_type(types, function, names);
+    for verb in ["continue", "rollback"] {
+      let name = format!("{verb}{}", names.function_type(&function.name));
+      for signature in pact_helper_signatures(function, names, verb) {
+        types.export(ExportKind::Value, &name);
+        writeln!(types, "function {signature};").unwrap();
+      }
+    }
+  }
+
+  /// Interface of the values a defpact's steps yield, keyed by step index
+  fn generate_yields_type(types: &mut Emitter, function: &PactFunction, names: &ModuleNames) {
+    let yields: Vec<(usize, &PactStep)> = function
+      .steps
+      .iter()
+      .enumerate()
+      .filter(|(_, step)| step.yields)
+      .collect();
+    if yields.is_empty() {
+      return;
+    }
+
+    let name = format!("{}Yields", names.function_type(&function.name));
+    writeln!(
+      types,
+      "/**\n * Values yielded by each step of `{}`, keyed by step index\n */",
+      function.name
+    )
+    .unwrap();
+    types.export(ExportKind::Type, &name);
+    writeln!(types, "interface {name} {{").unwrap();
+    for (index, step) in yields {
+      let fields = step
+        .yield_fields
+        .iter()
+        .map(|field| format!("\"{field}\": PactValue"))
+        .collect::<Vec<_>>()
+        .join("; ");
+      writeln!(types, "  {index}: {{ {fields} }};").unwrap();
+    }
+    writeln!(types, "}}").unwrap();
+  }
+
+  fn generate_capability_type(
+    types: &mut Emitter,
+    capability: &PactCapability,
+    names: &ModuleNames,
+    mapper: &TypeMapper,
+  ) {
+    if let Some(doc) = &capability.doc {
+      types.push_str(&convert_to_jsdoc(Some(doc)));
+    }
+
+    let name = names.capability(&capability.name);
+    types.export(ExportKind::Value, &name);
+
+    // Managed builders also expose which argument the manager function governs
+    if let Some(info) = &capability.managed {
+      let manager = info
+        .manager_function
+        .as_ref()
+        .map(|manager| format!("; readonly manager: \"{manager}\""))
+        .unwrap_or_default();
+      types.declare();
+      types.push_str("const ");
+      types.mark(capability.span, &capability.name);
+      write!(types, "{name}: {{\n  (").unwrap();
+      Self::write_parameter_types(types, &capability.parameters, mapper);
+      writeln!(
+        types,
+        "): PactCapability;\n  readonly managed: {{ readonly parameter: \"{}\"{manager} }};\n}};",
+        info.parameter
+      )
+      .unwrap();
+    } else {
+      types.push_str("function ");
+      types.mark(capability.span, &capability.name);
+      write!(types, "{name}(").unwrap();
+      Self::write_parameter_types(types, &capability.parameters, mapper);
+      types.push_str("): PactCapability;\n");
+    }
+  }
+
+  /// One interface per event, their union, the decoder and the type guards
+  fn generate_event_types(
+    types: &mut Emitter,
+    module: &PactModule,
+    names: &ModuleNames,
+    mapper: &TypeMapper,
+  ) {
+    let events: Vec<&PactCapability> = module_events(module).collect();
+    if events.is_empty() {
+      return;
+    }
+
+    Self::generate_event_interfaces(types, module, names, &events, mapper);
+    let module_name = type_identifier(&module.name);
+    let qualified = module_ref(module);
+
+    writeln!(
+      types,
+      "/**\n * Decode the `events` of a transaction result that {qualified} emitted\n */"
+    )
+    .unwrap();
+    types.export(ExportKind::Value, &format!("decode{module_name}Events"));
+    writeln!(
+      types,
+      "function decode{module_name}Events(events: PactEvent[] | undefined): {module_name}Event[];"
+    )
+    .unwrap();
+    for event in &events {
+      let type_name = event_type_name(module, names, event);
+      types.export(ExportKind::Value, &format!("is{type_name}"));
+      writeln!(
+        types,
+        "function is{type_name}(event: unknown): event is {type_name};"
+      )
+      .unwrap();
+    }
+  }
+
+  /// One interface per event and their union
+  fn generate_event_interfaces(
+    types: &mut Emitter,
+    module: &PactModule,
+    names: &ModuleNames,
+    events: &[&PactCapability],
+    mapper: &TypeMapper,
+  ) {
+    let qualified = module_ref(module);
+
+    for event in events {
+      if let Some(doc) = &event.doc {
+        types.push_str(&convert_to_jsdoc(Some(doc)));
+      }
+      let name = event_type_name(module, names, event);
+      types.export(ExportKind::Type, &name);
+      write!(
+        types,
+        "interface {name} {{\n  name: \"{}\";\n  module: \"{qualified}\";\n  moduleHash: string;\n  params: {{ ",
+        event.name
+      )
+      .unwrap();
+      // Event parameters are decoded results, not arguments
+      Self::write_typed_parameters(types, &event.parameters, "; ", |t| mapper.to_typescript(t));
+      types.push_str(" };\n}\n");
+    }
+
+    let union = events
+      .iter()
+      .map(|event| event_type_name(module, names, event))
+      .collect::<Vec<_>>()
+      .join(" | ");
+    let name = format!("{}Event", type_identifier(&module.name));
+    types.export(ExportKind::Type, &name);
+    writeln!(types, "type {name} = {union};").unwrap();
+  }
+
+  /// `name: type` pairs with camelCase names, typed as arguments
+  fn write_parameter_types(out: &mut Emitter, parameters: &[PactParameter], mapper: &TypeMapper) {
+    Self::write_typed_parameters(out, parameters, ", ", |t| mapper.to_input_typescript(t));
+  }
+
+  /// `name: type` pairs with camelCase names, each type given by `map`
+  fn write_typed_parameters(
+    out: &mut Emitter,
+    parameters: &[PactParameter],
+    separator: &str,
+    map: impl Fn(&str) -> String,
+  ) {
+    let names = parameter_names(parameters);
+    for (index, (p, name)) in parameters.iter().zip(names).enumerate() {
+      if index > 0 {
+        out.push_str(separator);
+      }
+      let ts_type = p
+        .parameter_type
+        .as_deref()
+        .map_or_else(|| "any".to_string(), &map);
+      out.mark(p.span, &p.name);
+      write!(out, "{name}: {ts_type}").unwrap();
+    }
+  }
+
+  /// `nameWithSigner(signer, params): PactTransactionBuilder<...>` for a signer helper
+  fn write_signer_helper_signature(
+    out: &mut Emitter,
+    function: &PactFunction,
+    name: &str,
+    mapper: &TypeMapper,
+  ) {
     let return_type = function
       .return_type
       .as_ref()
-      .map_or_else(|| "unknown".to_string(), |t| pact_type_to_typescript(t));
+      .map_or_else(|| "unknown".to_string(), |t| mapper.to_typescript(t));
+    write!(out, "{name}(signer: PactSignerLike").unwrap();
+    if !function.parameters.is_empty() {
+      out.push_str(", ");
+    }
+    Self::write_parameter_types(out, &function.parameters, mapper);
+    write!(
+      out,
+      "): PactTransactionBuilder<PactExecPayload, {return_type}>"
+    )
+    .unwrap();
+  }
 
-    writeln!(
-      &mut types,
-      "export function {camel_case_name}({param_types}): PactTransactionBuilder<PactExecPayload, {return_type}>;"
+  /// `name(params): ReturnType`, shared by function declarations and interface members
+  fn write_function_signature(
+    out: &mut Emitter,
+    function: &PactFunction,
+    name: &str,
+    mapper: &TypeMapper,
+  ) {
+    // Generate return type - map Pact types to TypeScript and handle special cases
+    let return_type = function
+      .return_type
+      .as_ref()
+      .map_or_else(|| "unknown".to_string(), |t| mapper.to_typescript(t));
+
+    out.mark(function.span, &function.name);
+    write!(out, "{name}(").unwrap();
+    Self::write_parameter_types(out, &function.parameters, mapper);
+    write!(
+      out,
+      "): PactTransactionBuilder<PactExecPayload, {return_type}>"
     )
     .unwrap();
+  }
+}
+
+/// Warnings for names that more than one module exports when modules are emitted flat
+fn export_collisions(modules: &[PactModule], parts: &[(Emitter, Emitter)]) -> Vec<Diagnostic> {
+  let mut owners: HashMap<&str, String> = HashMap::new();
+  let mut diagnostics = Vec::new();
 
-    types
+  for (module, (code, types)) in modules.iter().zip(parts) {
+    let mut seen = HashSet::new();
+    let names = code
+      .exports()
+      .iter()
+      .chain(types.exports())
+      .map(|export| export.name.as_str())
+      .filter(|name| seen.insert(*name));
+
+    for name in names {
+      match owners.get(name) {
+        Some(owner) => diagnostics.push(Diagnostic::warning(
+          DiagnosticCode::ExportCollision,
+          format!(
+            "`{name}` is exported by both `{owner}` and `{}`; set `exportMode: \"namespaced\"` to keep them apart",
+            module_ref(module)
+          ),
+          module.span.unwrap_or_default(),
+        )),
+        None => {
+          owners.insert(name, module_ref(module));
+        }
+      }
+    }
   }
+
+  diagnostics
 }
 
-/// Convert `snake_case` or kebab-case to camelCase
-fn to_camel_case(s: &str) -> String {
-  let mut result = String::new();
-  let mut capitalize_next = false;
-
-  for (i, c) in s.chars().enumerate() {
-    if c == '-' || c == '_' {
-      capitalize_next = true;
-    } else if i == 0 {
-      result.push(c.to_lowercase().next().unwrap_or(c));
-    } else if capitalize_next {
-      result.push(c.to_uppercase().next().unwrap_or(c));
-      capitalize_next = false;
-    } else {
-      result.push(c);
+/// Append `parts` to `out` with `separator` between them
+fn append_joined(out: &mut Emitter, parts: impl IntoIterator<Item = Emitter>, separator: &str) {
+  for (index, part) in parts.into_iter().enumerate() {
+    if index > 0 {
+      out.push_str(separator);
     }
+    out.append(part);
   }
+}
 
-  result
+/// The part without surrounding blank lines, unless nothing else is left
+fn non_blank(mut part: Emitter) -> Option<Emitter> {
+  part.trim();
+  (!part.is_empty()).then_some(part)
+}
+
+/// Wrap a module's code in a scope that returns the bindings it exported as one object
+fn wrap_module_code(module: &PactModule, mut body: Emitter, commonjs: bool) -> Emitter {
+  let names: Vec<String> = body
+    .take_exports()
+    .into_iter()
+    .filter(|export| export.kind == ExportKind::Value)
+    .map(|export| export.name)
+    .collect();
+  let mut code = Emitter::new().with_scoped_exports(commonjs);
+  let name = js_identifier(&module.name);
+  if let Some(namespace) = &module.namespace {
+    write!(code, "const {}_{name}", js_identifier(namespace)).unwrap();
+  } else {
+    code.export(ExportKind::Value, &name);
+    write!(code, "const {name}").unwrap();
+  }
+  writeln!(code, " = (() => {{").unwrap();
+  code.append(body);
+  code.end_line();
+  write!(code, "    return {{ {} }};\n}})();", names.join(", ")).unwrap();
+  code
 }
 
-/// Convert `snake_case` or kebab-case to `PascalCase`
-fn to_pascal_case(s: &str) -> String {
-  let mut result = String::new();
-  let mut capitalize_next = true;
-
-  for c in s.chars() {
-    if c == '-' || c == '_' {
-      capitalize_next = true;
-    } else if capitalize_next {
-      result.push(c.to_uppercase().next().unwrap_or(c));
-      capitalize_next = false;
+/// One exported object per namespace holding its modules, e.g. `export const free = { coin: free_coin };`
+fn namespace_exports(modules: &[PactModule], commonjs: bool) -> Option<Emitter> {
+  let mut namespaces: Vec<(&str, Vec<String>)> = Vec::new();
+  for module in modules {
+    let Some(namespace) = module.namespace.as_deref() else {
+      continue;
+    };
+    let name = js_identifier(&module.name);
+    let entry = format!("{name}: {}_{name}", js_identifier(namespace));
+    match namespaces.iter_mut().find(|(ns, _)| *ns == namespace) {
+      Some((_, entries)) => entries.push(entry),
+      None => namespaces.push((namespace, vec![entry])),
+    }
+  }
+
+  (!namespaces.is_empty()).then(|| {
+    let mut code = Emitter::new().with_scoped_exports(commonjs);
+    for (index, (namespace, entries)) in namespaces.iter().enumerate() {
+      if index > 0 {
+        code.push('\n');
+      }
+      let name = js_identifier(namespace);
+      code.export(ExportKind::Value, &name);
+      write!(code, "const {name} = {{ {} }};", entries.join(", ")).unwrap();
+    }
+    code
+  })
+}
+
+/// Path of the namespace a module's declarations are wrapped in, e.g. `free.coin`
+fn module_path(module: &PactModule) -> String {
+  match &module.namespace {
+    Some(namespace) => format!(
+      "{}.{}",
+      js_identifier(namespace),
+      js_identifier(&module.name)
+    ),
+    None => js_identifier(&module.name),
+  }
+}
+
+/// Wrap a module's declarations in the namespace its exported object is typed by
+fn wrap_module_types(module: &PactModule, mut body: Emitter) -> Emitter {
+  // Names inside the namespace are reached through its path
+  body.take_exports();
+  let mut types = Emitter::new();
+  let root = js_identifier(module.namespace.as_deref().unwrap_or(&module.name));
+  types.export(ExportKind::Value, &root);
+  types.declare();
+  writeln!(types, "namespace {} {{", module_path(module)).unwrap();
+  types.append(body);
+  types.end_line();
+  types.push('}');
+  types
+}
+
+/// Reference used in generated Pact code, e.g. `free.coin`
+fn module_ref(module: &PactModule) -> String {
+  match &module.namespace {
+    Some(namespace) => format!("{namespace}.{}", module.name),
+    None => module.name.clone(),
+  }
+}
+
+/// Steps that can be rolled back: those with a rollback expression, except the last
+/// since the pact is complete once it runs
+fn rollback_steps(function: &PactFunction) -> Vec<usize> {
+  let last = function.steps.len().saturating_sub(1);
+  function
+    .steps
+    .iter()
+    .enumerate()
+    .filter(|(index, step)| *index < last && step.rollback.is_some())
+    .map(|(index, _)| index)
+    .collect()
+}
+
+/// Step-typed signatures of a defpact's `continue` or `rollback` builder; steps resuming
+/// a cross-chain yield can only run with the SPV proof of that yield
+fn pact_helper_signatures(function: &PactFunction, names: &ModuleNames, verb: &str) -> Vec<String> {
+  let steps = function.steps.len();
+  let name = format!("{verb}{}", names.function_type(&function.name));
+  let result = "PactTransactionBuilder<PactContPayload>";
+  let optional = "options?: { proof?: string; data?: PactEnvData }";
+
+  if verb == "rollback" {
+    let rollbacks = rollback_steps(function);
+    if rollbacks.is_empty() {
+      return Vec::new();
+    }
+    return vec![format!(
+      "{name}(pactId: string, step: {}, {optional}): {result}",
+      step_union(&rollbacks)
+    )];
+  }
+
+  let (cross_chain, local): (Vec<usize>, Vec<usize>) =
+    (1..steps).partition(|&step| function.steps[step - 1].yield_chain.is_some());
+  let mut signatures = Vec::new();
+  if !cross_chain.is_empty() {
+    signatures.push(format!(
+      "{name}(pactId: string, step: {}, options: {{ proof: string; data?: PactEnvData }}): {result}",
+      step_union(&cross_chain)
+    ));
+  }
+  if !local.is_empty() {
+    signatures.push(format!(
+      "{name}(pactId: string, step: {}, {optional}): {result}",
+      step_union(&local)
+    ));
+  }
+  signatures
+}
+
+/// `[1, 2]` becomes `1 | 2`
+fn step_union(steps: &[usize]) -> String {
+  steps
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<_>>()
+    .join(" | ")
+}
+
+/// Schema a Pact type refers to, directly or as the element of (nested) lists
+fn schema_reference<'a>(pact_type: &str, module: &'a PactModule) -> Option<&'a PactSchema> {
+  let mut base = pact_type.trim();
+  while let Some(inner) = base
+    .strip_prefix('[')
+    .and_then(|rest| rest.strip_suffix(']'))
+  {
+    base = inner.trim();
+  }
+  let name = base
+    .strip_prefix("object{")
+    .or_else(|| base.strip_prefix('{'))
+    .and_then(|rest| rest.strip_suffix('}'))?;
+  module.schemas.iter().find(|schema| schema.name == name)
+}
+
+/// Schemas that need a decoder because a defun returns them, directly or through other schemas
+fn decoded_schemas<'a>(module: &'a PactModule, mapper: &TypeMapper) -> Vec<&'a PactSchema> {
+  let return_types = module
+    .functions
+    .iter()
+    .filter(|function| function.is_defun)
+    .filter_map(|function| function.return_type.as_deref());
+  referenced_schemas(return_types, module, mapper)
+}
+
+/// Schemas that need an encoder because a function takes them, directly or through other schemas
+fn encoded_schemas<'a>(module: &'a PactModule, mapper: &TypeMapper) -> Vec<&'a PactSchema> {
+  let parameter_types = module
+    .functions
+    .iter()
+    .flat_map(|function| &function.parameters)
+    .filter_map(|param| param.parameter_type.as_deref());
+  referenced_schemas(parameter_types, module, mapper)
+}
+
+/// Local schemas the types name, followed through schema fields, leaving out types the user mapped
+fn referenced_schemas<'a, 'b>(
+  types: impl Iterator<Item = &'b str>,
+  module: &'a PactModule,
+  mapper: &TypeMapper,
+) -> Vec<&'a PactSchema> {
+  let mut pending: Vec<&PactSchema> = types
+    .filter(|pact_type| !mapper.overrides(pact_type))
+    .filter_map(|pact_type| schema_reference(pact_type, module))
+    .collect();
+  let mut decoded: HashSet<&str> = HashSet::new();
+  while let Some(schema) = pending.pop() {
+    if !decoded.insert(schema.name.as_str()) {
+      continue;
+    }
+    pending.extend(
+      schema
+        .fields
+        .iter()
+        .filter(|field| {
+          !(mapper.overrides_field(&schema.name, &field.name)
+            || mapper.overrides(&field.field_type))
+        })
+        .filter_map(|field| schema_reference(&field.field_type, module)),
+    );
+  }
+  module
+    .schemas
+    .iter()
+    .filter(|schema| decoded.contains(schema.name.as_str()))
+    .collect()
+}
+
+/// JavaScript expression writing `value` as a Pact literal of `pact_type`, passing the
+/// generated encoder for local schema objects
+fn encode_expression(
+  pact_type: &str,
+  value: &str,
+  module: &PactModule,
+  names: &ModuleNames,
+  mapper: &TypeMapper,
+) -> String {
+  let pact_type = pact_type.trim();
+  if pact_type.is_empty() {
+    return format!("encodePactArg({value})");
+  }
+  let value = unmapped_value(pact_type, value, mapper);
+  match schema_reference(pact_type, module).filter(|_| !mapper.overrides(pact_type)) {
+    Some(schema) => format!(
+      "encodePactArg({value}, \"{pact_type}\", encode{})",
+      names.schema(&schema.name)
+    ),
+    None => format!("encodePactArg({value}, \"{pact_type}\")"),
+  }
+}
+
+/// JavaScript expression converting `value` to the JSON form of a Pact value of `pact_type`
+fn pact_value_expression(pact_type: Option<&str>, value: &str, mapper: &TypeMapper) -> String {
+  match pact_type.map(str::trim) {
+    Some(pact_type) => format!(
+      "toPactValue({}, \"{pact_type}\")",
+      unmapped_value(pact_type, value, mapper)
+    ),
+    None => format!("toPactValue({value})"),
+  }
+}
+
+/// `value` of a mapped type passed through its configured encoder, if it has one
+fn unmapped_value(pact_type: &str, value: &str, mapper: &TypeMapper) -> String {
+  match mapper.encoder(pact_type) {
+    Some((encoder, depth)) => map_items(encoder, value, depth, false),
+    None => value.to_string(),
+  }
+}
+
+/// `function` called on `value`, or on the items of `value` when it is `depth` lists deep
+fn map_items(function: &str, value: &str, depth: usize, typed: bool) -> String {
+  let list = |items: &str| {
+    if typed {
+      format!("({items} as unknown[])")
     } else {
-      result.push(c);
+      items.to_string()
+    }
+  };
+  if depth == 0 {
+    return format!("{function}({value})");
+  }
+  let mut function = function.to_string();
+  for _ in 1..depth {
+    function = format!("(item) => {}.map({function})", list("item"));
+  }
+  format!("{}.map({function})", list(value))
+}
+
+/// JavaScript expression converting `value`, Pact JSON of `pact_type`, into its declared
+/// TypeScript shape, or `None` when the JSON already has that shape. Types mapped by the
+/// user are decoded by their configured decoder, or left as Pact returns them.
+fn decode_expression(
+  pact_type: &str,
+  value: &str,
+  module: &PactModule,
+  names: &ModuleNames,
+  typed: bool,
+  mapper: &TypeMapper,
+) -> Option<String> {
+  let pact_type = pact_type.trim();
+  if mapper.overrides(pact_type) {
+    let (decoder, depth) = mapper.decoder(pact_type)?;
+    return Some(map_items(decoder, value, depth, typed));
+  }
+  let mut base = pact_type;
+  let mut depth = 0;
+  while let Some(inner) = base
+    .strip_prefix('[')
+    .and_then(|rest| rest.strip_suffix(']'))
+  {
+    base = inner.trim();
+    depth += 1;
+  }
+
+  // Wrapped numbers and times; `fromPactValue` walks the lists itself and its
+  // return type follows the type name, so it's written without spaces
+  if matches!(base, "integer" | "decimal" | "time") {
+    let pact_type = format!("{}{base}{}", "[".repeat(depth), "]".repeat(depth));
+    return Some(format!("fromPactValue({value}, \"{pact_type}\")"));
+  }
+
+  let schema = schema_reference(base, module)?;
+  let decoder = format!("decode{}", names.schema(&schema.name));
+  Some(map_items(&decoder, value, depth, typed))
+}
+
+/// Doc text of a function, followed by the capabilities its body acquires and requires
+fn function_doc(function: &PactFunction, names: &ModuleNames) -> Option<String> {
+  let doc = doc_with_pact_name(
+    function.doc.as_deref(),
+    &function.name,
+    &names.function(&function.name),
+  );
+  if function.capabilities.is_empty() {
+    return doc;
+  }
+  let capabilities = function
+    .capabilities
+    .iter()
+    .map(|capability| {
+      let verb = if capability.acquired {
+        "Acquires"
+      } else {
+        "Requires"
+      };
+      let call = std::iter::once(capability.name.as_str())
+        .chain(capability.args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+      format!("{verb} `({call})`")
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+  Some(match doc {
+    Some(doc) => format!("{doc}\n\n{capabilities}"),
+    None => capabilities,
+  })
+}
+
+/// `transfer` becomes `transferWithSigner`
+fn signer_helper_name(function: &PactFunction, names: &ModuleNames) -> String {
+  format!("{}WithSigner", names.function_base(&function.name))
+}
+
+fn signer_helper_doc(function: &PactFunction, names: &ModuleNames) -> String {
+  format!(
+    "`{}` signed by `signer` for the capabilities it acquires",
+    names.function(&function.name)
+  )
+}
+
+/// Capability values for the signer of a defun, or of the first step that starts a defpact,
+/// one per acquired capability a signature can scope, or `None` when it acquires none or an
+/// argument is neither a parameter nor a literal. Local capabilities that are neither managed
+/// nor check a guard are internal, so they're left out, as are local names without a
+/// `defcap`; those of other modules can't be inspected and are kept.
+fn signer_capabilities(
+  function: &PactFunction,
+  module: &PactModule,
+  names: &ModuleNames,
+  mapper: &TypeMapper,
+) -> Option<Vec<String>> {
+  let first_step = if function.is_defun {
+    None
+  } else {
+    Some(function.steps.first()?.span?)
+  };
+  let in_first_step = |requirement: &&CapabilityRequirement| {
+    first_step.is_none_or(|step| {
+      requirement
+        .span
+        .is_some_and(|span| span.start_byte >= step.start_byte && span.end_byte <= step.end_byte)
+    })
+  };
+
+  let mut capabilities: Vec<String> = Vec::new();
+  for requirement in function
+    .capabilities
+    .iter()
+    .filter(|c| c.acquired)
+    .filter(in_first_step)
+  {
+    let capability = match local_capability(&requirement.name, module) {
+      // Reported by `unknown_capabilities`
+      CapabilityRef::Undefined => continue,
+      CapabilityRef::Local(capability) if capability.managed.is_none() && !capability.guarded => {
+        continue
+      }
+      // Local builders encode each argument as their own parameter types
+      CapabilityRef::Local(capability) => {
+        let args = requirement
+          .args
+          .iter()
+          .map(|arg| signer_argument(arg, function, None))
+          .collect::<Option<Vec<_>>>()?;
+        format!(
+          "{}({})",
+          names.capability(&capability.name),
+          args.join(", ")
+        )
+      }
+      CapabilityRef::Foreign => {
+        let args = requirement
+          .args
+          .iter()
+          .map(|arg| signer_argument(arg, function, Some(mapper)))
+          .collect::<Option<Vec<_>>>()?;
+        format!(
+          "{{ name: {}, args: [{}] }}",
+          serde_json::to_string(&requirement.name).unwrap_or_default(),
+          args.join(", ")
+        )
+      }
+    };
+    if !capabilities.contains(&capability) {
+      capabilities.push(capability);
     }
   }
+  (!capabilities.is_empty()).then_some(capabilities)
+}
 
-  result
+/// What an acquired capability name refers to
+enum CapabilityRef<'a> {
+  Local(&'a PactCapability),
+  /// A local name the module has no `defcap` for
+  Undefined,
+  /// Another module's capability
+  Foreign,
+}
+
+fn local_capability<'a>(name: &str, module: &'a PactModule) -> CapabilityRef<'a> {
+  let name = match name.rsplit_once('.') {
+    Some((qualifier, name)) if qualifier == module.name || qualifier == module_ref(module) => name,
+    Some(_) => return CapabilityRef::Foreign,
+    None => name,
+  };
+  module
+    .capabilities
+    .iter()
+    .find(|c| c.name == name)
+    .map_or(CapabilityRef::Undefined, CapabilityRef::Local)
+}
+
+/// Warnings for acquired capabilities whose local names have no `defcap`, such as ones
+/// brought in with `use`, which signer helpers leave out
+fn unknown_capabilities(modules: &[PactModule]) -> Vec<Diagnostic> {
+  let mut diagnostics = Vec::new();
+  for module in modules {
+    for function in &module.functions {
+      let mut reported = HashSet::new();
+      for requirement in function.capabilities.iter().filter(|c| c.acquired) {
+        if matches!(
+          local_capability(&requirement.name, module),
+          CapabilityRef::Undefined
+        ) && reported.insert(requirement.name.as_str())
+        {
+          diagnostics.push(Diagnostic::warning(
+            DiagnosticCode::UnknownCapability,
+            format!(
+              "`{}` acquires `{}`, which `{}` doesn't define; its signer helper leaves it out",
+              function.name,
+              requirement.name,
+              module_ref(module)
+            ),
+            requirement.span.unwrap_or_default(),
+          ));
+        }
+      }
+    }
+  }
+  diagnostics
+}
+
+/// JavaScript for a capability argument that is a parameter of `function` or a literal;
+/// `encode` converts it to a Pact value for capabilities without a generated builder
+fn signer_argument(
+  arg: &str,
+  function: &PactFunction,
+  encode: Option<&TypeMapper>,
+) -> Option<String> {
+  let names = parameter_names(&function.parameters);
+  if let Some((param, name)) = function
+    .parameters
+    .iter()
+    .zip(names)
+    .find(|(p, _)| p.name == arg)
+  {
+    return Some(match encode {
+      Some(mapper) => pact_value_expression(param.parameter_type.as_deref(), &name, mapper),
+      None => name,
+    });
+  }
+  // Literal integers are already Pact values, decimals are wrapped as `toPactValue` would
+  let literal = PactLiteral::parse(arg)?;
+  Some(match (encode, &literal) {
+    (Some(_), PactLiteral::Decimal(digits)) => format!(
+      "{{ decimal: {} }}",
+      serde_json::to_string(digits).unwrap_or_default()
+    ),
+    _ => literal.to_js(),
+  })
+}
+
+/// Capabilities that emit events: `@event` ones and, implicitly, managed ones
+fn module_events(module: &PactModule) -> impl Iterator<Item = &PactCapability> {
+  module
+    .capabilities
+    .iter()
+    .filter(|capability| capability.is_event || capability.managed.is_some())
+}
+
+/// `TRANSFER` in `coin` becomes `CoinTransferEvent`
+fn event_type_name(module: &PactModule, names: &ModuleNames, event: &PactCapability) -> String {
+  format!(
+    "{}{}Event",
+    type_identifier(&module.name),
+    names.capability_type(&event.name)
+  )
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::identifier::{to_camel_case, to_pascal_case};
+  use crate::types::{TypeCodec, TypeMappings};
 
   #[test]
   fn test_generate_empty_module() {
@@ -676,7 +2317,8 @@ mod tests {
     let mut generator = CodeGenerator::new(false);
     let (code, _, _, _) = generator.generate(&[module]);
 
-    assert!(code.contains("import { execution, continuation }"));
+    assert!(code
+      .contains("import { execution, continuation, encodePactArg, toPactValue, fromPactValue }"));
   }
 
   #[test]
@@ -689,15 +2331,22 @@ mod tests {
         PactParameter {
           name: "id".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "title".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
       ],
       return_type: Some("string".to_string()),
       body: "...".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module.add_function(function);
 
@@ -707,7 +2356,7 @@ mod tests {
     // Check JavaScript generation
     assert!(code.contains("export function createTodo(id, title) {"));
     assert!(code.contains(
-      "return execution(`(todos.create-todo ${JSON.stringify(id)} ${JSON.stringify(title)})`);"
+      "return execution(`(todos.create-todo ${encodePactArg(id, \"string\")} ${encodePactArg(title, \"string\")})`);"
     ));
 
     // Check TypeScript generation
@@ -729,6 +2378,11 @@ mod tests {
       return_type: Some("[object{todo}]".to_string()),
       body: "...".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module.add_function(function);
 
@@ -740,31 +2394,310 @@ mod tests {
   }
 
   #[test]
-  fn test_module_without_namespace() {
+  fn test_module_without_namespace() {
+    let mut module = PactModule::new("utils".to_string(), "governance".to_string());
+
+    let function = PactFunction {
+      name: "helper-func".to_string(),
+      doc: None,
+      parameters: vec![PactParameter {
+        name: "value".to_string(),
+        parameter_type: Some("string".to_string()),
+        span: None,
+      }],
+      return_type: Some("bool".to_string()),
+      body: "...".to_string(),
+      is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
+    };
+    module.add_function(function);
+
+    let mut generator = CodeGenerator::new(false);
+    let (code, _, _, _) = generator.generate(&[module]);
+
+    assert!(
+      code.contains("return execution(`(utils.helper-func ${encodePactArg(value, \"string\")})`);")
+    );
+    assert!(code.contains("export function helperFunc(value) {"));
+  }
+
+  #[test]
+  fn test_arguments_are_encoded_by_type() {
+    let source = r"
+(module coin GOVERNANCE
+  (defschema limit amount:decimal until:time)
+  (defschema account balance:decimal last-limits:[object{limit}])
+  (defun transfer-create:string (sender:string receiver:string receiver-guard:guard amount:decimal)
+    1)
+  (defun rotate (account:string owner:keyset)
+    1)
+  (defun lock (until:time count:integer ids:[string] extra)
+    1)
+  (defun open-all (accounts:[object{account}])
+    1))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+
+    assert!(code.contains(
+      "return execution(`(coin.transfer-create ${encodePactArg(sender, \"string\")} ${encodePactArg(receiver, \"string\")} (read-msg \"receiver-guard\") ${encodePactArg(amount, \"decimal\")})`).withGuard(\"receiver-guard\", receiverGuard);"
+    ));
+    assert!(code.contains(
+      "return execution(`(coin.rotate ${encodePactArg(account, \"string\")} (read-keyset \"owner\"))`).withKeyset(\"owner\", owner);"
+    ));
+    assert!(code.contains(
+      "(coin.lock ${encodePactArg(until, \"time\")} ${encodePactArg(count, \"integer\")} ${encodePactArg(ids, \"[string]\")} ${encodePactArg(extra)})"
+    ));
+
+    // Schema objects are written field by field, under their Pact names
+    assert!(code.contains(
+      "(coin.open-all ${encodePactArg(accounts, \"[object{account}]\", encodeAccount)})"
+    ));
+    assert!(code.contains(
+      "export function encodeAccount(value) {\n    return `{\"balance\": ${encodePactArg(value.balance, \"decimal\")}, \"last-limits\": ${encodePactArg(value.lastLimits, \"[object{limit}]\", encodeLimit)}}`;\n}"
+    ));
+    assert!(code.contains(
+      "export function encodeLimit(value) {\n    return `{\"amount\": ${encodePactArg(value.amount, \"decimal\")}, \"until\": ${encodePactArg(value.until, \"time\")}}`;\n}"
+    ));
+    assert!(types.contains("export function encodeAccount(value: Account): string;"));
+
+    let (code, _, _, _) = CodeGenerator::new(false)
+      .with_typescript(true)
+      .generate(&modules);
+    assert!(code.contains("export function encodeLimit(value: Limit): string {"));
+  }
+
+  #[test]
+  fn test_capability_builders() {
+    let source = r#"
+(namespace 'free)
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defcap TRANSFER:bool (sender:string receiver:string amount:decimal)
+    "Transfer capability"
+    @managed amount TRANSFER-mgr
+    true)
+  (defcap DEBIT-ALL (account) true))
+"#;
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+
+    assert!(code.contains("export function governanceCapability() {\n    return { name: \"free.coin.GOVERNANCE\", args: [] };"));
+    assert!(code.contains(
+      "export function transferCapability(sender, receiver, amount) {\n    return { name: \"free.coin.TRANSFER\", args: [toPactValue(sender, \"string\"), toPactValue(receiver, \"string\"), toPactValue(amount, \"decimal\")] };\n}\ntransferCapability.managed = { parameter: \"amount\", manager: \"TRANSFER-mgr\" };"
+    ));
+    assert!(code.contains("export function debitAllCapability(account) {\n    return { name: \"free.coin.DEBIT-ALL\", args: [toPactValue(account)] };"));
+
+    assert!(types.contains("export function governanceCapability(): PactCapability;"));
+    assert!(types.contains(
+      "/**\n * Transfer capability\n */\nexport declare const transferCapability: {\n  (sender: string, receiver: string, amount: PactDecimal): PactCapability;\n  readonly managed: { readonly parameter: \"amount\"; readonly manager: \"TRANSFER-mgr\" };\n};"
+    ));
+    assert!(types.contains("export function debitAllCapability(account: any): PactCapability;"));
+  }
+
+  #[test]
+  fn test_signer_helpers() {
+    let source = r#"
+(module bank GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defcap DEBIT (account:string) (compose-capability (OWNER account)))
+  (defcap OWNER (account:string) (enforce-guard (account-guard account)))
+  (defcap INTERNAL () true)
+  (defun account-guard:guard (account:string) (read-keyset account))
+  (defun pay:string (sender:string receiver:string amount:decimal)
+    "Pay a merchant"
+    (with-capability (DEBIT sender)
+      (with-capability (INTERNAL)
+        (with-capability (coin.TRANSFER sender receiver amount)
+          (coin.transfer sender receiver amount)))))
+  (defun audit:string (account:string)
+    (require-capability (DEBIT account))
+    "ok")
+  (defun rotate:string (account:string)
+    (with-capability (INTERNAL) account))
+  (defun tip:string (sender:string)
+    (with-capability (coin.TRANSFER sender "tips" 0.100000000000000000001)
+      (coin.transfer sender "tips" 0.100000000000000000001)))
+  (defun fee:decimal (amount:decimal) (* amount 0.01)))
+"#;
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+
+    assert!(code.contains(
+      "/**\n * Pay a merchant\n * \n * Acquires `(DEBIT sender)`\n * Acquires `(INTERNAL)`\n * Acquires `(coin.TRANSFER sender receiver amount)`\n */\nexport function pay("
+    ));
+    assert!(code.contains(
+      "export function payWithSigner(signer, sender, receiver, amount) {\n    return pay(sender, receiver, amount).withSigner(signer, () => [debitCapability(sender), { name: \"coin.TRANSFER\", args: [toPactValue(sender, \"string\"), toPactValue(receiver, \"string\"), toPactValue(amount, \"decimal\")] }]);\n}"
+    ));
+    assert!(code.contains("Requires `(DEBIT account)`"));
+    // Required capabilities are granted by the caller, not signed for
+    assert!(!code.contains("auditWithSigner"));
+    assert!(!code.contains("feeWithSigner"));
+    // Unmanaged capabilities without a guard are internal, so a signer can't scope them
+    assert!(!code.contains("withSigner(signer, () => [internalCapability()"));
+    assert!(!code.contains("rotateWithSigner"));
+    // Literal decimals are passed in their wire form so they aren't rounded
+    assert!(code.contains(
+      "tip(sender).withSigner(signer, () => [{ name: \"coin.TRANSFER\", args: [toPactValue(sender, \"string\"), \"tips\", { decimal: \"0.100000000000000000001\" }] }]);"
+    ));
+
+    assert!(types.contains(
+      "export function payWithSigner(signer: PactSignerLike, sender: string, receiver: string, amount: PactDecimal): PactTransactionBuilder<PactExecPayload, string>;"
+    ));
+
+    let (code, _, _, _) = CodeGenerator::new(true)
+      .with_typescript(true)
+      .generate(&modules);
+    assert!(code.contains(
+      "export function payWithSigner(signer: PactSignerLike, sender: string, receiver: string, amount: PactDecimal): PactTransactionBuilder<PactExecPayload, string> {"
+    ));
+  }
+
+  #[test]
+  fn test_signer_helpers_follow_calls_and_pacts() {
+    let source = r#"
+(module bank GOVERNANCE
+  (use coin)
+  (defcap GOVERNANCE () true)
+  (defcap DEBIT (account:string amount:decimal) @managed amount DEBIT-mgr true)
+  (defcap CREDIT (account:string) (enforce-guard (read-keyset account)))
+  (defun debit:string (account:string amount:decimal)
+    (with-capability (DEBIT account amount) "debited"))
+  (defun credit:string (account:string)
+    (with-capability (CREDIT account) "credited"))
+  (defun move:string (from:string to:string amount:decimal)
+    (debit from amount)
+    (credit to))
+  (defun pay:string (from:string amount:decimal)
+    (with-capability (TRANSFER from "bank" amount) (debit from amount)))
+  (defpact settle:string (from:string to:string amount:decimal)
+    (step (debit from amount))
+    (step (credit to))))
+"#;
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let mut generator = CodeGenerator::new(true);
+    let (code, types, _, _) = generator.generate(&modules);
+
+    // Callees' capabilities are signed for with the caller's arguments
+    assert!(code.contains(
+      "return move(from, to, amount).withSigner(signer, () => [debitCapability(from, amount), creditCapability(to)]);"
+    ));
+    // `TRANSFER` comes from `use coin`, so it can't be built, but the rest still is
+    assert!(code.contains(
+      "return pay(from, amount).withSigner(signer, () => [debitCapability(from, amount)]);"
+    ));
+    let unknown: Vec<(&str, &str)> = generator
+      .diagnostics()
+      .iter()
+      .map(|d| (d.code.as_str(), d.message.as_str()))
+      .collect();
+    assert_eq!(
+      unknown,
+      [(
+        "P0010",
+        "`pay` acquires `TRANSFER`, which `bank` doesn't define; its signer helper leaves it out"
+      )]
+    );
+    assert_eq!(generator.diagnostics()[0].span.start_line, 15);
+
+    // A pact is started by its first step, so only that step is signed for
+    assert!(code.contains(
+      "export function settleWithSigner(signer, from, to, amount) {\n    return settle(from, to, amount).withSigner(signer, () => [debitCapability(from, amount)]);\n}"
+    ));
+    assert!(code.contains(
+      "Acquires `(DEBIT from amount)`\n * Acquires `(CREDIT to)`\n */\nexport function settle("
+    ));
+    assert!(types.contains("export function settleWithSigner(signer: PactSignerLike, from: string, to: string, amount: PactDecimal): PactTransactionBuilder<PactExecPayload, string>;"));
+  }
+
+  #[test]
+  fn test_event_decoders() {
+    let source = r#"
+(namespace 'free)
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defcap TRANSFER:bool (sender:string receiver:string amount:decimal)
+    @managed amount TRANSFER-mgr
+    true)
+  (defcap RELEASE_ALLOCATION (account:string amount:decimal)
+    "Allocation released"
+    @event
+    true))
+"#;
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+
+    assert!(code.contains(
+      "        if (event.module.name !== \"coin\" || event.module.namespace !== \"free\") continue;"
+    ));
+    assert!(code.contains(
+      "            case \"TRANSFER\":\n                decoded.push({ name: \"TRANSFER\", module: \"free.coin\", moduleHash: event.moduleHash, params: { sender: event.params[0], receiver: event.params[1], amount: fromPactValue(event.params[2], \"decimal\") } });"
+    ));
+    assert!(code.contains("case \"RELEASE_ALLOCATION\":"));
+    assert!(!code.contains("case \"GOVERNANCE\":"));
+    assert!(code.contains(
+      "export function isCoinTransferEvent(event) {\n    return event?.module === \"free.coin\" && event?.name === \"TRANSFER\";\n}"
+    ));
+
+    assert!(types.contains(
+      "export interface CoinTransferEvent {\n  name: \"TRANSFER\";\n  module: \"free.coin\";\n  moduleHash: string;\n  params: { sender: string; receiver: string; amount: PactDecimal };\n}"
+    ));
+    assert!(types
+      .contains("/**\n * Allocation released\n */\nexport interface CoinReleaseAllocationEvent {"));
+    assert!(
+      types.contains("export type CoinEvent = CoinTransferEvent | CoinReleaseAllocationEvent;")
+    );
+    assert!(types
+      .contains("export function decodeCoinEvents(events: PactEvent[] | undefined): CoinEvent[];"));
+    assert!(types.contains(
+      "export function isCoinReleaseAllocationEvent(event: unknown): event is CoinReleaseAllocationEvent;"
+    ));
+  }
+
+  #[test]
+  fn test_dynamic_namespace_filled_from_config() {
     let mut module = PactModule::new("utils".to_string(), "governance".to_string());
-
-    let function = PactFunction {
-      name: "helper-func".to_string(),
+    module.dynamic_namespace = Some("(read-msg 'ns)".to_string());
+    module.add_function(PactFunction {
+      name: "ping".to_string(),
       doc: None,
-      parameters: vec![PactParameter {
-        name: "value".to_string(),
-        parameter_type: Some("string".to_string()),
-      }],
-      return_type: Some("bool".to_string()),
+      parameters: vec![],
+      return_type: None,
       body: "...".to_string(),
       is_defun: true,
-    };
-    module.add_function(function);
-
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
+    });
+
+    let mut generator = CodeGenerator::new(false).with_namespace(Some("n_abc".to_string()));
+    let (code, _, _, _) = generator.generate(std::slice::from_ref(&module));
+    assert!(code.contains("return execution(`(n_abc.utils.ping)`);"));
+
+    // Without a configured namespace the reference stays unqualified
     let mut generator = CodeGenerator::new(false);
     let (code, _, _, _) = generator.generate(&[module]);
-
-    assert!(code.contains("return execution(`(utils.helper-func ${JSON.stringify(value)})`);"));
-    assert!(code.contains("export function helperFunc(value) {"));
+    assert!(code.contains("return execution(`(utils.ping)`);"));
   }
 
   #[test]
-  fn test_defpact_uses_continuation() {
+  fn test_defpact_is_started_with_an_execution() {
     let mut module = PactModule::new("transfers".to_string(), "governance".to_string());
 
     let defpact = PactFunction {
@@ -774,23 +2707,71 @@ mod tests {
         PactParameter {
           name: "from".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "to".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
       ],
       return_type: Some("string".to_string()),
       body: "...".to_string(),
       is_defun: false, // This is a defpact
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module.add_function(defpact);
 
     let mut generator = CodeGenerator::new(false);
     let (code, _, _, _) = generator.generate(&[module]);
 
-    assert!(code.contains("return continuation(`(transfers.cross-chain-transfer"));
+    assert!(code.contains("return execution(`(transfers.cross-chain-transfer"));
     assert!(code.contains("export function crossChainTransfer(from, to) {"));
+    // Without parsed steps there is nothing to continue
+    assert!(!code.contains("continuation("));
+  }
+
+  #[test]
+  fn test_defpact_continuation_helpers() {
+    let source = r#"
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defpact transfer-crosschain (sender:string receiver:string target-chain:string amount:decimal)
+    (step-with-rollback
+      (yield { "receiver": receiver, "amount": amount } target-chain)
+      (debit-back sender amount))
+    (step
+      (resume { "receiver" := receiver, "amount" := amount }
+        (credit receiver amount)))
+    (step "done")))
+"#;
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+
+    assert!(code.contains("export const transferCrosschainSteps = 3;"));
+    assert!(code.contains(
+      "export function continueTransferCrosschain(pactId, step, options = {}) {\n    return continuation({ pactId, step, rollback: false, data: options.data ?? {}, proof: options.proof ?? null });\n}"
+    ));
+    assert!(code.contains("export function rollbackTransferCrosschain(pactId, step, options = {}) {\n    return continuation({ pactId, step, rollback: true,"));
+
+    assert!(types.contains("export const transferCrosschainSteps: 3;"));
+    assert!(types.contains(
+      "export interface TransferCrosschainYields {\n  0: { \"receiver\": PactValue; \"amount\": PactValue };\n}"
+    ));
+    assert!(types.contains(
+      "export function continueTransferCrosschain(pactId: string, step: 1, options: { proof: string; data?: PactEnvData }): PactTransactionBuilder<PactContPayload>;"
+    ));
+    assert!(types.contains(
+      "export function continueTransferCrosschain(pactId: string, step: 2, options?: { proof?: string; data?: PactEnvData }): PactTransactionBuilder<PactContPayload>;"
+    ));
+    assert!(types
+      .contains("export function rollbackTransferCrosschain(pactId: string, step: 0, options?:"));
   }
 
   #[test]
@@ -804,6 +2785,11 @@ mod tests {
       return_type: Some("integer".to_string()),
       body: "...".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module.add_function(function);
 
@@ -811,9 +2797,12 @@ mod tests {
     let (code, types, _, _) = generator.generate(&[module]);
 
     assert!(code.contains("export function getCount() {"));
-    assert!(code.contains("return execution(`(counter.get-count)`);"));
-    assert!(types
-      .contains("export function getCount(): PactTransactionBuilder<PactExecPayload, number>;"));
+    assert!(code.contains(
+      "return execution(`(counter.get-count)`).withDecoder((value) => fromPactValue(value, \"integer\"));"
+    ));
+    assert!(types.contains(
+      "export function getCount(): PactTransactionBuilder<PactExecPayload, PactInteger>;"
+    ));
   }
 
   #[test]
@@ -827,38 +2816,50 @@ mod tests {
         PactParameter {
           name: "user-id".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "first-name".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "last-name".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "email".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
         PactParameter {
           name: "age".to_string(),
           parameter_type: Some("integer".to_string()),
+          span: None,
         },
       ],
       return_type: Some("string".to_string()),
       body: "...".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module.add_function(function);
 
     let mut generator = CodeGenerator::new(true);
     let (code, types, _, _) = generator.generate(&[module]);
 
-    assert!(code
-      .contains("export function createUserProfile(user-id, first-name, last-name, email, age) {"));
-    assert!(code.contains("${JSON.stringify(user-id)} ${JSON.stringify(first-name)} ${JSON.stringify(last-name)} ${JSON.stringify(email)} ${JSON.stringify(age)}"));
-    assert!(types
-      .contains("userId: string, firstName: string, lastName: string, email: string, age: number"));
+    assert!(
+      code.contains("export function createUserProfile(userId, firstName, lastName, email, age) {")
+    );
+    assert!(code.contains("${encodePactArg(userId, \"string\")} ${encodePactArg(firstName, \"string\")} ${encodePactArg(lastName, \"string\")} ${encodePactArg(email, \"string\")} ${encodePactArg(age, \"integer\")}"));
+    assert!(types.contains(
+      "userId: string, firstName: string, lastName: string, email: string, age: PactInteger"
+    ));
   }
 
   #[test]
@@ -872,20 +2873,25 @@ mod tests {
         SchemaField {
           name: "user-id".to_string(),
           field_type: "string".to_string(),
+          span: None,
         },
         SchemaField {
           name: "email-address".to_string(),
           field_type: "string".to_string(),
+          span: None,
         },
         SchemaField {
           name: "is-active".to_string(),
           field_type: "bool".to_string(),
+          span: None,
         },
         SchemaField {
           name: "signup-date".to_string(),
           field_type: "time".to_string(),
+          span: None,
         },
       ],
+      span: None,
     };
     module.add_schema(schema);
 
@@ -900,6 +2906,675 @@ mod tests {
     assert!(types.contains("* User profile schema"));
   }
 
+  #[test]
+  fn test_wire_shaped_declarations() {
+    let source = r"
+(module ledger GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema entry amount:decimal count:integer at:time owner:keyset rule:guard)
+  (defschema batch entries:[object{entry}] totals:[decimal])
+  (defcap RECORDED (at:time amount:decimal) @event true)
+  (defun record:object{batch} (batch:object{batch} amounts:[decimal] at:time owner:keyset rule:guard)
+    batch))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+
+    assert!(types.contains(
+      "export interface Entry {\n  amount: PactDecimal;\n  count: PactInteger;\n  at: Date;\n  owner: PactKeyset;\n  rule: PactGuard;\n}"
+    ));
+    assert!(
+      types.contains("export interface Batch {\n  entries: Entry[];\n  totals: PactDecimal[];\n}")
+    );
+    // Arguments take any time the runtime encodes, results are decoded to dates
+    assert!(types.contains(
+      "export function record(batch: Batch, amounts: PactDecimal[], at: PactTimeInput, owner: PactKeyset, rule: PactGuard): PactTransactionBuilder<PactExecPayload, Batch>;"
+    ));
+    assert!(types.contains(
+      "export function recordedCapability(at: PactTimeInput, amount: PactDecimal): PactCapability;"
+    ));
+    assert!(types.contains("params: { at: Date; amount: PactDecimal };"));
+    assert!(code.contains("at: fromPactValue(value[\"at\"], \"time\")"));
+    assert!(code.contains("totals: fromPactValue(value[\"totals\"], \"[decimal]\")"));
+  }
+
+  #[test]
+  fn test_schema_validators() {
+    let source = r"
+(module bank GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema account-row balance:decimal owner-guard:guard opened:time))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+    assert!(!code.contains("validatePactObject"));
+    assert!(!types.contains("validateAccountRow"));
+
+    let (code, types, _, _) = CodeGenerator::new(true)
+      .with_validators(true)
+      .generate(&modules);
+    assert!(code.contains("fromPactValue, validatePactObject }"));
+    assert!(code.contains(
+      "export function validateAccountRow(value) {\n    return validatePactObject(value, { balance: \"decimal\", ownerGuard: \"guard\", opened: \"time\" });\n}"
+    ));
+    assert!(code.contains(
+      "export function isAccountRow(value) {\n    return validateAccountRow(value).length === 0;\n}"
+    ));
+    assert!(types.contains("PactGuard, PactValidationError }"));
+    assert!(
+      types.contains("export function validateAccountRow(value: unknown): PactValidationError[];")
+    );
+    assert!(types.contains("export function isAccountRow(value: unknown): value is AccountRow;"));
+  }
+
+  #[test]
+  fn test_literal_constants_are_exported() {
+    let source = r#"
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defconst MINIMUM_PRECISION 12
+    "Maximum allowed decimal precision")
+  (defconst COIN_CHARSET:integer CHARSET_LATIN1)
+  (defconst FEES { "transfer": 0.5, "tags": ["a" "b"], "enabled": true })
+  (defconst SUPPLY 123456789.123456789012)
+  (defconst ADMIN (read-keyset "admin")))
+"#;
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+
+    assert!(code.contains(
+      "/**\n * Maximum allowed decimal precision\n */\nexport const MINIMUM_PRECISION = 12;"
+    ));
+    assert!(code.contains(
+      "export const FEES = { \"transfer\": 0.5, \"tags\": [\"a\", \"b\"], \"enabled\": true };"
+    ));
+    // A number would round the decimal, so it keeps its wire form
+    assert!(code.contains("export const SUPPLY = { decimal: \"123456789.123456789012\" };"));
+    assert!(!code.contains("COIN_CHARSET"));
+    assert!(!code.contains("ADMIN"));
+
+    assert!(types.contains("export const MINIMUM_PRECISION: 12;"));
+    assert!(types.contains(
+      "export const FEES: { readonly \"transfer\": 0.5; readonly \"tags\": readonly [\"a\", \"b\"]; readonly \"enabled\": true };"
+    ));
+    assert!(
+      types.contains("export const SUPPLY: { readonly decimal: \"123456789.123456789012\" };")
+    );
+
+    let (code, _, _, _) = CodeGenerator::new(true)
+      .with_typescript(true)
+      .generate(&modules);
+    assert!(
+      code.contains("export const SUPPLY = { decimal: \"123456789.123456789012\" } as const;")
+    );
+
+    let skipped = skipped_constants(&modules);
+    assert_eq!(skipped.len(), 2);
+    assert_eq!(skipped[0].code, "P0007");
+    assert_eq!(
+      skipped[0].message,
+      "`COIN_CHARSET` is not exported because its value is not a literal"
+    );
+    assert_eq!(skipped[1].severity, crate::error::Severity::Warning);
+  }
+
+  #[test]
+  fn test_runtime_import_and_module_format() {
+    let source = r"
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defconst DECIMALS 12)
+  (defun get-balance:decimal (account:string) 1.0))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let runtime = RuntimeOptions {
+      import: "@acme/pact-runtime".to_string(),
+      execution: "exec".to_string(),
+      ..RuntimeOptions::default()
+    };
+    let (code, types, _, _) = CodeGenerator::new(true)
+      .with_runtime(runtime.clone())
+      .generate(&modules);
+    assert!(code.contains(
+      "import { exec as execution, continuation, encodePactArg, toPactValue, fromPactValue } from \"@acme/pact-runtime\";"
+    ));
+    assert!(code.contains("    return execution(`(coin.get-balance"));
+    assert!(types.contains("PactGuard } from \"@acme/pact-runtime\";"));
+
+    let (code, _, _, _) = CodeGenerator::new(false)
+      .with_runtime(RuntimeOptions {
+        commonjs: true,
+        ..runtime
+      })
+      .generate(&modules);
+    assert!(code.contains(
+      "const { exec: execution, continuation, encodePactArg, toPactValue, fromPactValue } = require(\"@acme/pact-runtime\");"
+    ));
+    assert!(!code.contains("export "));
+    assert!(code.contains("const DECIMALS = 12;"));
+    assert!(code.contains("function getBalance(account) {"));
+    assert!(code.ends_with("module.exports = { DECIMALS, getBalance, governanceCapability };\n"));
+  }
+
+  #[test]
+  fn test_typescript_output() {
+    let source = r#"
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defconst CHAINS ["0" "1"])
+  (defschema account balance:decimal)
+  (defcap TRANSFER (sender:string amount:decimal) @managed amount TRANSFER-mgr true)
+  (defun get-balance:decimal (account:string) 1.0)
+  (defpact cross (a:string)
+    (step-with-rollback (yield { "a": a } "1") (rollback-it))
+    (step (resume { "a" := a } a))))
+"#;
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let (code, types, _, _) = CodeGenerator::new(true)
+      .with_validators(true)
+      .with_typescript(true)
+      .generate(&modules);
+
+    assert!(types.is_empty());
+    assert!(code.contains("import type { PactTransactionBuilder, PactExecPayload, PactContPayload, PactEnvData, PactValue, PactCapability, PactSignerLike, PactEvent, PactInteger, PactDecimal, PactTimeInput, PactKeyset, PactGuard, PactValidationError } from \"@pact-toolbox/transaction\";"));
+
+    // Type declarations come first, then the annotated bindings
+    let schema = code.find("export interface Account {").unwrap();
+    let union = code
+      .find("export type CoinEvent = CoinTransferEvent;")
+      .unwrap();
+    assert!(schema < union && union < code.find("export const CHAINS").unwrap());
+
+    assert!(code.contains("export const CHAINS = [\"0\", \"1\"] as const;"));
+    assert!(code.contains("export function isAccount(value: unknown): value is Account {"));
+    assert!(code.contains(
+      "export function getBalance(account: string): PactTransactionBuilder<PactExecPayload, PactDecimal> {\n    return execution(`(coin.get-balance ${encodePactArg(account, \"string\")})`).withDecoder((value) => fromPactValue(value, \"decimal\"));"
+    ));
+    assert!(code.contains(
+      "export function continueCross(pactId: string, step: 1, options: { proof: string; data?: PactEnvData }): PactTransactionBuilder<PactContPayload>;\nexport function continueCross(pactId: string, step: number, options: { proof?: string; data?: PactEnvData } = {}): PactTransactionBuilder<PactContPayload> {"
+    ));
+    assert!(code.contains(
+      "export function transferCapability(sender: string, amount: PactDecimal): PactCapability {"
+    ));
+    assert!(code.contains(
+      "transferCapability.managed = { parameter: \"amount\", manager: \"TRANSFER-mgr\" } as const;"
+    ));
+    assert!(code.contains("amount: fromPactValue(event.params[1], \"decimal\") }"));
+    assert!(!code.contains("as PactDecimal"));
+    assert!(code.contains("export function isCoinTransferEvent(event: unknown): event is CoinTransferEvent {\n    const candidate = event as CoinTransferEvent | undefined;"));
+    assert!(!code.contains("any"));
+  }
+
+  #[test]
+  fn test_function_effect_annotations() {
+    let source = r"
+(module bank GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (deftable accounts:{account})
+  (defun fee:decimal (amount:decimal) (* amount 0.01))
+  (defun get-balance:decimal (id:string) (at 'balance (read accounts id)))
+  (defun delete:string (id:string) (update accounts id { 'balance: 0.0 })))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+    assert!(code.contains("}\nfee.effect = \"pure\";\n"));
+    assert!(code.contains("}\ngetBalance.effect = \"read-only\";\n"));
+    assert!(code.contains("}\ndelete_.effect = \"mutating\";\n"));
+    assert!(types.contains(
+      "export function getBalance(id: string): PactTransactionBuilder<PactExecPayload, PactDecimal>;\nexport declare namespace getBalance { export const effect: \"read-only\"; }"
+    ));
+
+    let (code, _, _, _) = CodeGenerator::new(true)
+      .with_typescript(true)
+      .generate(&modules);
+    assert!(code.contains("getBalance.effect = \"read-only\" as const;"));
+  }
+
+  #[test]
+  fn test_reserved_and_odd_names() {
+    let source = r#"
+(module registry GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema entry new:bool default-value?:string)
+  (defun delete:string (class:string in:integer)
+    "Remove an entry"
+    "ok")
+  (defun valid?:bool (key:string) true))
+"#;
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+
+    assert!(code.contains(
+      "/**\n * Remove an entry\n * \n * Pact name: `delete`\n */\nexport function delete_(class_, in_) {\n    return execution(`(registry.delete ${encodePactArg(class_, \"string\")} ${encodePactArg(in_, \"integer\")})`);"
+    ));
+    assert!(code.contains("/**\n * Pact name: `valid?`\n */\nexport function valid(key) {"));
+    assert!(code.contains("(registry.valid? ${encodePactArg(key, \"string\")})"));
+
+    assert!(types.contains("export interface Entry {\n  new: boolean;\n  defaultValue: string;\n}"));
+    assert!(types.contains(
+      "export function delete_(class_: string, in_: PactInteger): PactTransactionBuilder<PactExecPayload, string>;"
+    ));
+  }
+
+  #[test]
+  fn test_clashing_names_are_numbered() {
+    let source = r#"
+(module registry GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defcap TRANSFER (from:string) @event true)
+  (defcap transfer (from:string) @event true)
+  (defschema entry get-x:string get_x:integer)
+  (defschema Entry flag:bool)
+  (defun valid:bool () true)
+  (defun valid?:bool () false)
+  (defun get-x:string (a-b:string a_b:string) a-b)
+  (defun get_x:{entry} () (read entries "x")))
+"#;
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let mut generator = CodeGenerator::new(true);
+    let (code, types, _, _) = generator.generate(&modules);
+
+    let messages: Vec<&str> = generator
+      .diagnostics()
+      .iter()
+      .map(|d| d.message.as_str())
+      .collect();
+    assert_eq!(
+      messages,
+      [
+        "`TRANSFER` and `transfer` both become `transferCapability`; `transfer` is named `transfer2Capability`",
+        "`get-x` and `get_x` both become `getX`; `get_x` is named `getX2`",
+        "`entry` and `Entry` both become `Entry`; `Entry` is named `Entry2`",
+        "`valid` and `valid?` both become `valid`; `valid?` is named `valid2`",
+        "`a-b` and `a_b` both become `aB`; `a_b` is named `aB2`",
+        "`get-x` and `get_x` both become `getX`; `get_x` is named `getX2`",
+      ]
+    );
+    assert!(generator
+      .diagnostics()
+      .iter()
+      .all(|d| d.code == "P0009" && d.span.start_line > 0));
+
+    assert!(code.contains("export function valid() {"));
+    assert!(code.contains("/**\n * Pact name: `valid?`\n */\nexport function valid2() {"));
+    assert!(code.contains(
+      "export function getX(aB, aB2) {\n    return execution(`(registry.get-x ${encodePactArg(aB, \"string\")} ${encodePactArg(aB2, \"string\")})`);"
+    ));
+    assert!(code.contains("export function getX2() {"));
+    assert!(code.contains(
+      "return { getX: value[\"get-x\"], getX2: fromPactValue(value[\"get_x\"], \"integer\") };"
+    ));
+    assert!(code.contains("export function transfer2Capability(from) {"));
+    assert!(code.contains("export function isRegistryTransfer2Event(event) {"));
+
+    assert!(types.contains("export interface Entry {\n  getX: string;\n  getX2: PactInteger;\n}"));
+    assert!(types.contains("export interface Entry2 {\n  flag: boolean;\n}"));
+    assert!(types.contains("export function decodeEntry(value: unknown): Entry;"));
+    assert!(types.contains("export interface RegistryTransfer2Event {"));
+
+    let names = &generator.names()[0];
+    assert_eq!(names.module, "registry");
+    assert_eq!(names.functions["valid"], "valid");
+    assert_eq!(names.functions["valid?"], "valid2");
+    assert_eq!(names.functions["get_x"], "getX2");
+    assert_eq!(names.capabilities["TRANSFER"], "transferCapability");
+    assert_eq!(names.capabilities["transfer"], "transfer2Capability");
+    assert_eq!(names.schemas["Entry"], "Entry2");
+  }
+
+  #[test]
+  fn test_namespaced_exports() {
+    let source = r"
+(namespace 'free)
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defcap DEBIT (sender:string amount:decimal) @managed amount DEBIT-mgr true)
+  (defun get-balance:decimal (account:string) 1.0))
+(module token GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defun get-balance:decimal (account:string) 2.0))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let mut flat = CodeGenerator::new(true);
+    flat.generate(&modules);
+    let collisions = flat.diagnostics();
+    let messages: Vec<&str> = collisions.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+      messages,
+      [
+        "`getBalance` is exported by both `free.coin` and `free.token`; set `exportMode: \"namespaced\"` to keep them apart",
+        "`governanceCapability` is exported by both `free.coin` and `free.token`; set `exportMode: \"namespaced\"` to keep them apart",
+      ]
+    );
+    assert_eq!(collisions[0].code, "P0008");
+
+    let mut generator = CodeGenerator::new(true).with_namespaced_exports(true);
+    let (code, types, _, _) = generator.generate(&modules);
+    assert!(generator.diagnostics().is_empty());
+
+    assert!(code.contains("const free_coin = (() => {\n    function getBalance(account) {"));
+    assert!(code.contains(
+      "    return { getBalance, governanceCapability, debitCapability, decodeCoinEvents, isCoinDebitEvent };\n})();"
+    ));
+    assert!(code.ends_with("export const free = { coin: free_coin, token: free_token };"));
+    assert_eq!(code.matches("export ").count(), 1);
+
+    assert!(types.contains(
+      "export declare namespace free.coin {\n  export function getBalance(account: string): PactTransactionBuilder<PactExecPayload, PactDecimal>;"
+    ));
+    assert!(types.contains("  export const debitCapability: {"));
+    assert!(types.contains("export declare namespace free.token {"));
+  }
+
+  #[test]
+  fn test_collisions_follow_the_configuration() {
+    let source = r"
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema entry amount:decimal))
+(module token GOVERNANCE
+  (defcap GOVERNANCE () false)
+  (defschema entry supply:integer))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+    let collided = |mut generator: CodeGenerator| {
+      generator.generate(&modules);
+      let mut names: Vec<String> = generator
+        .diagnostics()
+        .iter()
+        .filter_map(|d| d.message.split('`').nth(1).map(ToString::to_string))
+        .collect();
+      names.sort();
+      names
+    };
+
+    // Declarations are only compared when they are generated
+    assert_eq!(
+      collided(CodeGenerator::new(false)),
+      ["governanceCapability"]
+    );
+    assert_eq!(
+      collided(CodeGenerator::new(true)),
+      ["Entry", "governanceCapability"]
+    );
+    assert_eq!(
+      collided(CodeGenerator::new(false).with_validators(true)),
+      ["governanceCapability", "isEntry", "validateEntry"]
+    );
+
+    // Names a module keeps in its scope stay out of `module.exports`
+    let (code, _, _, _) = CodeGenerator::new(false)
+      .with_namespaced_exports(true)
+      .with_runtime(RuntimeOptions {
+        commonjs: true,
+        ..RuntimeOptions::default()
+      })
+      .generate(&modules);
+    assert!(code.contains("const coin = (() => {\n    function governanceCapability() {"));
+    assert!(code.ends_with("module.exports = { coin, token };\n"));
+  }
+
+  #[test]
+  fn test_namespaced_typescript_types() {
+    let source = r"
+(namespace 'free)
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema account balance:decimal)
+  (defcap DEBIT (sender:string) @event true)
+  (defun get-account:object{account} (id:string) {}))
+(module token GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema account supply:integer)
+  (defun get-account:object{account} (id:string) {}))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let (code, _, _, _) = CodeGenerator::new(true)
+      .with_namespaced_exports(true)
+      .with_typescript(true)
+      .generate(&modules);
+
+    // Each module's schema and event types live in its own namespace
+    assert!(code.contains(
+      "export declare namespace free.coin {\n  export interface Account {\n    balance: PactDecimal;\n  }"
+    ));
+    assert!(code.contains("  export type CoinEvent = CoinDebitEvent;"));
+    assert!(code.contains(
+      "export declare namespace free.token {\n  export interface Account {\n    supply: PactInteger;\n  }"
+    ));
+    assert!(!code.contains("\nexport interface Account"));
+
+    // and the wrapped code refers to them through local aliases
+    assert!(code.contains(
+      "const free_coin = (() => {\n    type Account = free.coin.Account;\n    type CoinDebitEvent = free.coin.CoinDebitEvent;\n    type CoinEvent = free.coin.CoinEvent;\n\n"
+    ));
+    assert!(code.contains("const free_token = (() => {\n    type Account = free.token.Account;\n"));
+  }
+
+  #[test]
+  fn test_result_decoders() {
+    let source = r"
+(module registry GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema entry amount:decimal memo:string)
+  (defschema details owner:string created-at:time entries:[object{entry}] tags:[string])
+  (defschema unused count:integer)
+  (defun get-details:object{details} (id:string) {})
+  (defun get-entries:[object{entry}] () [])
+  (defun get-grid:[[object{entry}]] () [])
+  (defun get-name:string (id:string) id))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let (code, types, _, _) = CodeGenerator::new(true).generate(&modules);
+
+    assert!(code.contains(
+      "export function decodeEntry(value) {\n    return { amount: fromPactValue(value[\"amount\"], \"decimal\"), memo: value[\"memo\"] };\n}"
+    ));
+    assert!(code.contains(
+      "export function decodeDetails(value) {\n    return { owner: value[\"owner\"], createdAt: fromPactValue(value[\"created-at\"], \"time\"), entries: value[\"entries\"].map(decodeEntry), tags: value[\"tags\"] };\n}"
+    ));
+    assert!(!code.contains("decodeUnused"));
+    assert!(code.contains(
+      "return execution(`(registry.get-details ${encodePactArg(id, \"string\")})`).withDecoder(decodeDetails);"
+    ));
+    assert!(code.contains(
+      "return execution(`(registry.get-entries)`).withDecoder((value) => value.map(decodeEntry));"
+    ));
+    assert!(code.contains(
+      "return execution(`(registry.get-grid)`).withDecoder((value) => value.map((item) => item.map(decodeEntry)));"
+    ));
+    assert!(
+      code.contains("return execution(`(registry.get-name ${encodePactArg(id, \"string\")})`);")
+    );
+    assert!(types.contains("export function decodeDetails(value: unknown): Details;"));
+
+    let (code, _, _, _) = CodeGenerator::new(true)
+      .with_typescript(true)
+      .generate(&modules);
+    assert!(code.contains(
+      "export function decodeDetails(value: unknown): Details {\n    const fields = value as Record<string, unknown>;\n    return { owner: fields[\"owner\"] as string, createdAt: fromPactValue(fields[\"created-at\"], \"time\"), entries: (fields[\"entries\"] as unknown[]).map(decodeEntry), tags: fields[\"tags\"] as string[] };\n}"
+    ));
+    assert!(code.contains(
+      ".withDecoder((value) => (value as unknown[]).map((item) => (item as unknown[]).map(decodeEntry)));"
+    ));
+  }
+
+  #[test]
+  fn test_type_codecs() {
+    let source = r"
+(module ledger GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema entry amount:decimal memo:string)
+  (defcap CREDIT (amount:decimal) @event true)
+  (defun credit:decimal (amount:decimal entries:[object{entry}]) amount)
+  (defun totals:[decimal] (amounts:[decimal]) amounts)
+  (defun get-entry:object{entry} (id:string) {}))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let mappings = TypeMappings {
+      types: Some(HashMap::from([(
+        "decimal".to_string(),
+        "Decimal".to_string(),
+      )])),
+      schemas: Some(HashMap::from([(
+        "entry.memo".to_string(),
+        "Memo".to_string(),
+      )])),
+      imports: Some(vec![
+        "import type { Decimal } from \"decimal.js\"".to_string(),
+        "import type { Memo } from \"./memo\"".to_string(),
+      ]),
+      codecs: Some(HashMap::from([
+        (
+          "decimal".to_string(),
+          TypeCodec {
+            from: "./decimal".to_string(),
+            decode: Some("toDecimal".to_string()),
+            encode: Some("fromDecimal".to_string()),
+          },
+        ),
+        (
+          "entry.memo".to_string(),
+          TypeCodec {
+            from: "./memo".to_string(),
+            decode: Some("parseMemo".to_string()),
+            encode: None,
+          },
+        ),
+      ])),
+    };
+    let mapper = || TypeMapper::new(Some(&mappings));
+
+    // Mapped values go through the encoder before Pact encoding and the decoder after it
+    let (code, _, _, _) = CodeGenerator::new(true)
+      .with_type_mapper(mapper())
+      .generate(&modules);
+    assert!(code.contains(
+      "from \"@pact-toolbox/transaction\";\nimport { fromDecimal, toDecimal } from \"./decimal\";\nimport { parseMemo } from \"./memo\";\n\n"
+    ));
+    assert!(code.contains(
+      "return execution(`(ledger.credit ${encodePactArg(fromDecimal(amount), \"decimal\")} ${encodePactArg(entries, \"[object{entry}]\", encodeEntry)})`).withDecoder(toDecimal);"
+    ));
+    assert!(code.contains(
+      "return execution(`(ledger.totals ${encodePactArg(amounts.map(fromDecimal), \"[decimal]\")})`).withDecoder((value) => value.map(toDecimal));"
+    ));
+    assert!(code.contains(
+      "return { amount: toDecimal(value[\"amount\"]), memo: parseMemo(value[\"memo\"]) };"
+    ));
+    // A field without an encoder is encoded as it is
+    assert!(code.contains(
+      "return `{\"amount\": ${encodePactArg(fromDecimal(value.amount), \"decimal\")}, \"memo\": ${encodePactArg(value.memo, \"string\")}}`;"
+    ));
+    assert!(code.contains("args: [toPactValue(fromDecimal(amount), \"decimal\")]"));
+    assert!(code.contains("params: { amount: toDecimal(event.params[0]) }"));
+
+    let (code, _, _, _) = CodeGenerator::new(true)
+      .with_runtime(RuntimeOptions {
+        commonjs: true,
+        ..RuntimeOptions::default()
+      })
+      .with_type_mapper(mapper())
+      .generate(&modules);
+    assert!(code.contains("\nconst { fromDecimal, toDecimal } = require(\"./decimal\");\n"));
+
+    let (code, _, _, _) = CodeGenerator::new(true)
+      .with_typescript(true)
+      .with_type_mapper(mapper())
+      .generate(&modules);
+    assert!(
+      code.contains("import { parseMemo } from \"./memo\";\nimport type { PactTransactionBuilder")
+    );
+    assert!(code.contains(
+      "export function totals(amounts: Decimal[]): PactTransactionBuilder<PactExecPayload, Decimal[]> {"
+    ));
+    assert!(code.contains(".withDecoder((value) => (value as unknown[]).map(toDecimal));"));
+  }
+
+  #[test]
+  fn test_type_mappings() {
+    let source = r"
+(module ledger GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema entry amount:decimal memo:string)
+  (defschema account balance:decimal)
+  (defcap CREDIT (amount:decimal) @event true)
+  (defun credit:object{account} (amount:decimal entries:[object{entry}]) {}))
+";
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    // Mapped types must describe values as Pact sends them, since nothing converts them
+    let mappings = TypeMappings {
+      types: Some(HashMap::from([(
+        "decimal".to_string(),
+        "Amount".to_string(),
+      )])),
+      schemas: Some(HashMap::from([
+        ("account".to_string(), "AccountRow".to_string()),
+        ("entry.memo".to_string(), "Memo".to_string()),
+      ])),
+      imports: Some(vec![
+        "import type { Amount, Memo } from \"./amounts\";".to_string(),
+        "import type { AccountRow } from \"./rows\"".to_string(),
+      ]),
+      codecs: None,
+    };
+    let mapper = || TypeMapper::new(Some(&mappings));
+
+    let (_, types, _, _) = CodeGenerator::new(true)
+      .with_type_mapper(mapper())
+      .generate(&modules);
+    assert!(types.contains(
+      "from \"@pact-toolbox/transaction\";\nimport type { Amount, Memo } from \"./amounts\";\nimport type { AccountRow } from \"./rows\";\n\n"
+    ));
+    assert!(types.contains("export interface Entry {\n  amount: Amount;\n  memo: Memo;\n}"));
+    assert!(types.contains("export interface Account {\n  balance: Amount;\n}"));
+    assert!(types.contains(
+      "export function credit(amount: Amount, entries: Entry[]): PactTransactionBuilder<PactExecPayload, AccountRow>;"
+    ));
+    assert!(types.contains("  params: { amount: Amount };"));
+
+    let (code, _, _, _) = CodeGenerator::new(true)
+      .with_typescript(true)
+      .with_type_mapper(mapper())
+      .generate(&modules);
+    assert!(code.contains("import type { Amount, Memo } from \"./amounts\";\n"));
+    // Mapped values are encoded by their Pact type and returned undecoded
+    assert!(code.contains(
+      "return execution(`(ledger.credit ${encodePactArg(amount, \"decimal\")} ${encodePactArg(entries, \"[object{entry}]\", encodeEntry)})`);"
+    ));
+    assert!(code.contains(
+      "return `{\"amount\": ${encodePactArg(value.amount, \"decimal\")}, \"memo\": ${encodePactArg(value.memo, \"string\")}}`;"
+    ));
+    assert!(code.contains("amount: event.params[0] as Amount"));
+    assert!(!code.contains("decodeAccount"));
+  }
+
   #[test]
   fn test_multiple_modules() {
     let mut module1 = PactModule::with_namespace(
@@ -914,10 +3589,16 @@ mod tests {
       parameters: vec![PactParameter {
         name: "id".to_string(),
         parameter_type: Some("string".to_string()),
+        span: None,
       }],
       return_type: Some("string".to_string()),
       body: "...".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module1.add_function(function1);
 
@@ -930,6 +3611,11 @@ mod tests {
       return_type: Some("integer".to_string()),
       body: "...".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module2.add_function(function2);
 
@@ -937,9 +3623,12 @@ mod tests {
     let (code, _, _, _) = generator.generate(&[module1, module2]);
 
     assert!(code.contains("export function createTodo(id) {"));
-    assert!(code.contains("return execution(`(free.todos.create-todo ${JSON.stringify(id)})`);"));
+    assert!(code
+      .contains("return execution(`(free.todos.create-todo ${encodePactArg(id, \"string\")})`);"));
     assert!(code.contains("export function increment() {"));
-    assert!(code.contains("return execution(`(counters.increment)`);"));
+    assert!(code.contains(
+      "return execution(`(counters.increment)`).withDecoder((value) => fromPactValue(value, \"integer\"));"
+    ));
   }
 
   #[test]
@@ -954,6 +3643,11 @@ mod tests {
         return_type: Some("object{item}".to_string()),
         body: "...".to_string(),
         is_defun: true,
+        steps: vec![],
+        effect: PactEffect::Mutating,
+        capabilities: vec![],
+        span: None,
+        body_exprs: None,
       },
       PactFunction {
         name: "get-items".to_string(),
@@ -962,6 +3656,11 @@ mod tests {
         return_type: Some("[object{item}]".to_string()),
         body: "...".to_string(),
         is_defun: true,
+        steps: vec![],
+        effect: PactEffect::Mutating,
+        capabilities: vec![],
+        span: None,
+        body_exprs: None,
       },
       PactFunction {
         name: "get-strings".to_string(),
@@ -970,6 +3669,11 @@ mod tests {
         return_type: Some("[string]".to_string()),
         body: "...".to_string(),
         is_defun: true,
+        steps: vec![],
+        effect: PactEffect::Mutating,
+        capabilities: vec![],
+        span: None,
+        body_exprs: None,
       },
       PactFunction {
         name: "get-guard".to_string(),
@@ -978,6 +3682,11 @@ mod tests {
         return_type: Some("guard".to_string()),
         body: "...".to_string(),
         is_defun: true,
+        steps: vec![],
+        effect: PactEffect::Mutating,
+        capabilities: vec![],
+        span: None,
+        body_exprs: None,
       },
     ];
 
@@ -991,7 +3700,7 @@ mod tests {
     assert!(types.contains("PactTransactionBuilder<PactExecPayload, Item>;"));
     assert!(types.contains("PactTransactionBuilder<PactExecPayload, Item[]>;"));
     assert!(types.contains("PactTransactionBuilder<PactExecPayload, string[]>;"));
-    assert!(types.contains("PactTransactionBuilder<PactExecPayload, object>;"));
+    assert!(types.contains("PactTransactionBuilder<PactExecPayload, PactGuard>;"));
   }
 
   #[test]
@@ -1004,10 +3713,16 @@ mod tests {
       parameters: vec![PactParameter {
         name: "param".to_string(),
         parameter_type: Some("string".to_string()),
+        span: None,
       }],
       return_type: Some("string".to_string()),
       body: "...".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module.add_function(function);
 
@@ -1069,15 +3784,22 @@ mod tests {
         PactParameter {
           name: "no-type".to_string(),
           parameter_type: None, // Test missing type
+          span: None,
         },
         PactParameter {
           name: "with-type".to_string(),
           parameter_type: Some("string".to_string()),
+          span: None,
         },
       ],
       return_type: Some("bool".to_string()),
       body: "...".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module.add_function(function);
 
@@ -1099,6 +3821,11 @@ mod tests {
       return_type: Some("string".to_string()),
       body: "...".to_string(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
     module.add_function(function);
 
@@ -1117,4 +3844,179 @@ mod tests {
     assert!(code.is_empty());
     assert!(types.is_empty());
   }
+
+  fn transfer_signature(doc: Option<&str>) -> PactFunction {
+    PactFunction {
+      name: "transfer".to_string(),
+      doc: doc.map(str::to_string),
+      parameters: vec![
+        PactParameter {
+          name: "sender".to_string(),
+          parameter_type: Some("string".to_string()),
+          span: None,
+        },
+        PactParameter {
+          name: "amount".to_string(),
+          parameter_type: Some("decimal".to_string()),
+          span: None,
+        },
+      ],
+      return_type: Some("string".to_string()),
+      body: String::new(),
+      is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
+    }
+  }
+
+  #[test]
+  fn test_interface_generation() {
+    let mut interface = PactInterface::new("fungible-v2".to_string(), None);
+    interface.doc = Some("Standard for fungible coins".to_string());
+    interface
+      .functions
+      .push(transfer_signature(Some("Transfer funds")));
+    interface.schemas.push(PactSchema {
+      name: "account-details".to_string(),
+      doc: None,
+      fields: vec![SchemaField {
+        name: "balance".to_string(),
+        field_type: "decimal".to_string(),
+        span: None,
+      }],
+      span: None,
+    });
+
+    let mut generator = CodeGenerator::new(true).with_interfaces(&[interface]);
+    let (code, types, _, _) = generator.generate(&[]);
+
+    // Interfaces have no runtime representation
+    assert!(code.is_empty());
+    assert!(types
+      .contains("import { PactTransactionBuilder, PactExecPayload, PactContPayload, PactEnvData, PactValue, PactCapability, PactSignerLike, PactEvent, PactInteger, PactDecimal, PactTimeInput, PactKeyset, PactGuard }"));
+    assert!(types.contains("export interface AccountDetails {\n  balance: PactDecimal;\n}"));
+    assert!(types.contains(
+      "/**\n * Standard for fungible coins\n */\nexport interface FungibleV2 {\n  /**\n   * Transfer funds\n   */\n  transfer(sender: string, amount: PactDecimal): PactTransactionBuilder<PactExecPayload, string>;\n}"
+    ));
+  }
+
+  #[test]
+  fn test_module_typed_against_interface() {
+    let interface = PactInterface::new("fungible-v2".to_string(), None);
+
+    let mut module = PactModule::new("coin".to_string(), "governance".to_string());
+    module.implements.push("fungible-v2".to_string());
+    module.implements.push("unknown-iface".to_string());
+    module.add_function(transfer_signature(None));
+
+    let mut generator = CodeGenerator::new(true).with_interfaces(&[interface]);
+    let (_, types, _, _) = generator.generate(&[module]);
+
+    assert!(types.contains(
+      "export interface CoinModule extends FungibleV2 {\n  transfer(sender: string, amount: PactDecimal): PactTransactionBuilder<PactExecPayload, string>;\n}"
+    ));
+    assert!(!types.contains("UnknownIface"));
+  }
+
+  #[test]
+  fn test_module_without_known_interface() {
+    let mut module = PactModule::new("coin".to_string(), "governance".to_string());
+    module.implements.push("fungible-v2".to_string());
+    module.add_function(transfer_signature(None));
+
+    let mut generator = CodeGenerator::new(true);
+    let (_, types, _, _) = generator.generate(&[module]);
+
+    assert!(!types.contains("CoinModule"));
+  }
+
+  #[test]
+  fn test_source_maps_follow_spans() {
+    use sourcemap::SourceMap;
+
+    let source = r#"(module first GOVERNANCE
+  (defschema item name:string)
+  (defun get-item:string (id:string) id))
+
+(module second GOVERNANCE
+      (defun   get-item:string (key:string) key)
+  (defun user-id:string (id:string) id))
+
+(module third GOVERNANCE
+  (defschema entry "Größe 😀" size:integer))"#;
+
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let mut generator = CodeGenerator::new_with_source_maps(
+      true,
+      SourceMapOptions {
+        declaration_map: Some(true),
+        ..SourceMapOptions::default()
+      },
+      source,
+      "/project/items.pact",
+      &modules,
+    );
+    let (code, types, source_map, declaration_map) = generator.generate(&modules);
+
+    // Source maps no longer change the emitted code
+    let mut plain = CodeGenerator::new(true);
+    let (plain_code, plain_types, _, _) = plain.generate(&modules);
+    assert_eq!(code, plain_code);
+    assert_eq!(types, plain_types);
+
+    let lookup = |map: &SourceMap, text: &str, needle: &str, nth: usize| {
+      let (line_idx, line) = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.contains(needle))
+        .nth(nth)
+        .unwrap();
+      let column = line[..line.find(needle).unwrap()].encode_utf16().count() as u32;
+      let token = map.lookup_token(line_idx as u32, column).unwrap();
+      assert_eq!(token.get_source(), Some("items.pact"));
+      (token.get_src_line() + 1, token.get_src_col())
+    };
+
+    let js_map = SourceMap::from_slice(source_map.unwrap().as_bytes()).unwrap();
+    assert_eq!(lookup(&js_map, &code, "getItem(", 0), (3, 2));
+    assert_eq!(lookup(&js_map, &code, "getItem(", 1), (6, 6));
+    assert_eq!(lookup(&js_map, &code, "key)", 0), (6, 32));
+
+    // A parameter named like part of an earlier identifier still maps to itself
+    assert_eq!(lookup(&js_map, &code, "userId(id)", 0).0, 7);
+    let line = code.lines().position(|l| l.contains("userId(id)")).unwrap();
+    let column = code.lines().nth(line).unwrap().find("(id)").unwrap() as u32 + 1;
+    let token = js_map.lookup_token(line as u32, column).unwrap();
+    assert_eq!((token.get_src_line() + 1, token.get_src_col()), (7, 25));
+
+    let dts_map = SourceMap::from_slice(declaration_map.unwrap().as_bytes()).unwrap();
+    assert_eq!(lookup(&dts_map, &types, "Item {", 0), (2, 2));
+    assert_eq!(lookup(&dts_map, &types, "name: string", 0), (2, 18));
+    assert_eq!(lookup(&dts_map, &types, "getItem(", 1), (6, 6));
+    // Columns count UTF-16 code units, in the Pact source as in the output
+    assert_eq!(lookup(&dts_map, &types, "size: PactInteger", 0), (10, 30));
+
+    // Positions survive the indentation of namespaced exports and CommonJS output
+    let mut generator = CodeGenerator::new_with_source_maps(
+      false,
+      SourceMapOptions::default(),
+      source,
+      "/project/items.pact",
+      &modules,
+    )
+    .with_namespaced_exports(true)
+    .with_runtime(RuntimeOptions {
+      commonjs: true,
+      ..RuntimeOptions::default()
+    });
+    let (code, _, source_map, _) = generator.generate(&modules);
+    let js_map = SourceMap::from_slice(source_map.unwrap().as_bytes()).unwrap();
+    assert_eq!(lookup(&js_map, &code, "getItem(", 1), (6, 6));
+    assert_eq!(lookup(&js_map, &code, "key)", 0), (6, 32));
+  }
 }
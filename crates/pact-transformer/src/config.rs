@@ -534,6 +534,16 @@ format = "ts"
           source_maps: None,
           source_file_path: None,
           declaration_maps: None,
+          namespace: None,
+          error_format: None,
+          recover: None,
+          validators: None,
+          runtime_import: None,
+          execution_builder: None,
+          continuation_builder: None,
+          module_format: None,
+          export_mode: None,
+          type_mappings: None,
         }),
         file_output: Some(FileOutputOptions {
           output_dir: "./src/generated".to_string(),
@@ -612,6 +622,16 @@ format = "ts"
         source_maps: None,
         source_file_path: None,
         declaration_maps: None,
+        namespace: None,
+        error_format: None,
+        recover: None,
+        validators: None,
+        runtime_import: None,
+        execution_builder: None,
+        continuation_builder: None,
+        module_format: None,
+        export_mode: None,
+        type_mappings: None,
       }),
       file_output: Some(FileOutputOptions {
         output_dir: "./dist".to_string(),
@@ -0,0 +1,351 @@
+use crate::ast::SourceSpan;
+use crate::source_map::SourceMark;
+use std::fmt;
+
+/// Whether an exported name is a runtime value or only a type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+  Value,
+  Type,
+}
+
+/// A name the generated code exports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+  pub name: String,
+  pub kind: ExportKind,
+}
+
+/// Generated text, the names it exports and where mapped Pact definitions landed in it
+///
+/// Declarations are started with [`Emitter::export`], so wrapping a module in a scope or
+/// listing it in `module.exports` works from the recorded names instead of reading the text back.
+/// Positions are tracked as the text is written, in UTF-16 code units like source maps count them.
+#[derive(Debug, Clone)]
+pub struct Emitter {
+  text: String,
+  indent: String,
+  line_start: bool,
+  scoped: bool,
+  ambient: bool,
+  exports: Vec<Export>,
+  /// 1-based line and UTF-16 column the next character is written at
+  line: u32,
+  column: u32,
+  marks: Vec<SourceMark>,
+}
+
+impl Default for Emitter {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Emitter {
+  pub fn new() -> Self {
+    Self {
+      text: String::with_capacity(4096),
+      indent: String::new(),
+      line_start: true,
+      scoped: false,
+      ambient: false,
+      exports: Vec::new(),
+      line: 1,
+      column: 0,
+      marks: Vec::new(),
+    }
+  }
+
+  /// Prefix written before every non-empty line
+  #[must_use]
+  pub fn with_indent(mut self, indent: &str) -> Self {
+    self.indent = indent.to_string();
+    self
+  }
+
+  /// Record exports without writing `export`, for code inside a scope or `module.exports`
+  #[must_use]
+  pub fn with_scoped_exports(mut self, scoped: bool) -> Self {
+    self.scoped = scoped;
+    self
+  }
+
+  /// Declarations go inside a `declare namespace`, where they are already ambient
+  #[must_use]
+  pub fn with_ambient(mut self, ambient: bool) -> Self {
+    self.ambient = ambient;
+    self
+  }
+
+  /// An empty emitter writing the same way, for a part generated on its own and appended
+  pub fn part(&self) -> Self {
+    Self {
+      text: String::with_capacity(1024),
+      indent: self.indent.clone(),
+      line_start: true,
+      scoped: self.scoped,
+      ambient: self.ambient,
+      exports: Vec::new(),
+      line: 1,
+      column: 0,
+      marks: Vec::new(),
+    }
+  }
+
+  /// Start the declaration of an exported name; a name declared again, such as a
+  /// namespace merged into a function, is recorded once
+  pub fn export(&mut self, kind: ExportKind, name: &str) {
+    if !self
+      .exports
+      .iter()
+      .any(|export| export.kind == kind && export.name == name)
+    {
+      self.exports.push(Export {
+        name: name.to_string(),
+        kind,
+      });
+    }
+    if !self.scoped {
+      self.push_str("export ");
+    }
+  }
+
+  /// `declare`, unless the declaration is already ambient
+  pub fn declare(&mut self) {
+    if !self.ambient {
+      self.push_str("declare ");
+    }
+  }
+
+  /// Map the text written next to the definition at `span`
+  pub fn mark(&mut self, span: Option<SourceSpan>, name: &str) {
+    let Some(span) = span else {
+      return;
+    };
+    // The mark belongs after the indentation of the line it starts
+    if self.line_start {
+      self.write_indent();
+    }
+    self.marks.push(SourceMark {
+      generated_line: self.line,
+      generated_column: self.column,
+      source_line: span.start_line,
+      source_column: span.start_column,
+      name: name.to_string(),
+    });
+  }
+
+  pub fn push_str(&mut self, text: &str) {
+    let mut rest = text;
+    while !rest.is_empty() {
+      let (line, next) = rest.split_at(rest.find('\n').map_or(rest.len(), |end| end + 1));
+      if self.line_start && line != "\n" {
+        self.write_indent();
+      }
+      self.text.push_str(line);
+      if line.ends_with('\n') {
+        self.line += 1;
+        self.column = 0;
+        self.line_start = true;
+      } else {
+        self.column += utf16_len(line);
+      }
+      rest = next;
+    }
+  }
+
+  fn write_indent(&mut self) {
+    self.text.push_str(&self.indent);
+    self.column += utf16_len(&self.indent);
+    self.line_start = false;
+  }
+
+  pub fn push(&mut self, c: char) {
+    self.push_str(c.encode_utf8(&mut [0; 4]));
+  }
+
+  /// Append a part with the exports and marks it recorded; the part is already indented
+  pub fn append(&mut self, part: Emitter) {
+    for mut mark in part.marks {
+      if mark.generated_line == 1 {
+        mark.generated_column += self.column;
+      }
+      mark.generated_line += self.line - 1;
+      self.marks.push(mark);
+    }
+    if !part.text.is_empty() {
+      self.text.push_str(&part.text);
+      self.line_start = part.line_start;
+      if part.line > 1 {
+        self.line += part.line - 1;
+        self.column = part.column;
+      } else {
+        self.column += part.column;
+      }
+    }
+    for export in part.exports {
+      self.export_quietly(export);
+    }
+  }
+
+  fn export_quietly(&mut self, export: Export) {
+    if !self.exports.contains(&export) {
+      self.exports.push(export);
+    }
+  }
+
+  /// Start a new line unless the text already ends with one
+  pub fn end_line(&mut self) {
+    if !self.line_start {
+      self.push('\n');
+    }
+  }
+
+  /// Drop leading blank lines and trailing whitespace
+  pub fn trim(&mut self) {
+    self.text.truncate(self.text.trim_end().len());
+    let blank = self.text.len() - self.text.trim_start().len();
+    let start = self.text[..blank].rfind('\n').map_or(0, |end| end + 1);
+    let removed = self.text[..start].matches('\n').count() as u32;
+    self.text.drain(..start);
+    for mark in &mut self.marks {
+      mark.generated_line -= removed;
+    }
+
+    let last_line = self.text.rsplit('\n').next().unwrap_or_default();
+    self.line = self.text.matches('\n').count() as u32 + 1;
+    self.column = utf16_len(last_line);
+    self.line_start = self.text.is_empty();
+  }
+
+  /// Exported names in the order they were declared
+  pub fn exports(&self) -> &[Export] {
+    &self.exports
+  }
+
+  /// Take the recorded exports, e.g. once a scope has returned them
+  pub fn take_exports(&mut self) -> Vec<Export> {
+    std::mem::take(&mut self.exports)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.text.is_empty()
+  }
+
+  /// The text and the marks recorded in it
+  pub fn finish(self) -> (String, Vec<SourceMark>) {
+    (self.text, self.marks)
+  }
+}
+
+fn utf16_len(text: &str) -> u32 {
+  text.encode_utf16().count() as u32
+}
+
+impl fmt::Write for Emitter {
+  fn write_str(&mut self, text: &str) -> fmt::Result {
+    self.push_str(text);
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fmt::Write;
+
+  #[test]
+  fn test_indents_and_records_exports() {
+    let mut code = Emitter::new().with_indent("    ").with_scoped_exports(true);
+    code.export(ExportKind::Value, "transfer");
+    writeln!(code, "function transfer() {{\n    return 1;\n}}\n").unwrap();
+
+    let mut part = code.part();
+    part.export(ExportKind::Value, "details");
+    part.push_str("const details = 2;");
+    code.append(part);
+    code.end_line();
+
+    let names: Vec<&str> = code.exports().iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["transfer", "details"]);
+    assert_eq!(
+      code.finish().0,
+      "    function transfer() {\n        return 1;\n    }\n\n    const details = 2;\n"
+    );
+  }
+
+  #[test]
+  fn test_export_keywords() {
+    let mut types = Emitter::new();
+    types.export(ExportKind::Value, "record");
+    types.push_str("function record(): void;\n");
+    types.export(ExportKind::Value, "record");
+    types.declare();
+    types.push_str("namespace record {}\n");
+    assert_eq!(types.exports().len(), 1);
+    assert_eq!(
+      types.finish().0,
+      "export function record(): void;\nexport declare namespace record {}\n"
+    );
+
+    let mut ambient = Emitter::new().with_ambient(true);
+    ambient.export(ExportKind::Value, "fee");
+    ambient.declare();
+    ambient.push_str("const fee: 1;");
+    assert_eq!(ambient.finish().0, "export const fee: 1;");
+  }
+
+  #[test]
+  fn test_trim_keeps_indentation() {
+    let mut types = Emitter::new().with_indent("  ");
+    types.push_str("\n\ninterface A {}\n\n");
+    types.trim();
+    assert_eq!(types.finish().0, "  interface A {}");
+  }
+
+  #[test]
+  fn test_marks_count_utf16_columns() {
+    let span = |line| {
+      Some(SourceSpan {
+        start_line: line,
+        start_column: 2,
+        ..SourceSpan::default()
+      })
+    };
+    let mut code = Emitter::new().with_indent("  ");
+    code.push_str("const note = \"日本 😀\";\n");
+    code.mark(span(3), "user-id");
+    code.push_str("userId(");
+
+    // A part keeps its marks when appended after the text before it
+    let mut part = Emitter::new();
+    part.push_str("😀, ");
+    part.mark(span(4), "id");
+    part.push_str("id) {}\n");
+    part.mark(span(5), "next");
+    part.push_str("next");
+    code.append(part);
+    code.mark(None, "unmapped");
+
+    let (text, marks) = code.finish();
+    assert_eq!(
+      text,
+      "  const note = \"日本 😀\";\n  userId(😀, id) {}\nnext"
+    );
+    let positions: Vec<_> = marks
+      .iter()
+      .map(|mark| {
+        (
+          mark.generated_line,
+          mark.generated_column,
+          mark.name.as_str(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      positions,
+      vec![(2, 2, "user-id"), (2, 13, "id"), (3, 0, "next")]
+    );
+    assert_eq!((marks[0].source_line, marks[0].source_column), (3, 2));
+  }
+}
@@ -1,4 +1,8 @@
+use crate::ast::SourceSpan;
 use napi::Error as NapiError;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -25,19 +29,315 @@ impl From<TransformError> for NapiError {
   }
 }
 
-#[derive(Debug, Clone)]
-pub struct ParseError {
+/// Stable diagnostic codes; new codes are appended, existing ones never change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticCode {
+  /// Tree-sitter produced no tree at all
+  ParseFailed,
+  /// Input the grammar could not place
+  UnexpectedInput,
+  /// `(`, `[` or `{` without its closing delimiter
+  UnclosedDelimiter,
+  /// Closing delimiter without a matching opener
+  UnexpectedDelimiter,
+  /// A token the grammar requires is absent
+  MissingToken,
+  /// `(module name ...)` without a governance keyset or capability
+  MissingGovernance,
+  /// A definition left out of recovery-mode output because it contains errors
+  SkippedDefinition,
+  /// A `defconst` whose value is computed, so it cannot be exported to JavaScript
+  NonLiteralConstant,
+  /// The same name exported by more than one module of a file
+  ExportCollision,
+  /// A definition renamed because its escaped name is taken by another in the same scope
+  NameClash,
+  /// A capability acquired under a local name that the module doesn't define
+  UnknownCapability,
+}
+
+impl DiagnosticCode {
+  const ALL: [Self; 11] = [
+    Self::ParseFailed,
+    Self::UnexpectedInput,
+    Self::UnclosedDelimiter,
+    Self::UnexpectedDelimiter,
+    Self::MissingToken,
+    Self::MissingGovernance,
+    Self::SkippedDefinition,
+    Self::NonLiteralConstant,
+    Self::ExportCollision,
+    Self::NameClash,
+    Self::UnknownCapability,
+  ];
+
+  pub fn from_code(code: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|c| c.as_str() == code)
+  }
+
+  /// Short caret label used in code frames
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::ParseFailed => "could not parse",
+      Self::UnexpectedInput => "unexpected input",
+      Self::UnclosedDelimiter => "unclosed delimiter",
+      Self::UnexpectedDelimiter => "unmatched delimiter",
+      Self::MissingToken => "missing token",
+      Self::MissingGovernance => "missing governance",
+      Self::SkippedDefinition => "skipped",
+      Self::NonLiteralConstant => "not a literal",
+      Self::ExportCollision => "duplicate export",
+      Self::NameClash => "renamed",
+      Self::UnknownCapability => "unknown capability",
+    }
+  }
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::ParseFailed => "P0000",
+      Self::UnexpectedInput => "P0001",
+      Self::UnclosedDelimiter => "P0002",
+      Self::UnexpectedDelimiter => "P0003",
+      Self::MissingToken => "P0004",
+      Self::MissingGovernance => "P0005",
+      Self::SkippedDefinition => "P0006",
+      Self::NonLiteralConstant => "P0007",
+      Self::ExportCollision => "P0008",
+      Self::NameClash => "P0009",
+      Self::UnknownCapability => "P0010",
+    }
+  }
+}
+
+#[napi(string_enum)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+  Error,
+  Warning,
+  Info,
+}
+
+/// A problem found in Pact source
+#[napi(object)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+  /// Stable identifier such as `P0002`
+  pub code: String,
+  pub severity: Severity,
   pub message: String,
-  pub line: usize,
-  pub column: usize,
+  pub span: SourceSpan,
 }
 
-impl ParseError {
-  pub fn new(message: String, line: usize, column: usize) -> Self {
+impl Diagnostic {
+  pub fn error(code: DiagnosticCode, message: String, span: SourceSpan) -> Self {
     Self {
+      code: code.as_str().to_string(),
+      severity: Severity::Error,
       message,
-      line,
-      column,
+      span,
     }
   }
+
+  pub fn warning(code: DiagnosticCode, message: String, span: SourceSpan) -> Self {
+    Self {
+      severity: Severity::Warning,
+      ..Self::error(code, message, span)
+    }
+  }
+}
+
+impl Severity {
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::Error => "error",
+      Self::Warning => "warning",
+      Self::Info => "info",
+    }
+  }
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let severity = self.severity.as_str();
+    write!(
+      f,
+      "{}:{}: {severity}[{}]: {}",
+      self.span.start_line,
+      self.span.start_column + 1,
+      self.code,
+      self.message
+    )
+  }
+}
+
+/// Render diagnostics as rustc-style code frames over `source`
+pub fn render_code_frames(diagnostics: &[Diagnostic], source: &str, file_name: &str) -> String {
+  diagnostics
+    .iter()
+    .map(|diagnostic| render_code_frame(diagnostic, source, file_name))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Render a single diagnostic with the source lines it covers
+///
+/// ```text
+/// error[P0002]: Unclosed `(` in module; expected `)` at 2:17
+///  --> coin.pact:1:1
+///   |
+/// 1 | (module m GOVERNANCE
+///   | ^ unclosed delimiter
+/// ```
+pub fn render_code_frame(diagnostic: &Diagnostic, source: &str, file_name: &str) -> String {
+  let span = &diagnostic.span;
+  let lines: Vec<&str> = source.lines().collect();
+  let start_line = span.start_line.max(1) as usize;
+  let end_line = (span.end_line as usize).max(start_line);
+  let gutter = " ".repeat(end_line.to_string().len());
+  let label = DiagnosticCode::from_code(&diagnostic.code).map_or("", DiagnosticCode::label);
+
+  let mut out = format!(
+    "{}[{}]: {}\n{gutter}--> {file_name}:{start_line}:{}\n{gutter} |\n",
+    diagnostic.severity.as_str(),
+    diagnostic.code,
+    diagnostic.message,
+    span.start_column + 1
+  );
+
+  let Some(first) = lines.get(start_line - 1) else {
+    return out;
+  };
+
+  let multiline = end_line > start_line && end_line <= lines.len();
+  let first_end = if multiline {
+    first.len()
+  } else {
+    span.end_column as usize
+  };
+  push_source_line(&mut out, &gutter, start_line, first);
+  push_carets(
+    &mut out,
+    &gutter,
+    first,
+    span.start_column as usize,
+    first_end,
+    if multiline { "" } else { label },
+  );
+
+  if multiline {
+    if end_line > start_line + 1 {
+      out.push_str("...\n");
+    }
+    let last = lines[end_line - 1];
+    push_source_line(&mut out, &gutter, end_line, last);
+    push_carets(&mut out, &gutter, last, 0, span.end_column as usize, label);
+  }
+
+  out
+}
+
+fn push_source_line(out: &mut String, gutter: &str, number: usize, line: &str) {
+  let _ = writeln!(out, "{number:>width$} | {line}", width = gutter.len());
+}
+
+/// Underline `start..end` (byte columns) of `line`, keeping tabs so carets line up
+fn push_carets(out: &mut String, gutter: &str, line: &str, start: usize, end: usize, label: &str) {
+  let prefix = line.get(..start.min(line.len())).unwrap_or(line);
+  let indent: String = prefix
+    .chars()
+    .map(|c| if c == '\t' { '\t' } else { ' ' })
+    .collect();
+  let width = line
+    .get(start.min(line.len())..end.min(line.len()))
+    .map_or(0, |s| s.chars().count())
+    .max(1);
+  let carets = "^".repeat(width);
+  let line_out = format!("{gutter} | {indent}{carets} {label}");
+  out.push_str(line_out.trim_end());
+  out.push('\n');
+}
+
+/// Diagnostics as a JSON array for tooling, each with its rendered code frame
+pub fn diagnostics_to_json(diagnostics: &[Diagnostic], source: &str, file_name: &str) -> String {
+  let entries: Vec<serde_json::Value> = diagnostics
+    .iter()
+    .map(|diagnostic| {
+      let span = &diagnostic.span;
+      serde_json::json!({
+        "code": diagnostic.code,
+        "severity": diagnostic.severity.as_str(),
+        "message": diagnostic.message,
+        "file": file_name,
+        "start": { "line": span.start_line, "column": span.start_column + 1, "offset": span.start_byte },
+        "end": { "line": span.end_line, "column": span.end_column + 1, "offset": span.end_byte },
+        "rendered": render_code_frame(diagnostic, source, file_name),
+      })
+    })
+    .collect();
+  serde_json::Value::Array(entries).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parser::Parser;
+
+  fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    Parser::new().parse(source).1
+  }
+
+  #[test]
+  fn test_render_code_frame() {
+    let source = "(module m GOVERNANCE\n  (defun f () 1)";
+    let rendered = render_code_frames(&diagnostics(source), source, "broken.pact");
+
+    assert_eq!(
+      rendered,
+      "error[P0002]: Unclosed `(` in module; expected `)` at 2:17\n\
+       \x20--> broken.pact:1:1\n\
+       \x20 |\n\
+       1 | (module m GOVERNANCE\n\
+       \x20 | ^ unclosed delimiter\n"
+    );
+  }
+
+  #[test]
+  fn test_render_code_frame_multiline_span() {
+    let source = "(module m g\n  (defun f () 1))\n(module)\n";
+    let diagnostic = Diagnostic::error(
+      DiagnosticCode::UnexpectedInput,
+      "Unexpected input".to_string(),
+      SourceSpan {
+        start_byte: 2,
+        end_byte: 27,
+        start_line: 1,
+        start_column: 2,
+        end_line: 3,
+        end_column: 8,
+      },
+    );
+    let rendered = render_code_frame(&diagnostic, source, "a.pact");
+
+    assert!(rendered.contains(
+      "1 | (module m g\n  |   ^^^^^^^^^\n...\n3 | (module)\n  | ^^^^^^^^ unexpected input\n"
+    ));
+  }
+
+  #[test]
+  fn test_diagnostics_to_json() {
+    let source = "(module foo)";
+    let json = diagnostics_to_json(&diagnostics(source), source, "foo.pact");
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    let entry = &value[0];
+    assert_eq!(entry["code"], "P0005");
+    assert_eq!(entry["severity"], "error");
+    assert_eq!(entry["file"], "foo.pact");
+    assert_eq!(entry["start"]["column"], 1);
+    assert_eq!(entry["end"]["column"], 13);
+    assert!(entry["rendered"]
+      .as_str()
+      .unwrap()
+      .contains("^^^^^^^^^^^^ missing governance"));
+  }
 }
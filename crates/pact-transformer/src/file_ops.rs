@@ -1,7 +1,9 @@
-use crate::code_generator::CodeGenerator;
-use crate::parser::Parser;
+use crate::code_generator::{CodeGenerator, RuntimeOptions};
+use crate::error::render_code_frames;
+use crate::parser::{ParsedSource, Parser};
 use crate::source_map::{SourceMapGenerator, SourceMapOptions};
 use crate::transformer::TransformOptions;
+use crate::types::TypeMapper;
 use anyhow::{Context, Result};
 use napi_derive::napi;
 use path_clean::PathClean;
@@ -105,17 +107,30 @@ fn file_transform_impl(
     fs::read_to_string(input_path).with_context(|| format!("Failed to read file: {input_path}"))?;
 
   // Parse the source
-  let mut parser = Parser::new();
-  let (modules, errors) = parser.parse(&source);
+  let mut parser = Parser::new().with_recovery(transform_opts.recover.unwrap_or(false));
+  let ParsedSource {
+    modules,
+    interfaces,
+    errors,
+  } = parser.parse_source(&source);
 
   if !errors.is_empty() {
-    log::warn!("Parse errors in {input_path}: {errors:?}");
+    log::warn!(
+      "Parse errors in {input_path}:\n{}",
+      render_code_frames(&errors, &source, input_path)
+    );
   }
 
-  if modules.is_empty() {
-    return Ok(vec![]); // No modules to process
+  if modules.is_empty() && interfaces.is_empty() {
+    return Ok(vec![]); // Nothing to process
   }
 
+  let namespaced_exports = transform_opts.export_mode.as_deref() == Some("namespaced");
+  let mut skipped = crate::code_generator::skipped_constants(&modules);
+
+  // The `ts` format gets one annotated file instead of code plus declarations
+  let typescript = file_opts.format == "ts";
+
   // Determine if we should use source maps
   let use_source_maps = file_opts
     .source_maps
@@ -131,13 +146,34 @@ fn file_transform_impl(
       &source,
       input_path,
       &modules,
-    );
-    generator.generate(&modules)
+    )
+    .with_interfaces(&interfaces)
+    .with_namespace(transform_opts.namespace.clone())
+    .with_validators(transform_opts.validators.unwrap_or(false))
+    .with_runtime(RuntimeOptions::from_transform_options(Some(transform_opts)))
+    .with_namespaced_exports(namespaced_exports)
+    .with_typescript(typescript)
+    .with_type_mapper(TypeMapper::new(transform_opts.type_mappings.as_ref()));
+    let output = generator.generate(&modules);
+    skipped.extend_from_slice(generator.diagnostics());
+    output
   } else {
     // Use regular generator
-    let mut generator = CodeGenerator::new(transform_opts.generate_types.unwrap_or(true));
-    generator.generate(&modules)
+    let mut generator = CodeGenerator::new(transform_opts.generate_types.unwrap_or(true))
+      .with_interfaces(&interfaces)
+      .with_namespace(transform_opts.namespace.clone())
+      .with_validators(transform_opts.validators.unwrap_or(false))
+      .with_runtime(RuntimeOptions::from_transform_options(Some(transform_opts)))
+      .with_namespaced_exports(namespaced_exports)
+      .with_typescript(typescript)
+      .with_type_mapper(TypeMapper::new(transform_opts.type_mappings.as_ref()));
+    let output = generator.generate(&modules);
+    skipped.extend_from_slice(generator.diagnostics());
+    output
   };
+  if !skipped.is_empty() {
+    log::warn!("{}", render_code_frames(&skipped, &source, input_path));
+  }
 
   // Determine output paths
   let input_path_buf = PathBuf::from(input_path);
@@ -190,21 +226,10 @@ fn file_transform_impl(
     }
     "ts" => {
       // Write single .ts file with both code and types
-      if !js_code.is_empty() || !ts_types.is_empty() {
-        let mut combined = String::new();
-        if !ts_types.is_empty() {
-          combined.push_str(&ts_types);
-          combined.push_str("\n\n");
-        }
-        if !js_code.is_empty() {
-          // Convert JS code to TS by adding type annotations
-          let ts_code = convert_js_to_ts(&js_code);
-          combined.push_str(&ts_code);
-        }
-
+      if !js_code.is_empty() {
         let ts_path = output_dir.join(format!("{base_name}.ts"));
         let (final_ts_code, map_paths) = add_source_map_to_code(
-          &combined,
+          &js_code,
           source_map_json.as_ref(),
           &ts_path,
           &output_dir,
@@ -270,12 +295,6 @@ fn determine_output_dir(input_path: &Path, file_opts: &FileOutputOptions) -> Pat
   output_base.join(relative_dir).clean()
 }
 
-fn convert_js_to_ts(js_code: &str) -> String {
-  // Simple conversion from JS to TS by adding explicit return types
-  // This is a basic implementation - for full TS support, more sophisticated parsing would be needed
-  js_code.replace(") {", "): PactTransactionBuilder<PactExecPayload, any> {")
-}
-
 fn add_source_map_to_code(
   code: &str,
   source_map_json: Option<&String>,
@@ -469,6 +488,10 @@ mod tests {
     // Verify file exists in preserved structure
     let expected_path = output_dir.join("src").join("contracts").join("test.ts");
     assert!(expected_path.exists());
+
+    let ts_code = fs::read_to_string(expected_path).unwrap();
+    assert!(ts_code
+      .contains("export function hello(): PactTransactionBuilder<PactExecPayload, string> {"));
   }
 
   #[tokio::test]
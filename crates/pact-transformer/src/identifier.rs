@@ -0,0 +1,503 @@
+//! JavaScript identifiers for Pact names
+//!
+//! Pact names may contain characters such as `.`, `?` or `!`, start with a
+//! digit, or collide with JavaScript reserved words. Every emitter goes
+//! through this module so that a name is escaped the same way wherever it
+//! appears; the original Pact name stays available to docs and source maps.
+//! Escaping is not injective, so [`ModuleNames`] numbers the definitions whose
+//! names it would merge.
+
+use crate::ast::{
+  PactCapability, PactConstant, PactFunction, PactInterface, PactModule, PactParameter, PactSchema,
+  SchemaField, SourceSpan,
+};
+use crate::error::{Diagnostic, DiagnosticCode};
+use crate::literal::PactLiteral;
+use napi_derive::napi;
+use std::collections::HashMap;
+use std::convert::identity;
+
+/// Words that can't name a JavaScript binding in an ES module
+const RESERVED_WORDS: &[&str] = &[
+  "arguments",
+  "await",
+  "break",
+  "case",
+  "catch",
+  "class",
+  "const",
+  "continue",
+  "debugger",
+  "default",
+  "delete",
+  "do",
+  "else",
+  "enum",
+  "eval",
+  "export",
+  "extends",
+  "false",
+  "finally",
+  "for",
+  "function",
+  "if",
+  "implements",
+  "import",
+  "in",
+  "instanceof",
+  "interface",
+  "let",
+  "new",
+  "null",
+  "package",
+  "private",
+  "protected",
+  "public",
+  "return",
+  "static",
+  "super",
+  "switch",
+  "this",
+  "throw",
+  "true",
+  "try",
+  "typeof",
+  "var",
+  "void",
+  "while",
+  "with",
+  "yield",
+];
+
+/// Runtime helpers imported by generated code, which a binding of the same name would shadow
+const RUNTIME_BINDINGS: &[&str] = &[
+  "execution",
+  "continuation",
+  "encodePactArg",
+  "toPactValue",
+  "fromPactValue",
+  "validatePactObject",
+];
+
+/// Whether `word` can't be used as a binding name in generated code
+pub fn is_reserved(word: &str) -> bool {
+  RESERVED_WORDS.contains(&word) || RUNTIME_BINDINGS.contains(&word)
+}
+
+/// camelCase binding for a function, parameter or module, e.g. `delete` becomes `delete_`
+pub fn js_identifier(name: &str) -> String {
+  escape_reserved(property_name(name))
+}
+
+/// camelCase object key for a schema field or event parameter; reserved words are valid keys
+pub fn property_name(name: &str) -> String {
+  valid_start(to_camel_case(&word_separated(name)))
+}
+
+/// `PascalCase` type name for a schema, interface or defpact, e.g. `2fa-settings` becomes `_2faSettings`
+pub fn type_identifier(name: &str) -> String {
+  valid_start(to_pascal_case(&word_separated(name)))
+}
+
+/// `UPPER_SNAKE` binding for a constant, e.g. `MAX-RETRIES?` becomes `MAX_RETRIES`
+pub fn constant_identifier(name: &str) -> String {
+  let identifier = valid_start(
+    word_separated(name)
+      .split('-')
+      .filter(|word| !word.is_empty())
+      .collect::<Vec<_>>()
+      .join("_"),
+  );
+  escape_reserved(identifier)
+}
+
+/// Bindings for a parameter list, numbered where two parameters escape to the same name
+pub fn parameter_names(parameters: &[PactParameter]) -> Vec<String> {
+  parameter_scope(parameters).0
+}
+
+/// Object keys for a schema's fields, numbered where two fields escape to the same key
+pub fn field_names(fields: &[SchemaField]) -> Vec<String> {
+  field_scope(fields).0
+}
+
+fn parameter_scope(parameters: &[PactParameter]) -> (Vec<String>, Vec<Diagnostic>) {
+  let mut scope = Scope::default();
+  let names = parameters
+    .iter()
+    .map(|p| escape_reserved(scope.claim(&p.name, p.span, property_name(&p.name), escape_reserved)))
+    .collect();
+  (names, scope.diagnostics)
+}
+
+fn field_scope(fields: &[SchemaField]) -> (Vec<String>, Vec<Diagnostic>) {
+  let mut scope = Scope::default();
+  let names = fields
+    .iter()
+    .map(|field| {
+      scope.claim(
+        &field.name,
+        field.span,
+        property_name(&field.name),
+        identity,
+      )
+    })
+    .collect();
+  (names, scope.diagnostics)
+}
+
+/// JavaScript names of the definitions of a module or interface
+///
+/// The first definition to escape to a name keeps it; later ones are numbered, so `valid?`
+/// after `valid` becomes `valid2` and its signer helper `valid2WithSigner`. Functions,
+/// exported constants and capability builders share one scope, schemas another.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleNames {
+  /// camelCase names the function bindings and their helpers are built from
+  functions: HashMap<String, String>,
+  constants: HashMap<String, String>,
+  /// camelCase names the capability builders and event types are built from
+  capabilities: HashMap<String, String>,
+  schemas: HashMap<String, String>,
+}
+
+impl ModuleNames {
+  /// Names for a module, with a warning for each definition, parameter or field it renamed
+  pub fn module(module: &PactModule) -> (Self, Vec<Diagnostic>) {
+    Self::build(
+      &module.functions,
+      &module.constants,
+      &module.capabilities,
+      &module.schemas,
+    )
+  }
+
+  pub fn interface(interface: &PactInterface) -> Self {
+    Self::build(
+      &interface.functions,
+      &interface.constants,
+      &interface.capabilities,
+      &interface.schemas,
+    )
+    .0
+  }
+
+  fn build(
+    functions: &[PactFunction],
+    constants: &[PactConstant],
+    capabilities: &[PactCapability],
+    schemas: &[PactSchema],
+  ) -> (Self, Vec<Diagnostic>) {
+    let mut names = Self::default();
+    let mut values = Scope::default();
+    let mut types = Scope::default();
+    let mut diagnostics = Vec::new();
+
+    for function in functions {
+      let base = values.claim(
+        &function.name,
+        function.span,
+        property_name(&function.name),
+        escape_reserved,
+      );
+      names.functions.insert(function.name.clone(), base);
+      diagnostics.extend(parameter_scope(&function.parameters).1);
+    }
+    // Computed constants aren't exported, so they don't take a name
+    for constant in constants
+      .iter()
+      .filter(|constant| PactLiteral::parse(&constant.value).is_some())
+    {
+      let name = values.claim(
+        &constant.name,
+        constant.span,
+        constant_identifier(&constant.name),
+        identity,
+      );
+      names.constants.insert(constant.name.clone(), name);
+    }
+    for capability in capabilities {
+      let base = values.claim(
+        &capability.name,
+        capability.span,
+        property_name(&capability.name.to_lowercase()),
+        |base| format!("{base}Capability"),
+      );
+      names.capabilities.insert(capability.name.clone(), base);
+      diagnostics.extend(parameter_scope(&capability.parameters).1);
+    }
+    for schema in schemas {
+      let name = types.claim(
+        &schema.name,
+        schema.span,
+        type_identifier(&schema.name),
+        identity,
+      );
+      names.schemas.insert(schema.name.clone(), name);
+      diagnostics.extend(field_scope(&schema.fields).1);
+    }
+
+    diagnostics.extend(values.diagnostics);
+    diagnostics.extend(types.diagnostics);
+    diagnostics.sort_by_key(|d| (d.span.start_line, d.span.start_column));
+    (names, diagnostics)
+  }
+
+  /// Binding of a function, e.g. `delete` becomes `delete_`
+  pub fn function(&self, name: &str) -> String {
+    escape_reserved(self.function_base(name))
+  }
+
+  /// camelCase name the helpers of a function are built from, e.g. `deleteWithSigner`
+  pub fn function_base(&self, name: &str) -> String {
+    self
+      .functions
+      .get(name)
+      .cloned()
+      .unwrap_or_else(|| property_name(name))
+  }
+
+  /// `PascalCase` name of a defpact's types and builders, e.g. `continueTransferCrosschain`
+  pub fn function_type(&self, name: &str) -> String {
+    upper_first(&self.function_base(name))
+  }
+
+  pub fn constant(&self, name: &str) -> String {
+    self
+      .constants
+      .get(name)
+      .cloned()
+      .unwrap_or_else(|| constant_identifier(name))
+  }
+
+  /// Builder of a capability, e.g. `TRANSFER-XCHAIN` becomes `transferXchainCapability`
+  pub fn capability(&self, name: &str) -> String {
+    format!("{}Capability", self.capability_base(name))
+  }
+
+  /// `PascalCase` name of a capability in its event type, e.g. `Transfer`
+  pub fn capability_type(&self, name: &str) -> String {
+    upper_first(&self.capability_base(name))
+  }
+
+  fn capability_base(&self, name: &str) -> String {
+    self
+      .capabilities
+      .get(name)
+      .cloned()
+      .unwrap_or_else(|| property_name(&name.to_lowercase()))
+  }
+
+  pub fn schema(&self, name: &str) -> String {
+    self
+      .schemas
+      .get(name)
+      .cloned()
+      .unwrap_or_else(|| type_identifier(name))
+  }
+
+  /// The exported names by Pact name, for callers of the transform
+  pub fn to_js_names(&self, module: String) -> JsNames {
+    let map = |names: &HashMap<String, String>, name: &dyn Fn(&str) -> String| {
+      names.keys().map(|key| (key.clone(), name(key))).collect()
+    };
+    JsNames {
+      module,
+      functions: map(&self.functions, &|name| self.function(name)),
+      constants: self.constants.clone(),
+      capabilities: map(&self.capabilities, &|name| self.capability(name)),
+      schemas: self.schemas.clone(),
+    }
+  }
+}
+
+/// What a module's definitions are called in the generated code, keyed by Pact name
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct JsNames {
+  /// Module reference, e.g. `free.coin`
+  pub module: String,
+  pub functions: HashMap<String, String>,
+  pub constants: HashMap<String, String>,
+  /// Capability builders, e.g. `TRANSFER` to `transferCapability`
+  pub capabilities: HashMap<String, String>,
+  /// Schema interfaces, e.g. `account-details` to `AccountDetails`
+  pub schemas: HashMap<String, String>,
+}
+
+/// Identifiers declared in one JavaScript scope and the Pact names that declared them
+#[derive(Default)]
+struct Scope {
+  owners: HashMap<String, String>,
+  diagnostics: Vec<Diagnostic>,
+}
+
+impl Scope {
+  /// `base`, or the first of `base2`, `base3`, ... whose identifier is still free, where
+  /// `identifier` turns a base into the declared name
+  fn claim(
+    &mut self,
+    name: &str,
+    span: Option<SourceSpan>,
+    base: String,
+    identifier: impl Fn(String) -> String,
+  ) -> String {
+    let declared = identifier(base.clone());
+    let Some(owner) = self.owners.get(&declared).cloned() else {
+      self.owners.insert(declared, name.to_string());
+      return base;
+    };
+    // Each name taken can block at most one number, so one of these is free
+    let (numbered, renamed) = (2..=self.owners.len() + 2)
+      .map(|n| {
+        let numbered = format!("{base}{n}");
+        (numbered.clone(), identifier(numbered))
+      })
+      .find(|(_, renamed)| !self.owners.contains_key(renamed))
+      .unwrap_or_default();
+    self.diagnostics.push(Diagnostic::warning(
+      DiagnosticCode::NameClash,
+      format!("`{owner}` and `{name}` both become `{declared}`; `{name}` is named `{renamed}`"),
+      span.unwrap_or_default(),
+    ));
+    self.owners.insert(renamed, name.to_string());
+    numbered
+  }
+}
+
+/// Doc text for a definition, naming the Pact definition when its `identifier` had to be
+/// escaped or numbered rather than just re-cased
+pub fn doc_with_pact_name(doc: Option<&str>, name: &str, identifier: &str) -> Option<String> {
+  let doc = doc.map(str::trim).filter(|doc| !doc.is_empty());
+  if identifier == to_camel_case(name) {
+    return doc.map(ToString::to_string);
+  }
+  let note = format!("Pact name: `{name}`");
+  Some(match doc {
+    Some(doc) => format!("{doc}\n\n{note}"),
+    None => note,
+  })
+}
+
+/// Convert `snake_case` or kebab-case to camelCase
+pub fn to_camel_case(s: &str) -> String {
+  let mut result = String::new();
+  let mut capitalize_next = false;
+
+  for (i, c) in s.chars().enumerate() {
+    if c == '-' || c == '_' {
+      capitalize_next = true;
+    } else if i == 0 {
+      result.push(c.to_lowercase().next().unwrap_or(c));
+    } else if capitalize_next {
+      result.push(c.to_uppercase().next().unwrap_or(c));
+      capitalize_next = false;
+    } else {
+      result.push(c);
+    }
+  }
+
+  result
+}
+
+/// Convert `snake_case` or kebab-case to `PascalCase`
+pub fn to_pascal_case(s: &str) -> String {
+  let mut result = String::new();
+  let mut capitalize_next = true;
+
+  for c in s.chars() {
+    if c == '-' || c == '_' {
+      capitalize_next = true;
+    } else if capitalize_next {
+      result.push(c.to_uppercase().next().unwrap_or(c));
+      capitalize_next = false;
+    } else {
+      result.push(c);
+    }
+  }
+
+  result
+}
+
+/// Treat characters JavaScript identifiers can't hold (`.`, `?`, `!`, `*`, ...) as word breaks
+fn word_separated(name: &str) -> String {
+  name
+    .chars()
+    .map(|c| {
+      if c.is_alphanumeric() || c == '_' || c == '$' {
+        c
+      } else {
+        '-'
+      }
+    })
+    .collect()
+}
+
+fn escape_reserved(identifier: String) -> String {
+  if is_reserved(&identifier) {
+    format!("{identifier}_")
+  } else {
+    identifier
+  }
+}
+
+fn upper_first(identifier: &str) -> String {
+  let mut chars = identifier.chars();
+  chars
+    .next()
+    .map(|first| first.to_uppercase().chain(chars).collect())
+    .unwrap_or_default()
+}
+
+/// Prefix names that start with a digit
+fn valid_start(identifier: String) -> String {
+  if identifier.starts_with(|c: char| c.is_numeric()) {
+    format!("_{identifier}")
+  } else {
+    identifier
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_reserved_words() {
+    assert_eq!(js_identifier("delete"), "delete_");
+    assert_eq!(js_identifier("new"), "new_");
+    assert_eq!(js_identifier("class"), "class_");
+    assert_eq!(js_identifier("execution"), "execution_");
+    assert_eq!(js_identifier("get-balance"), "getBalance");
+    assert_eq!(property_name("default"), "default");
+  }
+
+  #[test]
+  fn test_invalid_characters() {
+    assert_eq!(js_identifier("valid?"), "valid");
+    assert_eq!(js_identifier("format-data!"), "formatData");
+    assert_eq!(js_identifier("coin.details"), "coinDetails");
+    assert_eq!(js_identifier("2fa"), "_2fa");
+    assert_eq!(type_identifier("user-data*"), "UserData");
+    assert_eq!(type_identifier("2fa-settings"), "_2faSettings");
+    assert_eq!(constant_identifier("MAX-RETRIES?"), "MAX_RETRIES");
+    assert_eq!(constant_identifier("1ST"), "_1ST");
+  }
+
+  #[test]
+  fn test_doc_with_pact_name() {
+    assert_eq!(doc_with_pact_name(None, "get-balance", "getBalance"), None);
+    assert_eq!(
+      doc_with_pact_name(Some("Read a balance"), "get-balance", "getBalance").as_deref(),
+      Some("Read a balance")
+    );
+    assert_eq!(
+      doc_with_pact_name(None, "delete", "delete_").as_deref(),
+      Some("Pact name: `delete`")
+    );
+    assert_eq!(
+      doc_with_pact_name(Some("Is it valid"), "valid?", "valid2").as_deref(),
+      Some("Is it valid\n\nPact name: `valid?`")
+    );
+  }
+}
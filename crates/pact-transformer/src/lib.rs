@@ -12,11 +12,15 @@
 use napi::Result;
 use napi_derive::napi;
 
+mod analysis;
 mod ast;
 mod code_generator;
 mod config;
+mod emitter;
 mod error;
 mod file_ops;
+mod identifier;
+mod literal;
 mod parser;
 mod plugin;
 mod source_map;
@@ -77,6 +81,36 @@ impl PactTransformer {
       if opts.declaration_maps.is_some() {
         merged_options.declaration_maps = opts.declaration_maps;
       }
+      if opts.namespace.is_some() {
+        merged_options.namespace = opts.namespace;
+      }
+      if opts.error_format.is_some() {
+        merged_options.error_format = opts.error_format;
+      }
+      if opts.recover.is_some() {
+        merged_options.recover = opts.recover;
+      }
+      if opts.validators.is_some() {
+        merged_options.validators = opts.validators;
+      }
+      if opts.runtime_import.is_some() {
+        merged_options.runtime_import = opts.runtime_import;
+      }
+      if opts.execution_builder.is_some() {
+        merged_options.execution_builder = opts.execution_builder;
+      }
+      if opts.continuation_builder.is_some() {
+        merged_options.continuation_builder = opts.continuation_builder;
+      }
+      if opts.module_format.is_some() {
+        merged_options.module_format = opts.module_format;
+      }
+      if opts.export_mode.is_some() {
+        merged_options.export_mode = opts.export_mode;
+      }
+      if opts.type_mappings.is_some() {
+        merged_options.type_mappings = opts.type_mappings;
+      }
     }
 
     let result = core_transform(code, Some(merged_options)).await?;
@@ -86,6 +120,7 @@ impl PactTransformer {
       typescript: Some(result.types).filter(|t| !t.is_empty()),
       source_map: result.source_map,
       declaration_map: result.declaration_map,
+      diagnostics: result.diagnostics,
     })
   }
 
@@ -116,6 +151,36 @@ impl PactTransformer {
       if transform_opts.declaration_maps.is_some() {
         merged_transform_options.declaration_maps = transform_opts.declaration_maps;
       }
+      if transform_opts.namespace.is_some() {
+        merged_transform_options.namespace = transform_opts.namespace;
+      }
+      if transform_opts.error_format.is_some() {
+        merged_transform_options.error_format = transform_opts.error_format;
+      }
+      if transform_opts.recover.is_some() {
+        merged_transform_options.recover = transform_opts.recover;
+      }
+      if transform_opts.validators.is_some() {
+        merged_transform_options.validators = transform_opts.validators;
+      }
+      if transform_opts.runtime_import.is_some() {
+        merged_transform_options.runtime_import = transform_opts.runtime_import;
+      }
+      if transform_opts.execution_builder.is_some() {
+        merged_transform_options.execution_builder = transform_opts.execution_builder;
+      }
+      if transform_opts.continuation_builder.is_some() {
+        merged_transform_options.continuation_builder = transform_opts.continuation_builder;
+      }
+      if transform_opts.module_format.is_some() {
+        merged_transform_options.module_format = transform_opts.module_format;
+      }
+      if transform_opts.export_mode.is_some() {
+        merged_transform_options.export_mode = transform_opts.export_mode;
+      }
+      if transform_opts.type_mappings.is_some() {
+        merged_transform_options.type_mappings = transform_opts.type_mappings;
+      }
     }
 
     let file_options = self.config.file_output.clone();
@@ -175,6 +240,36 @@ impl PactTransformer {
       if transform_opts.declaration_maps.is_some() {
         merged_transform_options.declaration_maps = transform_opts.declaration_maps;
       }
+      if transform_opts.namespace.is_some() {
+        merged_transform_options.namespace = transform_opts.namespace;
+      }
+      if transform_opts.error_format.is_some() {
+        merged_transform_options.error_format = transform_opts.error_format;
+      }
+      if transform_opts.recover.is_some() {
+        merged_transform_options.recover = transform_opts.recover;
+      }
+      if transform_opts.validators.is_some() {
+        merged_transform_options.validators = transform_opts.validators;
+      }
+      if transform_opts.runtime_import.is_some() {
+        merged_transform_options.runtime_import = transform_opts.runtime_import;
+      }
+      if transform_opts.execution_builder.is_some() {
+        merged_transform_options.execution_builder = transform_opts.execution_builder;
+      }
+      if transform_opts.continuation_builder.is_some() {
+        merged_transform_options.continuation_builder = transform_opts.continuation_builder;
+      }
+      if transform_opts.module_format.is_some() {
+        merged_transform_options.module_format = transform_opts.module_format;
+      }
+      if transform_opts.export_mode.is_some() {
+        merged_transform_options.export_mode = transform_opts.export_mode;
+      }
+      if transform_opts.type_mappings.is_some() {
+        merged_transform_options.type_mappings = transform_opts.type_mappings;
+      }
     }
 
     let file_options = self.config.file_output.clone();
@@ -220,40 +315,60 @@ impl PactTransformer {
   #[napi]
   #[allow(clippy::needless_pass_by_value)]
   #[must_use]
-  pub fn get_errors(&self, source: String) -> Vec<ErrorInfo> {
+  pub fn get_errors(&self, source: String) -> Vec<error::Diagnostic> {
     CoreTransformer::get_errors(&source)
-      .into_iter()
-      .map(|e| ErrorInfo {
-        message: e.message,
-        line: e.line,
-        column: e.column,
-      })
-      .collect()
   }
 
   /// Parse Pact source and return module AST
   #[napi]
   #[allow(clippy::needless_pass_by_value)]
-  pub fn parse(&self, source: String) -> Result<Vec<ModuleInfo>> {
-    let (modules, _errors) = CoreTransformer::parse(&source);
+  pub fn parse(&self, source: String, options: Option<ParseOptions>) -> Result<Vec<ModuleInfo>> {
+    let expressions = options.and_then(|o| o.expressions).unwrap_or(false);
+    let (modules, _errors) = CoreTransformer::parse(&source, expressions);
     Ok(
       modules
         .into_iter()
-        .map(|m| ModuleInfo {
-          name: m.name,
-          namespace: m.namespace,
-          governance: m.governance,
-          doc: m.doc,
-          function_count: m.functions.len() as u32,
-          schema_count: m.schemas.len() as u32,
-          capability_count: m.capabilities.len() as u32,
-          constant_count: m.constants.len() as u32,
+        .map(|m| {
+          let tables = m
+            .tables
+            .iter()
+            .map(|table| TableInfo {
+              name: table.name.clone(),
+              schema: table.schema.clone(),
+              schema_resolved: m.table_schema(table).is_some(),
+              doc: table.doc.clone(),
+              span: table.span,
+            })
+            .collect::<Vec<_>>();
+
+          ModuleInfo {
+            name: m.name,
+            namespace: m.namespace,
+            governance: m.governance,
+            doc: m.doc,
+            function_count: m.functions.len() as u32,
+            schema_count: m.schemas.len() as u32,
+            capability_count: m.capabilities.len() as u32,
+            constant_count: m.constants.len() as u32,
+            table_count: tables.len() as u32,
+            tables,
+            uses: m.uses,
+            functions: m.functions,
+            capabilities: m.capabilities,
+          }
         })
         .collect(),
     )
   }
 }
 
+/// Options for the parse method
+#[napi(object)]
+pub struct ParseOptions {
+  /// Also build the typed body of each function, pact and capability as `bodyExprs`
+  pub expressions: Option<bool>,
+}
+
 /// Options for transformFile method
 #[napi(object)]
 pub struct TransformFileOptions {
@@ -308,6 +423,8 @@ pub struct TransformResult {
   pub typescript: Option<String>,
   pub source_map: Option<String>,
   pub declaration_map: Option<String>,
+  /// Recovered parse errors and generation warnings reported alongside the output
+  pub diagnostics: Vec<error::Diagnostic>,
 }
 
 /// File operation result
@@ -340,14 +457,22 @@ pub struct ModuleInfo {
   pub schema_count: u32,
   pub capability_count: u32,
   pub constant_count: u32,
+  pub table_count: u32,
+  pub tables: Vec<TableInfo>,
+  pub uses: Vec<ast::PactUse>,
+  pub functions: Vec<ast::PactFunction>,
+  pub capabilities: Vec<ast::PactCapability>,
 }
 
-/// Error information
+/// Table information
 #[napi(object)]
-pub struct ErrorInfo {
-  pub message: String,
-  pub line: u32,
-  pub column: u32,
+pub struct TableInfo {
+  pub name: String,
+  pub schema: Option<String>,
+  /// Whether the schema is declared in the same module
+  pub schema_resolved: bool,
+  pub doc: Option<String>,
+  pub span: Option<ast::SourceSpan>,
 }
 
 /// Watch statistics
@@ -387,7 +512,7 @@ mod integration_tests {
 
   #[test]
   fn test_pact_transformer_integration() {
-    let (modules, errors) = CoreTransformer::parse(SIMPLE_MODULE);
+    let (modules, errors) = CoreTransformer::parse(SIMPLE_MODULE, false);
 
     assert!(errors.is_empty(), "Should parse without errors: {errors:?}");
     assert_eq!(modules.len(), 1, "Should parse exactly one module");
@@ -427,9 +552,28 @@ mod integration_tests {
     assert_eq!(constant.name, "MAX_AGE");
   }
 
+  #[test]
+  fn test_parse_with_expressions() {
+    let transformer = create_pact_transformer(None).unwrap();
+
+    let modules = transformer.parse(SIMPLE_MODULE.to_string(), None).unwrap();
+    assert_eq!(modules[0].functions[0].name, "create-user");
+    assert!(modules[0].functions[0].body_exprs.is_none());
+
+    let options = ParseOptions {
+      expressions: Some(true),
+    };
+    let modules = transformer
+      .parse(SIMPLE_MODULE.to_string(), Some(options))
+      .unwrap();
+    let body = modules[0].functions[0].body_exprs.as_ref().unwrap();
+    assert_eq!(body[0].value.as_deref(), Some("insert"));
+    assert!(modules[0].capabilities[0].body_exprs.is_some());
+  }
+
   #[test]
   fn test_empty_module_parsing() {
-    let (modules, errors) = CoreTransformer::parse("(module empty GOVERNANCE)");
+    let (modules, errors) = CoreTransformer::parse("(module empty GOVERNANCE)", false);
 
     assert!(
       errors.is_empty(),
@@ -456,7 +600,7 @@ mod integration_tests {
           (defcap OTHER-GOV () true))
         ";
 
-    let (modules, errors) = CoreTransformer::parse(multi_module);
+    let (modules, errors) = CoreTransformer::parse(multi_module, false);
     assert!(
       errors.is_empty(),
       "Multi-module should parse without errors"
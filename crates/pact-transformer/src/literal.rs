@@ -0,0 +1,330 @@
+//! Literal Pact values, as written in `defconst` definitions
+
+use std::fmt::Write;
+
+/// Largest integer a JavaScript number holds exactly
+const MAX_SAFE_INTEGER: u64 = 9_007_199_254_740_991;
+
+/// A Pact value made only of literals
+#[derive(Debug, Clone, PartialEq)]
+pub enum PactLiteral {
+  String(String),
+  Integer(String),
+  Decimal(String),
+  Bool(bool),
+  List(Vec<PactLiteral>),
+  Object(Vec<(String, PactLiteral)>),
+}
+
+impl PactLiteral {
+  /// Parse the source text of a value; `None` for anything that is not a literal
+  pub fn parse(text: &str) -> Option<Self> {
+    let mut reader = Reader {
+      chars: text.trim().chars().collect(),
+      pos: 0,
+    };
+    let literal = reader.value()?;
+    reader.skip_separators();
+    (reader.pos == reader.chars.len()).then_some(literal)
+  }
+
+  /// JavaScript expression for the value. Numbers a JavaScript number can't hold exactly
+  /// keep their wire form, `{ int: "..." }` or `{ decimal: "..." }`, as decoded results do.
+  pub fn to_js(&self) -> String {
+    match self {
+      Self::String(s) => json_string(s),
+      Self::Integer(digits) if is_safe_integer(digits) => digits.clone(),
+      Self::Integer(digits) => format!("{{ int: {} }}", json_string(digits)),
+      Self::Decimal(digits) if is_exact_decimal(digits) => digits.clone(),
+      Self::Decimal(digits) => format!("{{ decimal: {} }}", json_string(digits)),
+      Self::Bool(b) => b.to_string(),
+      Self::List(items) => format!(
+        "[{}]",
+        items.iter().map(Self::to_js).collect::<Vec<_>>().join(", ")
+      ),
+      Self::Object(fields) => Self::object(fields, "", ", ", Self::to_js),
+    }
+  }
+
+  /// TypeScript literal type of the value, with readonly lists and objects
+  pub fn to_ts_type(&self) -> String {
+    match self {
+      Self::List(items) => format!(
+        "readonly [{}]",
+        items
+          .iter()
+          .map(Self::to_ts_type)
+          .collect::<Vec<_>>()
+          .join(", ")
+      ),
+      Self::Object(fields) => Self::object(fields, "readonly ", "; ", Self::to_ts_type),
+      Self::Integer(digits) if !is_safe_integer(digits) => {
+        format!("{{ readonly int: {} }}", json_string(digits))
+      }
+      Self::Decimal(digits) if !is_exact_decimal(digits) => {
+        format!("{{ readonly decimal: {} }}", json_string(digits))
+      }
+      _ => self.to_js(),
+    }
+  }
+
+  /// Whether the JavaScript value is an array or object rather than a primitive
+  pub fn is_compound(&self) -> bool {
+    match self {
+      Self::List(_) | Self::Object(_) => true,
+      Self::Integer(digits) => !is_safe_integer(digits),
+      Self::Decimal(digits) => !is_exact_decimal(digits),
+      Self::String(_) | Self::Bool(_) => false,
+    }
+  }
+
+  fn object(
+    fields: &[(String, Self)],
+    key_prefix: &str,
+    separator: &str,
+    render: impl Fn(&Self) -> String,
+  ) -> String {
+    if fields.is_empty() {
+      return "{}".to_string();
+    }
+    let mut out = String::from("{ ");
+    for (i, (key, value)) in fields.iter().enumerate() {
+      if i > 0 {
+        out.push_str(separator);
+      }
+      write!(out, "{key_prefix}{}: {}", json_string(key), render(value)).unwrap();
+    }
+    out.push_str(" }");
+    out
+  }
+}
+
+fn json_string(s: &str) -> String {
+  serde_json::to_string(s).unwrap_or_default()
+}
+
+fn is_safe_integer(digits: &str) -> bool {
+  digits
+    .trim_start_matches('-')
+    .parse::<u64>()
+    .is_ok_and(|n| n <= MAX_SAFE_INTEGER)
+}
+
+/// Whether a JavaScript number holds the decimal exactly, i.e. prints back as the same
+/// digits once trailing zeros are dropped
+fn is_exact_decimal(digits: &str) -> bool {
+  let trimmed = digits.trim_end_matches('0').trim_end_matches('.');
+  digits
+    .parse::<f64>()
+    .is_ok_and(|number| number.is_finite() && number.to_string() == trimmed)
+}
+
+struct Reader {
+  chars: Vec<char>,
+  pos: usize,
+}
+
+impl Reader {
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.pos).copied()
+  }
+
+  /// Whitespace and commas, which Pact accepts between list items and object pairs
+  fn skip_separators(&mut self) {
+    while self.peek().is_some_and(|c| c.is_whitespace() || c == ',') {
+      self.pos += 1;
+    }
+  }
+
+  fn value(&mut self) -> Option<PactLiteral> {
+    self.skip_separators();
+    match self.peek()? {
+      '"' => self.string().map(PactLiteral::String),
+      '\'' => {
+        self.pos += 1;
+        let symbol = self.word();
+        (!symbol.is_empty()).then_some(PactLiteral::String(symbol))
+      }
+      '[' => self.list(),
+      '{' => self.object(),
+      _ => Self::scalar(&self.word()),
+    }
+  }
+
+  fn scalar(word: &str) -> Option<PactLiteral> {
+    match word {
+      "true" => return Some(PactLiteral::Bool(true)),
+      "false" => return Some(PactLiteral::Bool(false)),
+      _ => {}
+    }
+    let unsigned = word.strip_prefix('-').unwrap_or(word);
+    let (whole, fraction) = match unsigned.split_once('.') {
+      Some((whole, fraction)) => (whole, Some(fraction)),
+      None => (unsigned, None),
+    };
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    match fraction {
+      None if is_digits(whole) => Some(PactLiteral::Integer(word.to_string())),
+      Some(fraction) if is_digits(whole) && is_digits(fraction) => {
+        Some(PactLiteral::Decimal(word.to_string()))
+      }
+      _ => None,
+    }
+  }
+
+  /// A bare token up to the next delimiter
+  fn word(&mut self) -> String {
+    let start = self.pos;
+    while self
+      .peek()
+      .is_some_and(|c| !c.is_whitespace() && !matches!(c, ',' | ')' | ']' | '}' | ':' | '('))
+    {
+      self.pos += 1;
+    }
+    self.chars[start..self.pos].iter().collect()
+  }
+
+  fn string(&mut self) -> Option<String> {
+    self.pos += 1;
+    let mut out = String::new();
+    loop {
+      match self.peek()? {
+        '"' => {
+          self.pos += 1;
+          return Some(out);
+        }
+        '\\' => {
+          self.pos += 1;
+          match self.peek()? {
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            // A backslash before a line break continues the string on the next line
+            c if c.is_whitespace() => {
+              while self.peek().is_some_and(char::is_whitespace) {
+                self.pos += 1;
+              }
+              if self.peek() == Some('\\') {
+                self.pos += 1;
+              }
+              continue;
+            }
+            c => out.push(c),
+          }
+          self.pos += 1;
+        }
+        c => {
+          out.push(c);
+          self.pos += 1;
+        }
+      }
+    }
+  }
+
+  fn list(&mut self) -> Option<PactLiteral> {
+    self.pos += 1;
+    let mut items = Vec::new();
+    loop {
+      self.skip_separators();
+      if self.peek()? == ']' {
+        self.pos += 1;
+        return Some(PactLiteral::List(items));
+      }
+      items.push(self.value()?);
+    }
+  }
+
+  fn object(&mut self) -> Option<PactLiteral> {
+    self.pos += 1;
+    let mut fields = Vec::new();
+    loop {
+      self.skip_separators();
+      match self.peek()? {
+        '}' => {
+          self.pos += 1;
+          return Some(PactLiteral::Object(fields));
+        }
+        '"' => {
+          let key = self.string()?;
+          self.skip_separators();
+          if self.peek()? != ':' {
+            return None;
+          }
+          self.pos += 1;
+          fields.push((key, self.value()?));
+        }
+        _ => return None,
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_scalars() {
+    assert_eq!(
+      PactLiteral::parse("\"coin\""),
+      Some(PactLiteral::String("coin".to_string()))
+    );
+    assert_eq!(
+      PactLiteral::parse("'keys-all"),
+      Some(PactLiteral::String("keys-all".to_string()))
+    );
+    assert_eq!(PactLiteral::parse("12").unwrap().to_js(), "12");
+    assert_eq!(PactLiteral::parse("-0.5").unwrap().to_js(), "-0.5");
+    assert_eq!(PactLiteral::parse("true"), Some(PactLiteral::Bool(true)));
+  }
+
+  #[test]
+  fn test_numbers_a_number_cant_hold() {
+    let integer = PactLiteral::parse("99999999999999999999").unwrap();
+    assert_eq!(integer.to_js(), r#"{ int: "99999999999999999999" }"#);
+    assert_eq!(
+      integer.to_ts_type(),
+      r#"{ readonly int: "99999999999999999999" }"#
+    );
+    assert!(integer.is_compound());
+
+    let decimal = PactLiteral::parse("123456789.123456789012").unwrap();
+    assert_eq!(decimal.to_js(), r#"{ decimal: "123456789.123456789012" }"#);
+    assert_eq!(
+      decimal.to_ts_type(),
+      r#"{ readonly decimal: "123456789.123456789012" }"#
+    );
+    assert!(decimal.is_compound());
+
+    // Trailing zeros don't change the value
+    for exact in ["1.0", "-0.50", "0.000001", "100.25", "9007199254740991"] {
+      let literal = PactLiteral::parse(exact).unwrap();
+      assert_eq!(literal.to_js(), exact);
+      assert!(!literal.is_compound());
+    }
+    assert_eq!(PactLiteral::parse("0.1").unwrap().to_ts_type(), "0.1");
+  }
+
+  #[test]
+  fn test_lists_and_objects() {
+    let literal =
+      PactLiteral::parse(r#"{ "fees": [1.0 2.5], "name": "a\"b", "on": false }"#).unwrap();
+    assert_eq!(
+      literal.to_js(),
+      r#"{ "fees": [1.0, 2.5], "name": "a\"b", "on": false }"#
+    );
+    assert_eq!(
+      literal.to_ts_type(),
+      r#"{ readonly "fees": readonly [1.0, 2.5]; readonly "name": "a\"b"; readonly "on": false }"#
+    );
+    assert_eq!(PactLiteral::parse("[1, 2]").unwrap().to_js(), "[1, 2]");
+  }
+
+  #[test]
+  fn test_non_literals() {
+    assert_eq!(PactLiteral::parse("(read-keyset \"admin\")"), None);
+    assert_eq!(PactLiteral::parse("[1 (+ 1 2)]"), None);
+    assert_eq!(PactLiteral::parse("{ \"a\": other.CONST }"), None);
+    assert_eq!(PactLiteral::parse("MINIMUM_PRECISION"), None);
+    assert_eq!(PactLiteral::parse("1.0 2"), None);
+  }
+}
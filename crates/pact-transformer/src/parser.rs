@@ -1,12 +1,30 @@
 // Arena functionality simplified
+use crate::analysis;
 use crate::ast::*;
-use crate::error::ParseError;
+use crate::error::{Diagnostic, DiagnosticCode};
 use rayon::prelude::*;
 use std::sync::Arc;
 use tree_sitter::{Node, Parser as TSParser};
 
+#[cfg(test)]
+thread_local! {
+  /// Expressions built on this thread, for tests checking which parses build them
+  static EXPRS_BUILT: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[allow(clippy::struct_field_names)]
 pub struct Parser {
   ts_parser: TSParser,
+  include_expressions: bool,
+  recover: bool,
+}
+
+/// Top-level declarations found in a Pact source
+#[derive(Debug, Default)]
+pub struct ParsedSource {
+  pub modules: Vec<PactModule>,
+  pub interfaces: Vec<PactInterface>,
+  pub errors: Vec<Diagnostic>,
 }
 
 impl Default for Parser {
@@ -17,7 +35,11 @@ impl Default for Parser {
       .set_language(&language.into())
       .expect("Error loading Pact grammar");
 
-    Self { ts_parser }
+    Self {
+      ts_parser,
+      include_expressions: false,
+      recover: false,
+    }
   }
 }
 
@@ -27,12 +49,37 @@ impl Parser {
     Self::default()
   }
 
-  pub fn parse(&mut self, source: &str) -> (Vec<PactModule>, Vec<ParseError>) {
+  /// Also build `body_exprs` for functions, pacts and capabilities. Off by
+  /// default since most callers only need signatures.
+  #[must_use]
+  pub fn with_expressions(mut self, include_expressions: bool) -> Self {
+    self.include_expressions = include_expressions;
+    self
+  }
+
+  /// Leave out definitions that contain syntax errors instead of parsing
+  /// them as best we can, and report each one as a warning.
+  #[must_use]
+  pub fn with_recovery(mut self, recover: bool) -> Self {
+    self.recover = recover;
+    self
+  }
+
+  pub fn parse(&mut self, source: &str) -> (Vec<PactModule>, Vec<Diagnostic>) {
+    let parsed = self.parse_source(source);
+    (parsed.modules, parsed.errors)
+  }
+
+  pub fn parse_source(&mut self, source: &str) -> ParsedSource {
     let Some(tree) = self.ts_parser.parse(source, None) else {
-      return (
-        vec![],
-        vec![ParseError::new("Failed to parse".to_string(), 0, 0)],
-      );
+      return ParsedSource {
+        errors: vec![Diagnostic::error(
+          DiagnosticCode::ParseFailed,
+          "Failed to parse".to_string(),
+          SourceSpan::default(),
+        )],
+        ..ParsedSource::default()
+      };
     };
 
     let root_node = tree.root_node();
@@ -41,55 +88,91 @@ impl Parser {
     // Collect parse errors
     if root_node.has_error() {
       let mut cursor = root_node.walk();
-      collect_errors(&mut cursor, &mut errors);
+      collect_errors(&mut cursor, source, &mut errors);
     }
 
     // Extract namespace and modules
     let source_arc = Arc::new(source.to_string());
-    let current_namespace = Self::find_current_namespace(root_node, &source_arc);
-    let module_nodes = Self::find_modules(root_node);
+    let module_nodes = Self::find_modules(root_node, &source_arc);
+    let interface_nodes = Self::find_interfaces(root_node, &source_arc);
+
+    let (recover, expressions) = (self.recover, self.include_expressions);
+    if recover {
+      for (node, _) in module_nodes.iter().chain(&interface_nodes) {
+        errors.extend(Self::skipped_definitions(*node, &source_arc));
+      }
+    }
 
     let modules: Vec<PactModule> = module_nodes
       .into_par_iter()
-      .filter_map(|node| Self::parse_module(node, &source_arc, current_namespace.clone()))
+      .filter_map(|(node, scope)| {
+        Self::parse_module(node, &source_arc, scope, recover, expressions)
+      })
       .collect();
 
-    (modules, errors)
+    let interfaces: Vec<PactInterface> = interface_nodes
+      .into_iter()
+      .filter_map(|(node, scope)| Self::parse_interface(node, &source_arc, scope, recover))
+      .collect();
+
+    ParsedSource {
+      modules,
+      interfaces,
+      errors,
+    }
   }
 
-  fn find_current_namespace(root: Node, source: &Arc<String>) -> Option<String> {
-    let mut cursor = root.walk();
+  /// Value of a single `(namespace ...)` form
+  fn parse_namespace(node: Node, source: &Arc<String>) -> NamespaceScope {
+    let Some(value) = node.child_by_field_name("namespace") else {
+      return NamespaceScope::Root;
+    };
+    let Ok(text) = value.utf8_text(source.as_bytes()) else {
+      return NamespaceScope::Root;
+    };
 
-    for child in root.children(&mut cursor) {
-      if child.kind() == "namespace" {
-        // Look for the namespace value - it's usually the second child after the identifier
-        let mut namespace_cursor = child.walk();
-        for namespace_child in child.children(&mut namespace_cursor) {
-          if namespace_child.kind() == "string" || namespace_child.kind() == "symbol" {
-            if let Ok(text) = namespace_child.utf8_text(source.as_bytes()) {
-              // Remove quotes if present
-              let namespace = text.trim_matches('\'').trim_matches('"').to_string();
-              return Some(namespace);
-            }
-          }
+    match value.kind() {
+      "string" | "symbol" => {
+        // Remove quotes if present
+        let namespace = text.trim_matches('\'').trim_matches('"');
+        if namespace.is_empty() {
+          NamespaceScope::Root
+        } else {
+          NamespaceScope::Static(namespace.to_string())
         }
       }
+      // e.g. `(namespace (read-msg 'ns))`, only known at deploy time
+      _ => NamespaceScope::Dynamic(text.to_string()),
     }
+  }
 
-    None
+  fn find_modules<'a>(root: Node<'a>, source: &Arc<String>) -> Vec<(Node<'a>, NamespaceScope)> {
+    Self::find_scoped(root, source, "module")
   }
 
-  fn find_modules(root: Node) -> Vec<Node> {
-    let mut modules = Vec::new();
+  fn find_interfaces<'a>(root: Node<'a>, source: &Arc<String>) -> Vec<(Node<'a>, NamespaceScope)> {
+    Self::find_scoped(root, source, "interface")
+  }
+
+  /// Top-level forms of `kind`, each paired with the namespace declared before it
+  fn find_scoped<'a>(
+    root: Node<'a>,
+    source: &Arc<String>,
+    kind: &str,
+  ) -> Vec<(Node<'a>, NamespaceScope)> {
+    let mut scope = NamespaceScope::Root;
+    let mut found = Vec::new();
     let mut cursor = root.walk();
 
     for child in root.children(&mut cursor) {
-      if child.kind() == "module" {
-        modules.push(child);
+      if child.kind() == "namespace" {
+        scope = Self::parse_namespace(child, source);
+      } else if child.kind() == kind {
+        found.push((child, scope.clone()));
       }
     }
 
-    modules
+    found
   }
 
   fn find_child_by_kind<'a>(parent: Node<'a>, kind: &str) -> Option<Node<'a>> {
@@ -101,7 +184,9 @@ impl Parser {
   fn parse_module(
     node: Node,
     source: &Arc<String>,
-    namespace: Option<String>,
+    scope: NamespaceScope,
+    recover: bool,
+    expressions: bool,
   ) -> Option<PactModule> {
     // Extract module name and governance using named fields
     let name = Self::find_child_by_kind(node, "module_identifier")?
@@ -114,78 +199,136 @@ impl Parser {
       .ok()?
       .to_string();
 
+    let (namespace, dynamic_namespace) = scope.into_parts();
     let mut module = PactModule::with_namespace(name, namespace, governance);
+    module.dynamic_namespace = dynamic_namespace;
+    module.span = Some(node_span(node));
 
     // Process all children
     let mut cursor = node.walk();
     let children: Vec<Node> = node.children(&mut cursor).collect();
 
-    let (functions, capabilities, schemas, constants, uses, implements, docs) =
-      rayon::scope(|_s| {
-        let mut functions = Vec::new();
-        let mut capabilities = Vec::new();
-        let mut schemas = Vec::new();
-        let mut constants = Vec::new();
-        let mut uses = Vec::new();
-        let mut implements = Vec::new();
-        let mut docs = Vec::new();
-
-        for child in &children {
-          match child.kind() {
-            "defun" => {
-              if let Some(func) = Self::parse_function(*child, source) {
-                functions.push(func);
+    let (
+      (functions, calls, function_calls, function_guards),
+      (capabilities, capability_guards),
+      schemas,
+      constants,
+      tables,
+      uses,
+      implements,
+      docs,
+    ) = rayon::scope(|_s| {
+      let mut functions = Vec::new();
+      let mut calls = Vec::new();
+      let mut function_calls = Vec::new();
+      let mut function_guards = Vec::new();
+      let mut capabilities = Vec::new();
+      let mut capability_guards = Vec::new();
+      let mut schemas = Vec::new();
+      let mut constants = Vec::new();
+      let mut tables = Vec::new();
+      let mut uses = Vec::new();
+      let mut implements = Vec::new();
+      let mut docs = Vec::new();
+
+      for child in &children {
+        if recover && child.has_error() {
+          continue;
+        }
+        match child.kind() {
+          "defun" => {
+            if let Some(mut func) = Self::parse_function(*child, source) {
+              let body = Self::body_nodes(*child);
+              let (effect, called) = analysis::body_effect(&body, source);
+              func.effect = effect;
+              func.capabilities = analysis::capability_requirements(&body, source);
+              function_guards.push(analysis::body_guard(&body, source));
+              function_calls.push(analysis::body_calls(&body, source));
+              if expressions {
+                func.body_exprs = Some(Self::parse_body(*child, source));
               }
+              functions.push(func);
+              calls.push(called);
             }
-            "defcap" => {
-              if let Some(cap) = Self::parse_capability(*child, source) {
-                capabilities.push(cap);
+          }
+          "defpact" => {
+            if let Some(mut pact) = Self::parse_pact(*child, source) {
+              // Every step runs in a transaction of its own, so a pact always mutates;
+              // the steps are analysed like a defun body otherwise
+              let body = Self::body_nodes(*child);
+              pact.capabilities = analysis::capability_requirements(&body, source);
+              function_guards.push(analysis::body_guard(&body, source));
+              function_calls.push(analysis::body_calls(&body, source));
+              if expressions {
+                pact.body_exprs = Some(Self::parse_body(*child, source));
               }
+              functions.push(pact);
+              calls.push(analysis::body_effect(&body, source).1);
             }
-            "defschema" => {
-              if let Some(schema) = Self::parse_schema(*child, source) {
-                schemas.push(schema);
+          }
+          "defcap" => {
+            if let Some(mut cap) = Self::parse_capability(*child, source) {
+              capability_guards.push(analysis::body_guard(&Self::body_nodes(*child), source));
+              if expressions {
+                cap.body_exprs = Some(Self::parse_body(*child, source));
               }
+              capabilities.push(cap);
             }
-            "defconst" => {
-              if let Some(constant) = Self::parse_constant(*child, source) {
-                constants.push(constant);
-              }
+          }
+          "defschema" => {
+            if let Some(schema) = Self::parse_schema(*child, source) {
+              schemas.push(schema);
             }
-            "use" => {
-              if let Some(use_stmt) = Self::parse_use(*child, source) {
-                uses.push(use_stmt);
-              }
+          }
+          "defconst" => {
+            if let Some(constant) = Self::parse_constant(*child, source) {
+              constants.push(constant);
             }
-            "implements" => {
-              if let Some(impl_stmt) = Self::parse_implements(*child, source) {
-                implements.push(impl_stmt);
-              }
+          }
+          "deftable" => {
+            if let Some(table) = Self::parse_table(*child, source) {
+              tables.push(table);
             }
-            "doc" => {
-              if let Some(doc) = Self::extract_doc(*child, source) {
-                docs.push(doc);
-              }
+          }
+          "use" => {
+            if let Some(use_stmt) = Self::parse_use(*child, source) {
+              uses.push(use_stmt);
             }
-            _ => {}
           }
+          "implements" => {
+            if let Some(impl_stmt) = Self::parse_implements(*child, source) {
+              implements.push(impl_stmt);
+            }
+          }
+          "doc" => {
+            if let Some(doc) = Self::extract_doc(*child, source) {
+              docs.push(doc);
+            }
+          }
+          _ => {}
         }
+      }
 
-        (
-          functions,
-          capabilities,
-          schemas,
-          constants,
-          uses,
-          implements,
-          docs,
-        )
-      });
+      (
+        (functions, calls, function_calls, function_guards),
+        (capabilities, capability_guards),
+        schemas,
+        constants,
+        tables,
+        uses,
+        implements,
+        docs,
+      )
+    });
 
     module.functions = functions;
+    analysis::resolve_effects(&mut module, &calls);
+    analysis::resolve_requirements(&mut module, &function_calls);
     module.capabilities = capabilities;
+    analysis::resolve_guards(&mut module, &function_guards, &capability_guards);
     module.schemas = schemas;
     module.constants = constants;
+    module.tables = tables;
     module.uses = uses;
     module.implements = implements;
     module.doc = docs.first().cloned();
@@ -193,6 +336,104 @@ impl Parser {
     Some(module)
   }
 
+  fn parse_interface(
+    node: Node,
+    source: &Arc<String>,
+    scope: NamespaceScope,
+    recover: bool,
+  ) -> Option<PactInterface> {
+    let name = Self::find_child_by_kind(node, "def_identifier")?
+      .utf8_text(source.as_bytes())
+      .ok()?
+      .to_string();
+
+    let (namespace, dynamic_namespace) = scope.into_parts();
+    let mut interface = PactInterface::new(name, namespace);
+    interface.dynamic_namespace = dynamic_namespace;
+    interface.span = Some(node_span(node));
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+      if recover && child.has_error() {
+        continue;
+      }
+      match child.kind() {
+        "defun" | "defpact" => {
+          let signature = if child.kind() == "defun" {
+            Self::parse_function(child, source)
+          } else {
+            Self::parse_pact(child, source)
+          };
+          if let Some(mut signature) = signature {
+            // Interface functions are declarations only, drop any trailing metadata
+            signature.body = String::new();
+            // Implementations decide the effect; assume they need a transaction
+            signature.effect = PactEffect::Mutating;
+            interface.functions.push(signature);
+          }
+        }
+        "defcap" => {
+          if let Some(cap) = Self::parse_capability(child, source) {
+            interface.capabilities.push(cap);
+          }
+        }
+        "defschema" => {
+          if let Some(schema) = Self::parse_schema(child, source) {
+            interface.schemas.push(schema);
+          }
+        }
+        "defconst" => {
+          if let Some(constant) = Self::parse_constant(child, source) {
+            interface.constants.push(constant);
+          }
+        }
+        "use" => {
+          if let Some(use_stmt) = Self::parse_use(child, source) {
+            interface.uses.push(use_stmt);
+          }
+        }
+        "doc" => {
+          if interface.doc.is_none() {
+            interface.doc = Self::extract_doc(child, source);
+          }
+        }
+        "model" => {
+          let mut model_cursor = child.walk();
+          interface.models.extend(
+            child
+              .children_by_field_name("body", &mut model_cursor)
+              .filter_map(|n| n.utf8_text(source.as_bytes()).ok())
+              .map(std::string::ToString::to_string),
+          );
+        }
+        _ => {}
+      }
+    }
+
+    Some(interface)
+  }
+
+  /// Warnings for the body forms of a module or interface that recovery drops
+  fn skipped_definitions(node: Node, source: &Arc<String>) -> Vec<Diagnostic> {
+    let mut cursor = node.walk();
+    node
+      .children_by_field_name("body", &mut cursor)
+      .filter(|child| child.has_error() && !child.is_error())
+      .map(|child| {
+        let form = child.kind();
+        let name = Self::find_child_by_kind(child, "def_identifier")
+          .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+          .map(|name| format!(" {}", name.split(':').next().unwrap_or(name)))
+          .unwrap_or_default();
+        Diagnostic::warning(
+          DiagnosticCode::SkippedDefinition,
+          format!("Skipped `{form}{name}` because it contains syntax errors"),
+          node_span(child),
+        )
+      })
+      .collect()
+  }
+
   fn parse_function(node: Node, source: &Arc<String>) -> Option<PactFunction> {
     // Find the def_identifier node and type annotation
     let name_with_type_node = Self::find_child_by_kind(node, "def_identifier")?;
@@ -231,6 +472,11 @@ impl Parser {
       return_type,
       body: Self::extract_function_body(node, source),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: Some(node_span(node)),
+      body_exprs: None,
     };
 
     // Check for doc strings
@@ -241,6 +487,102 @@ impl Parser {
     Some(function)
   }
 
+  fn parse_pact(node: Node, source: &Arc<String>) -> Option<PactFunction> {
+    // A defpact shares the defun signature, its body is a sequence of steps
+    let mut pact = Self::parse_function(node, source)?;
+    pact.is_defun = false;
+
+    let mut cursor = node.walk();
+    pact.steps = node
+      .children(&mut cursor)
+      .filter(|child| child.kind() == "step" || child.kind() == "step_with_rollback")
+      .filter_map(|step_node| Self::parse_step(step_node, source))
+      .collect();
+
+    Some(pact)
+  }
+
+  fn parse_step(node: Node, source: &Arc<String>) -> Option<PactStep> {
+    let expr_node = node.child_by_field_name("expr")?;
+
+    let mut step = PactStep {
+      entity: node
+        .child_by_field_name("entity")
+        .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+        .map(std::string::ToString::to_string),
+      expr: expr_node.utf8_text(source.as_bytes()).ok()?.to_string(),
+      rollback: node
+        .child_by_field_name("rollback_expr")
+        .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+        .map(std::string::ToString::to_string),
+      yields: false,
+      yield_fields: Vec::new(),
+      yield_chain: None,
+      resumes: false,
+      resume_fields: Vec::new(),
+      span: Some(node_span(node)),
+    };
+
+    Self::collect_step_effects(expr_node, source, &mut step);
+
+    Some(step)
+  }
+
+  /// Walk a step expression looking for `yield` and `resume` forms
+  fn collect_step_effects(node: Node, source: &Arc<String>, step: &mut PactStep) {
+    match node.kind() {
+      "s_expression" => {
+        let is_yield = node
+          .child_by_field_name("head")
+          .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+          .is_some_and(|head| head == "yield");
+
+        if is_yield {
+          step.yields = true;
+          let mut cursor = node.walk();
+          let args: Vec<Node> = node.children_by_field_name("tail", &mut cursor).collect();
+          if let Some(payload) = args.first().filter(|n| n.kind() == "object") {
+            step.yield_fields = Self::object_keys(*payload, source);
+          }
+          step.yield_chain = args
+            .get(1)
+            .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+            .map(std::string::ToString::to_string);
+        }
+      }
+      "resume" => {
+        step.resumes = true;
+        if let Some(bindings) = node.child_by_field_name("bindings") {
+          let mut cursor = bindings.walk();
+          step.resume_fields = bindings
+            .children(&mut cursor)
+            .filter(|n| n.kind() == "bind_pair")
+            .filter_map(|n| n.child_by_field_name("key"))
+            .filter_map(|n| n.utf8_text(source.as_bytes()).ok())
+            .map(|key| key.trim_matches('"').trim_start_matches('\'').to_string())
+            .collect();
+        }
+      }
+      _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+      Self::collect_step_effects(child, source, step);
+    }
+  }
+
+  fn object_keys(node: Node, source: &Arc<String>) -> Vec<String> {
+    let mut cursor = node.walk();
+    node
+      .children(&mut cursor)
+      .filter(|n| n.kind() == "pair")
+      .filter_map(|n| n.child_by_field_name("key"))
+      .filter_map(|n| n.utf8_text(source.as_bytes()).ok())
+      .map(|key| key.trim_matches('"').trim_start_matches('\'').to_string())
+      .collect()
+  }
+
   fn parse_capability(node: Node, source: &Arc<String>) -> Option<PactCapability> {
     // Similar to parse_function
     let name_node = Self::find_child_by_kind(node, "def_identifier")?;
@@ -274,6 +616,9 @@ impl Parser {
       return_type,
       managed: None,
       is_event: false,
+      guarded: false,
+      span: Some(node_span(node)),
+      body_exprs: None,
     };
 
     // Check for doc, @managed, @event
@@ -300,8 +645,9 @@ impl Parser {
             }
           }
         }
+        "event" => capability.is_event = true,
         "reference" => {
-          // Check if this is @event
+          // Older grammars parse @event as a plain reference
           if let Ok(text) = child.utf8_text(source.as_bytes()) {
             if text == "@event" {
               capability.is_event = true;
@@ -323,6 +669,7 @@ impl Parser {
       name,
       doc: None,
       fields: Vec::new(),
+      span: Some(node_span(node)),
     };
 
     // Parse fields from schema_field_list
@@ -385,6 +732,7 @@ impl Parser {
       doc: None,
       constant_type,
       value,
+      span: Some(node_span(node)),
     };
 
     // Check for doc
@@ -395,6 +743,283 @@ impl Parser {
     Some(constant)
   }
 
+  fn parse_table(node: Node, source: &Arc<String>) -> Option<PactTable> {
+    let name_node = Self::find_child_by_kind(node, "def_identifier")?;
+    let name = name_node.utf8_text(source.as_bytes()).ok()?.to_string();
+
+    let schema = node
+      .child_by_field_name("schema")
+      .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+      .map(std::string::ToString::to_string);
+
+    let doc = Self::find_child_by_kind(node, "doc").and_then(|n| Self::extract_doc(n, source));
+
+    Some(PactTable {
+      name,
+      schema,
+      doc,
+      span: Some(node_span(node)),
+    })
+  }
+
+  fn parse_body(node: Node, source: &Arc<String>) -> Vec<PactExpr> {
+    Self::body_nodes(node)
+      .into_iter()
+      .map(|n| Self::parse_expr(n, source))
+      .collect()
+  }
+
+  /// Expressions of a definition's body, leaving out comments and `@managed` arguments
+  fn body_nodes(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.children(&mut cursor).collect();
+
+    // The references after `@managed` belong to the annotation, see `parse_capability`
+    let mut managed_args = Vec::new();
+    if let Some(i) = children.iter().position(|n| n.kind() == "managed") {
+      for arg in children.iter().skip(i + 1).take(2) {
+        if arg.kind() != "reference" {
+          break;
+        }
+        managed_args.push(arg.id());
+      }
+    }
+
+    let mut cursor = node.walk();
+    node
+      .children_by_field_name("body", &mut cursor)
+      .filter(|n| n.kind() != "comment" && !managed_args.contains(&n.id()))
+      .collect()
+  }
+
+  fn parse_expr(node: Node, source: &Arc<String>) -> PactExpr {
+    #[cfg(test)]
+    EXPRS_BUILT.with(|built| built.set(built.get() + 1));
+    let text = |n: Node| {
+      n.utf8_text(source.as_bytes())
+        .map(std::string::ToString::to_string)
+        .ok()
+    };
+    let field_exprs = |name: &str| {
+      let mut cursor = node.walk();
+      node
+        .children_by_field_name(name, &mut cursor)
+        .filter(|n| n.is_named() && n.kind() != "comment")
+        .map(|n| Self::parse_expr(n, source))
+        .collect::<Vec<_>>()
+    };
+    let field_bindings = |name: &str| {
+      node
+        .child_by_field_name(name)
+        .map(|n| Self::parse_bind_pairs(n, source))
+        .unwrap_or_default()
+    };
+
+    let mut expr = PactExpr {
+      kind: PactExprKind::Unknown,
+      value: None,
+      bindings: Vec::new(),
+      children: Vec::new(),
+      span: Some(node_span(node)),
+    };
+
+    match node.kind() {
+      "s_expression" => {
+        expr.kind = PactExprKind::Application;
+        expr.value = node.child_by_field_name("head").and_then(text);
+        expr.children = field_exprs("tail");
+      }
+      "let_binding" => {
+        expr.kind = if node.child(1).is_some_and(|n| n.kind() == "let*") {
+          PactExprKind::LetStar
+        } else {
+          PactExprKind::Let
+        };
+        let mut cursor = node.walk();
+        expr.bindings = node
+          .children_by_field_name("bind_pairs", &mut cursor)
+          .filter(|n| n.kind() == "let_bind_pair")
+          .filter_map(|n| Self::parse_let_pair(n, source))
+          .collect();
+        expr.children = field_exprs("body");
+      }
+      "bind" => {
+        expr.kind = PactExprKind::Bind;
+        expr.bindings = field_bindings("bindings");
+        expr.children = [field_exprs("src"), field_exprs("body")].concat();
+      }
+      "with_capability" => {
+        expr.kind = PactExprKind::WithCapability;
+        expr.children = [field_exprs("capability"), field_exprs("body")].concat();
+      }
+      "with_read" | "with_default_read" => {
+        expr.kind = if node.kind() == "with_read" {
+          PactExprKind::WithRead
+        } else {
+          PactExprKind::WithDefaultRead
+        };
+        expr.value = node.child_by_field_name("table").and_then(text);
+        expr.bindings = field_bindings("bindings");
+        expr.children = [
+          field_exprs("key"),
+          field_exprs("default"),
+          field_exprs("body"),
+        ]
+        .concat();
+      }
+      "if_expression" => {
+        expr.kind = PactExprKind::If;
+        expr.children = [
+          field_exprs("condition"),
+          field_exprs("then"),
+          field_exprs("else"),
+        ]
+        .concat();
+      }
+      "cond" | "try_expression" => {
+        expr.kind = if node.kind() == "cond" {
+          PactExprKind::Cond
+        } else {
+          PactExprKind::Try
+        };
+        let mut cursor = node.walk();
+        expr.children = node
+          .named_children(&mut cursor)
+          .filter(|n| n.kind() != "comment")
+          .map(|n| Self::parse_expr(n, source))
+          .collect();
+      }
+      "lambda_expression" => {
+        expr.kind = PactExprKind::Lambda;
+        expr.bindings = node
+          .child_by_field_name("parameters")
+          .map(|n| Self::parse_parameters(n, source))
+          .unwrap_or_default()
+          .into_iter()
+          .map(|param| PactBinding {
+            name: param.name,
+            key: None,
+            binding_type: param.parameter_type,
+            value: None,
+            span: param.span,
+          })
+          .collect();
+        expr.children = field_exprs("body");
+      }
+      "resume" => {
+        expr.kind = PactExprKind::Resume;
+        expr.bindings = field_bindings("bindings");
+        expr.children = field_exprs("body");
+      }
+      "emit_event" => {
+        expr.kind = PactExprKind::EmitEvent;
+        expr.children = field_exprs("capability");
+      }
+      "step" | "step_with_rollback" => {
+        expr.kind = PactExprKind::Step;
+        expr.value = node.child_by_field_name("entity").and_then(text);
+        expr.children = [field_exprs("expr"), field_exprs("rollback_expr")].concat();
+      }
+      "list" => {
+        expr.kind = PactExprKind::List;
+        let mut cursor = node.walk();
+        expr.children = node
+          .named_children(&mut cursor)
+          .filter(|n| n.kind() != "comment")
+          .map(|n| Self::parse_expr(n, source))
+          .collect();
+      }
+      "object" => {
+        expr.kind = PactExprKind::Object;
+        let mut cursor = node.walk();
+        expr.bindings = node
+          .children(&mut cursor)
+          .filter(|n| n.kind() == "pair")
+          .filter_map(|pair| {
+            let key = text(pair.child_by_field_name("key")?)?;
+            let key = key.trim_matches('"').trim_start_matches('\'').to_string();
+            Some(PactBinding {
+              name: key.clone(),
+              key: Some(key),
+              binding_type: None,
+              value: pair
+                .child_by_field_name("value")
+                .map(|n| Self::parse_expr(n, source)),
+              span: Some(node_span(pair)),
+            })
+          })
+          .collect();
+      }
+      kind => {
+        expr.kind = match kind {
+          "integer" => PactExprKind::Integer,
+          "decimal" => PactExprKind::Decimal,
+          "string" => PactExprKind::String,
+          "boolean" => PactExprKind::Boolean,
+          "symbol" => PactExprKind::Symbol,
+          "reference" | "ident" => PactExprKind::Reference,
+          _ => PactExprKind::Unknown,
+        };
+        expr.value = text(node);
+      }
+    }
+
+    expr
+  }
+
+  fn parse_let_pair(node: Node, source: &Arc<String>) -> Option<PactBinding> {
+    let variable = Self::find_child_by_kind(node, "let_variable")?;
+    let name = Self::find_child_by_kind(variable, "let_variable_identifier")?
+      .utf8_text(source.as_bytes())
+      .ok()?
+      .to_string();
+
+    Some(PactBinding {
+      name,
+      key: None,
+      binding_type: Self::type_of(variable, source),
+      value: node
+        .child_by_field_name("value")
+        .map(|n| Self::parse_expr(n, source)),
+      span: Some(node_span(node)),
+    })
+  }
+
+  /// Pairs of a `{ "key" := name }` binding map
+  fn parse_bind_pairs(node: Node, source: &Arc<String>) -> Vec<PactBinding> {
+    let mut cursor = node.walk();
+    node
+      .children(&mut cursor)
+      .filter(|n| n.kind() == "bind_pair")
+      .filter_map(|pair| {
+        let name = pair
+          .child_by_field_name("name")?
+          .utf8_text(source.as_bytes())
+          .ok()?
+          .to_string();
+        let key = pair
+          .child_by_field_name("key")
+          .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+          .map(|key| key.trim_matches('"').trim_start_matches('\'').to_string());
+
+        Some(PactBinding {
+          name,
+          key,
+          binding_type: Self::type_of(pair, source),
+          value: None,
+          span: Some(node_span(pair)),
+        })
+      })
+      .collect()
+  }
+
+  fn type_of(node: Node, source: &Arc<String>) -> Option<String> {
+    Self::find_child_by_kind(node, "type_annotation")
+      .and_then(|n| Self::find_child_by_kind(n, "type_identifier"))
+      .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+      .map(std::string::ToString::to_string)
+  }
+
   fn parse_parameters(node: Node, source: &Arc<String>) -> Vec<PactParameter> {
     let mut cursor = node.walk();
     node
@@ -408,14 +1033,10 @@ impl Parser {
     let name_node = Self::find_child_by_kind(node, "parameter_identifier")?;
     let name = name_node.utf8_text(source.as_bytes()).ok()?.to_string();
 
-    let parameter_type = Self::find_child_by_kind(node, "type_annotation")
-      .and_then(|n| Self::find_child_by_kind(n, "type_identifier"))
-      .and_then(|n| n.utf8_text(source.as_bytes()).ok())
-      .map(std::string::ToString::to_string);
-
     Some(PactParameter {
       name,
-      parameter_type,
+      parameter_type: Self::type_of(node, source),
+      span: Some(node_span(node)),
     })
   }
 
@@ -428,14 +1049,34 @@ impl Parser {
       .and_then(|n| n.utf8_text(source.as_bytes()).ok())
       .map_or_else(|| "string".to_string(), std::string::ToString::to_string);
 
-    Some(SchemaField { name, field_type })
+    Some(SchemaField {
+      name,
+      field_type,
+      span: Some(node_span(node)),
+    })
   }
 
-  fn parse_use(node: Node, source: &Arc<String>) -> Option<String> {
-    // Use statements have a reference node as child
-    Self::find_child_by_kind(node, "reference")
+  fn parse_use(node: Node, source: &Arc<String>) -> Option<PactUse> {
+    let reference = node
+      .child_by_field_name("module")
+      .and_then(|n| n.utf8_text(source.as_bytes()).ok())?;
+
+    let mut pact_use = PactUse::new(reference);
+    pact_use.hash = node
+      .child_by_field_name("hash")
       .and_then(|n| n.utf8_text(source.as_bytes()).ok())
-      .map(std::string::ToString::to_string)
+      .map(|s| s.trim_matches('"').to_string());
+    pact_use.imports = node.child_by_field_name("imports").map(|list| {
+      let mut cursor = list.walk();
+      list
+        .named_children(&mut cursor)
+        .filter(|item| item.kind() != "comment")
+        .filter_map(|item| item.utf8_text(source.as_bytes()).ok())
+        .map(std::string::ToString::to_string)
+        .collect()
+    });
+    pact_use.span = Some(node_span(node));
+    Some(pact_use)
   }
 
   fn parse_implements(node: Node, source: &Arc<String>) -> Option<String> {
@@ -477,26 +1118,146 @@ impl Parser {
   }
 }
 
-fn collect_errors(cursor: &mut tree_sitter::TreeCursor, errors: &mut Vec<ParseError>) {
-  if cursor.node().is_error() {
-    let node = cursor.node();
-    let start = node.start_position();
-    errors.push(ParseError::new(
-      "Syntax error".to_string(),
-      start.row + 1,
-      start.column + 1,
-    ));
+/// Namespace in effect for a top-level form
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum NamespaceScope {
+  Root,
+  Static(String),
+  /// Source text of an expression evaluated at deploy time
+  Dynamic(String),
+}
+
+impl NamespaceScope {
+  /// Split into the `namespace` and `dynamic_namespace` AST fields
+  fn into_parts(self) -> (Option<String>, Option<String>) {
+    match self {
+      Self::Root => (None, None),
+      Self::Static(namespace) => (Some(namespace), None),
+      Self::Dynamic(expression) => (None, Some(expression)),
+    }
+  }
+}
+
+pub fn node_span(node: Node) -> SourceSpan {
+  let start = node.start_position();
+  let end = node.end_position();
+  SourceSpan {
+    start_byte: node.start_byte() as u32,
+    end_byte: node.end_byte() as u32,
+    start_line: start.row as u32 + 1,
+    start_column: start.column as u32,
+    end_line: end.row as u32 + 1,
+    end_column: end.column as u32,
+  }
+}
+
+fn collect_errors(
+  cursor: &mut tree_sitter::TreeCursor,
+  source: &str,
+  errors: &mut Vec<Diagnostic>,
+) {
+  let node = cursor.node();
+  if node.is_missing() {
+    errors.push(missing_diagnostic(node));
+  } else if node.is_error() {
+    errors.push(error_diagnostic(node, source));
   }
 
   if cursor.goto_first_child() {
-    collect_errors(cursor, errors);
+    collect_errors(cursor, source, errors);
     while cursor.goto_next_sibling() {
-      collect_errors(cursor, errors);
+      collect_errors(cursor, source, errors);
     }
     cursor.goto_parent();
   }
 }
 
+/// Describe a zero-width node tree-sitter inserted to recover
+fn missing_diagnostic(node: Node) -> Diagnostic {
+  let expected = node.kind();
+  let opener = match expected {
+    ")" => Some("("),
+    "]" => Some("["),
+    "}" => Some("{"),
+    _ => None,
+  };
+
+  if let (Some(opener), Some(parent)) = (opener, node.parent()) {
+    let mut cursor = parent.walk();
+    let open = parent
+      .children(&mut cursor)
+      .find(|child| child.kind() == opener);
+    let at = node.start_position();
+    return Diagnostic::error(
+      DiagnosticCode::UnclosedDelimiter,
+      format!(
+        "Unclosed `{opener}` in {}; expected `{expected}` at {}:{}",
+        parent.kind(),
+        at.row + 1,
+        at.column + 1
+      ),
+      node_span(open.unwrap_or(node)),
+    );
+  }
+
+  Diagnostic::error(
+    DiagnosticCode::MissingToken,
+    format!("Missing `{expected}`"),
+    node_span(node),
+  )
+}
+
+/// Describe a span of input the grammar could not place
+fn error_diagnostic(node: Node, source: &str) -> Diagnostic {
+  let span = node_span(node);
+  let text = node.utf8_text(source.as_bytes()).unwrap_or_default();
+  let mut cursor = node.walk();
+  let children: Vec<Node> = node.children(&mut cursor).collect();
+
+  if children.first().map(Node::kind) == Some("(") {
+    let named: Vec<&Node> = children.iter().filter(|child| child.is_named()).collect();
+    if children.get(1).map(Node::kind) == Some("module") && named.len() == 1 {
+      let name = named[0].utf8_text(source.as_bytes()).unwrap_or_default();
+      return Diagnostic::error(
+        DiagnosticCode::MissingGovernance,
+        format!("Module `{name}` is missing its governance keyset or capability"),
+        span,
+      );
+    }
+    if children.last().map(Node::kind) != Some(")") {
+      return Diagnostic::error(
+        DiagnosticCode::UnclosedDelimiter,
+        "Unclosed `(`; expected `)`".to_string(),
+        node_span(children[0]),
+      );
+    }
+  }
+
+  let text = text.trim();
+  if matches!(text, ")" | "]" | "}") {
+    return Diagnostic::error(
+      DiagnosticCode::UnexpectedDelimiter,
+      format!("Unexpected `{text}` without a matching opener"),
+      span,
+    );
+  }
+
+  let first_line = text.lines().next().unwrap_or_default();
+  let mut snippet: String = first_line.chars().take(40).collect();
+  if snippet.len() < text.len() {
+    snippet.push_str("...");
+  }
+  let context = match node.parent() {
+    Some(parent) if parent.kind() != "source_file" => format!(" in {}", parent.kind()),
+    _ => String::new(),
+  };
+  Diagnostic::error(
+    DiagnosticCode::UnexpectedInput,
+    format!("Unexpected `{snippet}`{context}"),
+    span,
+  )
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -676,6 +1437,27 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_parse_event_capability() {
+    let mut parser = Parser::new();
+    let source = r#"
+(module test GOVERNANCE
+  (defcap RELEASED (account:string)
+    "Emitted on release"
+    @event
+    true)
+  (defcap ADMIN () true)
+)"#;
+
+    let (modules, errors) = parser.parse(source);
+
+    assert!(errors.is_empty());
+    let capabilities = &modules[0].capabilities;
+    assert!(capabilities[0].is_event);
+    assert_eq!(capabilities[0].doc, Some("Emitted on release".to_string()));
+    assert!(!capabilities[1].is_event);
+  }
+
   #[test]
   fn test_parse_constant() {
     let mut parser = Parser::new();
@@ -852,6 +1634,573 @@ mod tests {
     assert_eq!(function.parameters[1].parameter_type, None);
   }
 
+  #[test]
+  fn test_parse_defpact_steps() {
+    let mut parser = Parser::new();
+    let source = r#"
+(module xchain GOVERNANCE
+  (defpact transfer-crosschain:string (sender:string receiver:string target-chain:string amount:decimal)
+    @doc "Transfer across chains"
+    (step-with-rollback
+      (with-capability (DEBIT sender)
+        (yield { "receiver": receiver, "amount": amount } target-chain))
+      (credit sender amount))
+    (step
+      (resume { "receiver" := r, "amount" := a }
+        (credit r a))))
+)"#;
+
+    let (modules, errors) = parser.parse(source);
+
+    assert_eq!(errors.len(), 0);
+    let module = &modules[0];
+    assert_eq!(module.functions.len(), 1);
+
+    let pact = &module.functions[0];
+    assert_eq!(pact.name, "transfer-crosschain");
+    assert!(!pact.is_defun);
+    assert_eq!(pact.doc, Some("Transfer across chains".to_string()));
+    assert_eq!(pact.parameters.len(), 4);
+    assert_eq!(pact.steps.len(), 2);
+
+    let first = &pact.steps[0];
+    assert!(first.yields);
+    assert!(!first.resumes);
+    assert_eq!(first.yield_fields, vec!["receiver", "amount"]);
+    assert_eq!(first.yield_chain, Some("target-chain".to_string()));
+    assert_eq!(first.rollback, Some("(credit sender amount)".to_string()));
+
+    let second = &pact.steps[1];
+    assert!(second.resumes);
+    assert!(!second.yields);
+    assert_eq!(second.resume_fields, vec!["receiver", "amount"]);
+    assert_eq!(second.rollback, None);
+  }
+
+  #[test]
+  fn test_parse_defpact_step_entity() {
+    let mut parser = Parser::new();
+    let source = r"
+(module private GOVERNANCE
+  (defpact payment (amount:decimal)
+    (step alice (debit amount))
+    (step bob (credit amount))))
+";
+
+    let (modules, errors) = parser.parse(source);
+
+    assert_eq!(errors.len(), 0);
+    let pact = &modules[0].functions[0];
+    assert_eq!(pact.steps.len(), 2);
+    assert_eq!(pact.steps[0].entity, Some("alice".to_string()));
+    assert_eq!(pact.steps[0].expr, "(debit amount)");
+    assert_eq!(pact.steps[1].entity, Some("bob".to_string()));
+  }
+
+  #[test]
+  fn test_parse_interface() {
+    let mut parser = Parser::new();
+    let source = r#"
+(namespace 'free)
+
+(interface fungible-v2
+  "Standard for fungible coins"
+  @model [(defproperty conserves-mass (amount:decimal) (= amount amount))]
+
+  (defschema account-details
+    balance:decimal
+    guard:guard)
+
+  (defcap TRANSFER:bool (sender:string receiver:string amount:decimal)
+    @managed amount TRANSFER-mgr)
+
+  (defconst PRECISION:integer 12)
+
+  (defun transfer:string (sender:string receiver:string amount:decimal)
+    @doc "Transfer between accounts"
+    @model [(property (> amount 0.0))])
+
+  (defpact transfer-crosschain:string (sender:string amount:decimal))
+)
+
+(module coin GOVERNANCE
+  (implements fungible-v2))
+"#;
+
+    let parsed = parser.parse_source(source);
+
+    assert_eq!(parsed.errors.len(), 0);
+    assert_eq!(parsed.modules.len(), 1);
+    assert_eq!(parsed.interfaces.len(), 1);
+
+    let interface = &parsed.interfaces[0];
+    assert_eq!(interface.name, "fungible-v2");
+    assert_eq!(interface.namespace, Some("free".to_string()));
+    assert_eq!(
+      interface.doc,
+      Some("Standard for fungible coins".to_string())
+    );
+    assert_eq!(
+      interface.models,
+      vec!["(defproperty conserves-mass (amount:decimal) (= amount amount))".to_string()]
+    );
+    assert_eq!(interface.schemas.len(), 1);
+    assert_eq!(interface.capabilities.len(), 1);
+    assert_eq!(interface.constants.len(), 1);
+
+    assert_eq!(interface.functions.len(), 2);
+    let transfer = &interface.functions[0];
+    assert_eq!(transfer.name, "transfer");
+    assert_eq!(transfer.parameters.len(), 3);
+    assert_eq!(transfer.return_type, Some("string".to_string()));
+    assert_eq!(transfer.doc, Some("Transfer between accounts".to_string()));
+    assert!(transfer.body.is_empty());
+    assert!(!interface.functions[1].is_defun);
+
+    // The tuple API keeps returning modules only
+    let (modules, errors) = parser.parse(source);
+    assert_eq!(modules.len(), 1);
+    assert!(errors.is_empty());
+  }
+
+  #[test]
+  fn test_parse_spans() {
+    let mut parser = Parser::new();
+    let source = "(module spans GOVERNANCE\n  (defschema row\n    id:string)\n  (defun get:string (id:string) id))";
+
+    let (modules, errors) = parser.parse(source);
+    assert!(errors.is_empty());
+
+    let text = |span: Option<SourceSpan>| {
+      let span = span.unwrap();
+      &source[span.start_byte as usize..span.end_byte as usize]
+    };
+
+    let module = &modules[0];
+    assert_eq!(text(module.span), source);
+
+    let function = &module.functions[0];
+    assert_eq!(text(function.span), "(defun get:string (id:string) id)");
+    assert_eq!(text(function.parameters[0].span), "id:string");
+    let span = function.span.unwrap();
+    assert_eq!((span.start_line, span.start_column), (4, 2));
+    assert_eq!((span.end_line, span.end_column), (4, 35));
+
+    let schema = &module.schemas[0];
+    assert_eq!(text(schema.fields[0].span), "id:string");
+    assert_eq!(schema.fields[0].span.unwrap().start_line, 3);
+  }
+
+  #[test]
+  fn test_body_exprs_are_opt_in() {
+    let source = "(module m G (defun f:integer (a:integer) (+ a 1)))";
+
+    let (modules, _) = Parser::new().parse(source);
+    assert!(modules[0].functions[0].body_exprs.is_none());
+
+    let (modules, _) = Parser::new().with_expressions(true).parse(source);
+    let body = modules[0].functions[0].body_exprs.as_ref().unwrap();
+    assert_eq!(body.len(), 1);
+    assert_eq!(body[0].kind, PactExprKind::Application);
+    assert_eq!(body[0].value.as_deref(), Some("+"));
+    assert_eq!(body[0].children[0].kind, PactExprKind::Reference);
+    assert_eq!(body[0].children[1].kind, PactExprKind::Integer);
+    assert_eq!(body[0].children[1].value.as_deref(), Some("1"));
+  }
+
+  #[test]
+  fn test_default_parse_builds_no_expressions() {
+    let source = r#"
+(module bank GOVERNANCE
+  (defcap GOVERNANCE () (enforce-keyset "bank.admin"))
+  (defcap DEBIT (account:string) (enforce-guard (read-keyset account)))
+  (defun debit:string (account:string amount:decimal)
+    (with-capability (DEBIT account)
+      (update accounts account { "balance": (- (at 'balance (read accounts account)) amount) })))
+  (defpact settle:string () (step "done")))
+"#;
+    // A single thread runs the whole parse, so its count covers every expression built
+    let pool = rayon::ThreadPoolBuilder::new()
+      .num_threads(1)
+      .build()
+      .unwrap();
+    let parse = |expressions: bool| {
+      pool.install(|| {
+        EXPRS_BUILT.with(|built| built.set(0));
+        let (modules, _) = Parser::new().with_expressions(expressions).parse(source);
+        (modules, EXPRS_BUILT.with(std::cell::Cell::get))
+      })
+    };
+
+    let (modules, built) = parse(false);
+    assert_eq!(built, 0);
+    let debit = &modules[0].functions[0];
+    assert!(debit.body_exprs.is_none());
+    assert_eq!(debit.effect, PactEffect::Mutating);
+    assert_eq!(debit.capabilities[0].name, "DEBIT");
+    assert_eq!(debit.capabilities[0].args, ["account"]);
+    assert!(modules[0].capabilities.iter().all(|cap| cap.guarded));
+
+    let (expanded, built) = parse(true);
+    assert!(built > 0);
+    assert_eq!(expanded[0].functions[0].effect, debit.effect);
+    assert!(expanded[0].functions[0].body_exprs.is_some());
+  }
+
+  #[test]
+  fn test_parse_body_exprs() {
+    let mut parser = Parser::new().with_expressions(true);
+    let source = r#"
+(module bank GOVERNANCE
+  (defcap TRANSFER (sender:string amount:decimal)
+    @managed amount TRANSFER-mgr
+    (enforce (> amount 0.0) "positive"))
+
+  (defun transfer (sender:string receiver:string amount:decimal)
+    ; move the funds
+    (with-capability (TRANSFER sender amount)
+      (let* ((fee:decimal 0.1)
+             (total (+ amount fee)))
+        (with-read accounts sender { "balance" := balance }
+          (update accounts sender { "balance": (- balance total), "tags": ['a, "b"] })))))
+
+  (defpact two-step ()
+    (step (yield { "x": 1 }))
+    (step-with-rollback (resume { "x" := x } x) "undo"))
+)"#;
+
+    let (modules, errors) = parser.parse(source);
+    assert!(errors.is_empty());
+    let module = &modules[0];
+
+    // @managed arguments are not part of the capability body
+    let cap_body = module.capabilities[0].body_exprs.as_ref().unwrap();
+    assert_eq!(cap_body.len(), 1);
+    assert_eq!(cap_body[0].value.as_deref(), Some("enforce"));
+
+    let body = module.functions[0].body_exprs.as_ref().unwrap();
+    assert_eq!(body.len(), 1);
+    let with_cap = &body[0];
+    assert_eq!(with_cap.kind, PactExprKind::WithCapability);
+    assert_eq!(with_cap.children[0].value.as_deref(), Some("TRANSFER"));
+
+    let let_star = &with_cap.children[1];
+    assert_eq!(let_star.kind, PactExprKind::LetStar);
+    assert_eq!(let_star.bindings.len(), 2);
+    assert_eq!(let_star.bindings[0].name, "fee");
+    assert_eq!(
+      let_star.bindings[0].binding_type.as_deref(),
+      Some("decimal")
+    );
+    assert_eq!(
+      let_star.bindings[1].value.as_ref().map(|v| v.kind),
+      Some(PactExprKind::Application)
+    );
+
+    let with_read = &let_star.children[0];
+    assert_eq!(with_read.kind, PactExprKind::WithRead);
+    assert_eq!(with_read.value.as_deref(), Some("accounts"));
+    assert_eq!(with_read.bindings[0].name, "balance");
+    assert_eq!(with_read.bindings[0].key.as_deref(), Some("balance"));
+    assert_eq!(with_read.children[0].kind, PactExprKind::Reference);
+
+    let update = &with_read.children[1];
+    assert_eq!(update.value.as_deref(), Some("update"));
+    let object = &update.children[2];
+    assert_eq!(object.kind, PactExprKind::Object);
+    assert_eq!(object.bindings[1].key.as_deref(), Some("tags"));
+    let tags = object.bindings[1].value.as_ref().unwrap();
+    assert_eq!(tags.kind, PactExprKind::List);
+    assert_eq!(tags.children[0].kind, PactExprKind::Symbol);
+    assert_eq!(tags.children[1].kind, PactExprKind::String);
+
+    let span = update.span.unwrap();
+    assert!(source[span.start_byte as usize..].starts_with("(update accounts"));
+
+    let mut applications = 0;
+    with_cap.walk(&mut |expr| {
+      if expr.kind == PactExprKind::Application {
+        applications += 1;
+      }
+    });
+    assert_eq!(applications, 4);
+
+    let steps = module.functions[1].body_exprs.as_ref().unwrap();
+    assert_eq!(steps.len(), 2);
+    assert!(steps.iter().all(|step| step.kind == PactExprKind::Step));
+    assert_eq!(steps[1].children.len(), 2);
+    assert_eq!(steps[1].children[0].kind, PactExprKind::Resume);
+    assert_eq!(steps[1].children[0].bindings[0].name, "x");
+  }
+
+  #[test]
+  fn test_function_effects() {
+    let source = r#"
+(namespace 'free)
+(module ledger GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defcap DEBIT (account:string) true)
+  (deftable accounts:{account})
+  (defun fee:decimal (amount:decimal) (* amount 0.01))
+  (defun get-balance:decimal (account:string) (at 'balance (read accounts account)))
+  (defun balance-with-fee:decimal (account:string) (+ (get-balance account) (fee 1.0)))
+  (defun details:object (account:string)
+    (with-read accounts account { "balance" := balance } { "balance": balance }))
+  (defun debit:string (account:string amount:decimal)
+    (with-capability (DEBIT account)
+      (update accounts account { "balance": (- (free.ledger.get-balance account) amount) })))
+  (defun debit-all:string (amount:decimal) (map (debit-twice) (keys accounts)))
+  (defun debit-twice:string (account:string) (debit account 1.0) (debit account 1.0))
+  (defun coin-balance:decimal (account:string) (coin.get-balance account))
+  (defpact settle:string () (step "done")))
+
+(module payroll GOVERNANCE
+  (use coin)
+  (defcap GOVERNANCE () true)
+  (deftable accounts:{account})
+  (defun pay:string () (transfer "x" "y" 1.0))
+  (defun debit:string (account:string) (update accounts account { "balance": 0.0 }))
+  (defun debit-each:[string] (accounts:[string]) (map debit accounts))
+  (defun labels:[string] (names:[string]) (map (format "{}") (filter (compose (length) (< 0)) names))))
+"#;
+    let (modules, errors) = Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let effects: Vec<(&str, PactEffect)> = modules[0]
+      .functions
+      .iter()
+      .map(|function| (function.name.as_str(), function.effect))
+      .collect();
+    assert_eq!(
+      effects,
+      [
+        ("fee", PactEffect::Pure),
+        ("get-balance", PactEffect::ReadOnly),
+        ("balance-with-fee", PactEffect::ReadOnly),
+        ("details", PactEffect::ReadOnly),
+        ("debit", PactEffect::Mutating),
+        ("debit-all", PactEffect::Mutating),
+        ("debit-twice", PactEffect::Mutating),
+        ("coin-balance", PactEffect::Mutating),
+        ("settle", PactEffect::Mutating),
+      ]
+    );
+
+    // Names from `use` can't be followed, and functions passed by name are called
+    let effects: Vec<(&str, PactEffect)> = modules[1]
+      .functions
+      .iter()
+      .map(|function| (function.name.as_str(), function.effect))
+      .collect();
+    assert_eq!(
+      effects,
+      [
+        ("pay", PactEffect::Mutating),
+        ("debit", PactEffect::Mutating),
+        ("debit-each", PactEffect::Mutating),
+        ("labels", PactEffect::Pure),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_capability_requirements() {
+    let source = r#"
+(module wallet GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defcap DEBIT (account:string) true)
+  (defun pay:string (sender:string receiver:string amount:decimal)
+    (with-capability (coin.TRANSFER sender receiver amount)
+      (require-capability (DEBIT sender))
+      (with-capability (DEBIT "fees")
+        "paid"))))
+"#;
+    let (modules, errors) = Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let requirements: Vec<(&str, Vec<&str>, bool)> = modules[0].functions[0]
+      .capabilities
+      .iter()
+      .map(|capability| {
+        (
+          capability.name.as_str(),
+          capability.args.iter().map(String::as_str).collect(),
+          capability.acquired,
+        )
+      })
+      .collect();
+    assert_eq!(
+      requirements,
+      [
+        ("coin.TRANSFER", vec!["sender", "receiver", "amount"], true),
+        ("DEBIT", vec!["sender"], false),
+        ("DEBIT", vec!["\"fees\""], true),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_capability_requirements_follow_calls() {
+    let source = r#"
+(namespace 'free)
+(module wallet GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defcap DEBIT (account:string amount:decimal) true)
+  (defcap CREDIT (account:string) true)
+  (defun debit:string (account:string amount:decimal)
+    (with-capability (DEBIT account amount) "debited"))
+  (defun credit:string (account:string)
+    (require-capability (CREDIT account))
+    "credited")
+  (defun move:string (sender:string receiver:string)
+    (debit sender 1.0)
+    (free.wallet.credit receiver))
+  (defun move-twice:string (sender:string receiver:string)
+    (move sender receiver)
+    (move receiver sender))
+  (defun countdown:string (n:integer)
+    (with-capability (DEBIT "clock" n) (countdown (- n 1))))
+  (defpact settle:string (sender:string receiver:string)
+    (step (with-capability (DEBIT sender 2.0) (credit receiver)))
+    (step (move receiver sender))))
+"#;
+    let (modules, errors) = Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let requirements = |name: &str| -> Vec<String> {
+      let function = modules[0]
+        .functions
+        .iter()
+        .find(|function| function.name == name)
+        .unwrap();
+      function
+        .capabilities
+        .iter()
+        .map(|capability| {
+          let verb = if capability.acquired {
+            "acquire"
+          } else {
+            "require"
+          };
+          format!("{verb} ({} {})", capability.name, capability.args.join(" "))
+        })
+        .collect()
+    };
+
+    assert_eq!(
+      requirements("move"),
+      ["acquire (DEBIT sender 1.0)", "require (CREDIT receiver)"]
+    );
+    assert_eq!(
+      requirements("move-twice"),
+      [
+        "acquire (DEBIT sender 1.0)",
+        "require (CREDIT receiver)",
+        "acquire (DEBIT receiver 1.0)",
+        "require (CREDIT sender)",
+      ]
+    );
+    // Only a parameter is replaced, so recursion adds one level and stops
+    assert_eq!(
+      requirements("countdown"),
+      [
+        "acquire (DEBIT \"clock\" n)",
+        "acquire (DEBIT \"clock\" (- n 1))"
+      ]
+    );
+    // Pact steps are analysed like a defun body
+    assert_eq!(
+      requirements("settle"),
+      [
+        "acquire (DEBIT sender 2.0)",
+        "require (CREDIT receiver)",
+        "acquire (DEBIT receiver 1.0)",
+        "require (CREDIT sender)",
+      ]
+    );
+    let settle = &modules[0].functions[5];
+    let first_step = settle.steps[0].span.unwrap();
+    assert!(settle.capabilities[1].span.unwrap().end_byte <= first_step.end_byte);
+  }
+
+  #[test]
+  fn test_capability_guards() {
+    let source = r#"
+(namespace 'free)
+(module wallet GOVERNANCE
+  (defcap GOVERNANCE () (enforce-keyset "free.admin"))
+  (defcap DEBIT (account:string) (compose-capability (free.wallet.OWNER account)))
+  (defcap OWNER (account:string) (enforce-owner account))
+  (defcap INTERNAL () true)
+  (defcap REMOTE () (compose-capability (coin.DEBIT "fees")))
+  (defun enforce-owner:bool (account:string) (enforce-guard (read-keyset account))))
+"#;
+    let (modules, errors) = Parser::new().parse(source);
+    assert!(errors.is_empty(), "{errors:?}");
+
+    let guarded: Vec<(&str, bool)> = modules[0]
+      .capabilities
+      .iter()
+      .map(|capability| (capability.name.as_str(), capability.guarded))
+      .collect();
+    assert_eq!(
+      guarded,
+      [
+        ("GOVERNANCE", true),
+        ("DEBIT", true),
+        ("OWNER", true),
+        ("INTERNAL", false),
+        ("REMOTE", false),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_parse_deftable() {
+    let mut parser = Parser::new();
+    let source = r#"
+(module bank GOVERNANCE
+  (defschema account
+    balance:decimal
+    guard:guard)
+
+  (deftable accounts:{account}
+    "Account balances")
+  (deftable audit-log)
+)"#;
+
+    let (modules, errors) = parser.parse(source);
+
+    assert_eq!(errors.len(), 0);
+    let module = &modules[0];
+    assert_eq!(module.tables.len(), 2);
+
+    let accounts = &module.tables[0];
+    assert_eq!(accounts.name, "accounts");
+    assert_eq!(accounts.schema, Some("account".to_string()));
+    assert_eq!(accounts.doc, Some("Account balances".to_string()));
+    assert_eq!(
+      module
+        .table_schema(accounts)
+        .map(|schema| schema.fields.len()),
+      Some(2)
+    );
+
+    let span = accounts.span.unwrap();
+    assert_eq!(span.start_line, 7);
+    assert_eq!(span.start_column, 2);
+    assert_eq!(span.end_line, 8);
+    assert_eq!(
+      &source[span.start_byte as usize..span.end_byte as usize],
+      "(deftable accounts:{account}\n    \"Account balances\")"
+    );
+
+    let audit = &module.tables[1];
+    assert_eq!(audit.name, "audit-log");
+    assert_eq!(audit.schema, None);
+    assert!(module.table_schema(audit).is_none());
+  }
+
   #[test]
   fn test_parse_empty_module() {
     let mut parser = Parser::new();
@@ -919,8 +2268,52 @@ mod tests {
 
     let module = &modules[0];
     assert_eq!(module.uses.len(), 2);
-    assert_eq!(module.uses[0], "coin");
-    assert_eq!(module.uses[1], "util.guards");
+    assert_eq!(module.uses[0].module, "coin");
+    assert_eq!(module.uses[0].namespace, None);
+    assert_eq!(module.uses[1].module, "guards");
+    assert_eq!(module.uses[1].namespace.as_deref(), Some("util"));
+  }
+
+  #[test]
+  fn test_parse_use_hash_and_imports() {
+    let mut parser = Parser::new();
+    let source = r#"
+(module test GOVERNANCE
+  (use coin "ut_J_ZNkoyaPUEJhiwVeWnkSQn9JT9sQCWKdjjVVrWo")
+  (use free.util [format-amount parse-amount])
+  (use util.guards "abc" [after-date])
+  (use empty []))
+"#;
+
+    let (modules, errors) = parser.parse(source);
+    assert!(errors.is_empty());
+
+    let uses = &modules[0].uses;
+    assert_eq!(uses.len(), 4);
+
+    assert_eq!(uses[0].reference(), "coin");
+    assert_eq!(
+      uses[0].hash.as_deref(),
+      Some("ut_J_ZNkoyaPUEJhiwVeWnkSQn9JT9sQCWKdjjVVrWo")
+    );
+    assert_eq!(uses[0].imports, None);
+
+    assert_eq!(uses[1].namespace.as_deref(), Some("free"));
+    assert_eq!(uses[1].module, "util");
+    assert_eq!(uses[1].hash, None);
+    assert_eq!(
+      uses[1].imports,
+      Some(vec![
+        "format-amount".to_string(),
+        "parse-amount".to_string()
+      ])
+    );
+
+    assert_eq!(uses[2].hash.as_deref(), Some("abc"));
+    assert_eq!(uses[2].imports, Some(vec!["after-date".to_string()]));
+
+    assert_eq!(uses[3].imports, Some(vec![]));
+    assert_eq!(uses[3].span.unwrap().start_line, 6);
   }
 
   #[test]
@@ -996,19 +2389,65 @@ mod tests {
   }
 
   #[test]
-  fn test_find_current_namespace() {
-    let _parser = Parser::new();
+  fn test_parse_namespace_forms() {
     let mut ts_parser = tree_sitter::Parser::new();
     let language = tree_sitter_pact::LANGUAGE;
     ts_parser.set_language(&language.into()).unwrap();
 
-    let source_text = "(namespace 'free)";
-    let source = Arc::new(source_text.to_string());
-    let tree = ts_parser.parse(source_text, None).unwrap();
-    let root = tree.root_node();
+    let mut scope_of = |source_text: &str| {
+      let source = Arc::new(source_text.to_string());
+      let tree = ts_parser.parse(source_text, None).unwrap();
+      let node = tree.root_node().child(0).unwrap();
+      Parser::parse_namespace(node, &source)
+    };
 
-    let namespace = Parser::find_current_namespace(root, &source);
-    assert_eq!(namespace, Some("free".to_string()));
+    assert_eq!(
+      scope_of("(namespace 'free)"),
+      NamespaceScope::Static("free".to_string())
+    );
+    assert_eq!(
+      scope_of("(namespace \"user\")"),
+      NamespaceScope::Static("user".to_string())
+    );
+    assert_eq!(scope_of("(namespace \"\")"), NamespaceScope::Root);
+    assert_eq!(
+      scope_of("(namespace (read-msg 'ns))"),
+      NamespaceScope::Dynamic("(read-msg 'ns)".to_string())
+    );
+  }
+
+  #[test]
+  fn test_namespaces_follow_declaration_order() {
+    let mut parser = Parser::new();
+    let source = r"
+(module root-mod GOVERNANCE)
+(namespace 'free)
+(module first GOVERNANCE)
+(interface first-iface)
+(namespace 'user)
+(module second GOVERNANCE)
+(namespace (read-msg 'ns))
+(module third GOVERNANCE)
+";
+
+    let parsed = parser.parse_source(source);
+    assert!(parsed.errors.is_empty());
+
+    let namespaces: Vec<_> = parsed
+      .modules
+      .iter()
+      .map(|m| (m.namespace.as_deref(), m.dynamic_namespace.as_deref()))
+      .collect();
+    assert_eq!(
+      namespaces,
+      vec![
+        (None, None),
+        (Some("free"), None),
+        (Some("user"), None),
+        (None, Some("(read-msg 'ns)")),
+      ]
+    );
+    assert_eq!(parsed.interfaces[0].namespace.as_deref(), Some("free"));
   }
 
   #[test]
@@ -1022,7 +2461,96 @@ mod tests {
     let tree = ts_parser.parse(source, None).unwrap();
     let root = tree.root_node();
 
-    let modules = Parser::find_modules(root);
+    let modules = Parser::find_modules(root, &Arc::new(source.to_string()));
     assert_eq!(modules.len(), 2);
   }
+
+  #[test]
+  fn test_diagnostic_unclosed_paren() {
+    let mut parser = Parser::new();
+    let (_, errors) = parser.parse("(module m GOVERNANCE\n  (defun f () 1)");
+
+    assert_eq!(errors.len(), 1);
+    let diagnostic = &errors[0];
+    assert_eq!(diagnostic.code, DiagnosticCode::UnclosedDelimiter.as_str());
+    assert_eq!(diagnostic.severity, crate::error::Severity::Error);
+    assert_eq!(
+      diagnostic.message,
+      "Unclosed `(` in module; expected `)` at 2:17"
+    );
+    // Points at the opening paren of the module
+    assert_eq!(diagnostic.span.start_line, 1);
+    assert_eq!(diagnostic.span.start_column, 0);
+    assert_eq!(diagnostic.span.end_column, 1);
+  }
+
+  #[test]
+  fn test_diagnostic_missing_governance() {
+    let mut parser = Parser::new();
+    let (_, errors) = parser.parse("(module foo)");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, DiagnosticCode::MissingGovernance.as_str());
+    assert_eq!(
+      errors[0].message,
+      "Module `foo` is missing its governance keyset or capability"
+    );
+    assert_eq!(errors[0].span.end_column, 12);
+  }
+
+  #[test]
+  fn test_diagnostic_delimiters() {
+    let mut parser = Parser::new();
+
+    let (_, errors) = parser.parse("(module m g (defun f () 1)))");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, DiagnosticCode::UnexpectedDelimiter.as_str());
+    assert_eq!(errors[0].span.start_column, 27);
+
+    let (_, errors) = parser.parse("(module m g (defun f () [1 2))");
+    assert_eq!(errors[0].code, DiagnosticCode::UnclosedDelimiter.as_str());
+    assert!(errors[0].message.starts_with("Unclosed `[` in list"));
+
+    let (_, errors) = parser.parse("(defun f () 1) (+ 1");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, DiagnosticCode::UnclosedDelimiter.as_str());
+    assert_eq!(errors[0].span.start_column, 15);
+    assert_eq!(
+      errors[0].to_string(),
+      "1:16: error[P0002]: Unclosed `(`; expected `)`"
+    );
+  }
+
+  #[test]
+  fn test_recovery_skips_broken_definitions() {
+    let source = r"
+(module m GOVERNANCE
+  (defun a () 1)
+  (defun b:integer () })
+  (defun c () 3))
+";
+
+    let strict = Parser::new().parse_source(source);
+    assert_eq!(strict.modules[0].functions.len(), 3);
+
+    let recovered = Parser::new().with_recovery(true).parse_source(source);
+    let names: Vec<_> = recovered.modules[0]
+      .functions
+      .iter()
+      .map(|f| f.name.as_str())
+      .collect();
+    assert_eq!(names, vec!["a", "c"]);
+
+    let warning = recovered
+      .errors
+      .iter()
+      .find(|d| d.severity == crate::error::Severity::Warning)
+      .unwrap();
+    assert_eq!(warning.code, DiagnosticCode::SkippedDefinition.as_str());
+    assert_eq!(
+      warning.message,
+      "Skipped `defun b` because it contains syntax errors"
+    );
+    assert_eq!(warning.span.start_line, 4);
+  }
 }
@@ -361,7 +361,7 @@ pub fn initialize_plugins(
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::ast::PactParameter;
+  use crate::ast::{PactEffect, PactParameter};
 
   #[test]
   fn test_plugin_manager() {
@@ -386,10 +386,16 @@ mod tests {
       parameters: vec![PactParameter {
         name: "amount".to_string(),
         parameter_type: Some("decimal".to_string()),
+        span: None,
       }],
       return_type: Some("string".to_string()),
       body: String::new(),
       is_defun: true,
+      steps: vec![],
+      effect: PactEffect::Mutating,
+      capabilities: vec![],
+      span: None,
+      body_exprs: None,
     };
 
     plugin
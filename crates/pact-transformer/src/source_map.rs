@@ -64,9 +64,8 @@ pub struct SourceMapping {
 pub struct SourceMapGenerator {
   options: SourceMapOptions,
   mappings: Vec<SourceMapping>,
+  declaration_mappings: Vec<SourceMapping>,
   source_contents: HashMap<String, String>,
-  generated_line: u32,
-  generated_column: u32,
 }
 
 impl SourceMapGenerator {
@@ -74,9 +73,8 @@ impl SourceMapGenerator {
     Self {
       options,
       mappings: Vec::new(),
+      declaration_mappings: Vec::new(),
       source_contents: HashMap::new(),
-      generated_line: 1,
-      generated_column: 0,
     }
   }
 
@@ -107,22 +105,29 @@ impl SourceMapGenerator {
     });
   }
 
-  /// Track current position in generated code
-  pub fn advance_generated_position(&mut self, text: &str) {
-    for ch in text.chars() {
-      if ch == '\n' {
-        self.generated_line += 1;
-        self.generated_column = 0;
-      } else {
-        self.generated_column += 1;
-      }
-    }
+  /// Add a mapping between a position in the generated declarations and the source
+  pub fn add_declaration_mapping(
+    &mut self,
+    generated_line: u32,
+    generated_column: u32,
+    source_file: &str,
+    source_line: u32,
+    source_column: u32,
+    name: Option<&str>,
+  ) {
+    self.declaration_mappings.push(SourceMapping {
+      generated_line,
+      generated_column,
+      source_file: source_file.to_string(),
+      source_line,
+      source_column,
+      name: name.map(std::string::ToString::to_string),
+    });
   }
 
-  /// Get current generated position
-  #[allow(dead_code)]
-  pub fn current_position(&self) -> (u32, u32) {
-    (self.generated_line, self.generated_column)
+  /// Content registered for a source file
+  pub fn source_content(&self, file_path: &str) -> Option<&str> {
+    self.source_contents.get(file_path).map(String::as_str)
   }
 
   /// Get source map options
@@ -130,11 +135,6 @@ impl SourceMapGenerator {
     &self.options
   }
 
-  /// Get source content for a file
-  pub fn get_source_content(&self, file_path: &str) -> Option<&String> {
-    self.source_contents.get(file_path)
-  }
-
   /// Generate the source map
   pub fn generate(&self, generated_file: &str) -> SourceMap {
     let mut builder = SourceMapBuilder::new(Some(generated_file));
@@ -231,7 +231,7 @@ impl SourceMapGenerator {
     }
 
     // Add mappings (for declarations, we map type definitions to their source)
-    for mapping in &self.mappings {
+    for mapping in &self.declaration_mappings {
       let source_id = builder.add_source(&mapping.source_file);
       let name_id = if let Some(name) = &mapping.name {
         if self.options.include_names.unwrap_or(true) {
@@ -267,67 +267,76 @@ impl SourceMapGenerator {
   }
 }
 
-/// Parse Pact source to find line/column positions for AST nodes
+/// Where the output generated for a named Pact definition starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceMark {
+  pub generated_line: u32,
+  pub generated_column: u32,
+  pub source_line: u32,
+  pub source_column: u32,
+  pub name: String,
+}
+
+/// Column in UTF-16 code units, as source maps count them, of a byte column in `line`
+pub fn utf16_column(line: &str, byte_column: u32) -> u32 {
+  line
+    .get(..byte_column as usize)
+    .map_or(byte_column, |prefix| prefix.encode_utf16().count() as u32)
+}
+
+/// Collect the start position of each declaration from its AST span
 #[allow(dead_code)]
-pub fn analyze_pact_positions(
-  source: &str,
-  modules: &[PactModule],
-) -> HashMap<String, SourceLocation> {
+pub fn analyze_pact_positions(modules: &[PactModule]) -> HashMap<String, SourceLocation> {
   let mut positions = HashMap::new();
-  let lines: Vec<&str> = source.lines().collect();
 
   for module in modules {
-    // Find module position
-    if let Some(pos) = find_text_position(&lines, &format!("module {}", module.name)) {
-      positions.insert(format!("module:{}", module.name), pos);
-    }
+    let mut insert = |key: String, span: Option<SourceSpan>| {
+      if let Some(span) = span {
+        positions.insert(key, SourceLocation::from(span));
+      }
+    };
+
+    insert(format!("module:{}", module.name), module.span);
 
-    // Find function positions
     for function in &module.functions {
-      if let Some(pos) = find_text_position(&lines, &format!("defun {}", function.name)) {
-        positions.insert(format!("function:{}:{}", module.name, function.name), pos);
-      }
+      insert(
+        format!("function:{}:{}", module.name, function.name),
+        function.span,
+      );
     }
 
-    // Find capability positions
     for capability in &module.capabilities {
-      if let Some(pos) = find_text_position(&lines, &format!("defcap {}", capability.name)) {
-        positions.insert(
-          format!("capability:{}:{}", module.name, capability.name),
-          pos,
-        );
-      }
+      insert(
+        format!("capability:{}:{}", module.name, capability.name),
+        capability.span,
+      );
     }
 
-    // Find schema positions
     for schema in &module.schemas {
-      if let Some(pos) = find_text_position(&lines, &format!("defschema {}", schema.name)) {
-        positions.insert(format!("schema:{}:{}", module.name, schema.name), pos);
-      }
+      insert(
+        format!("schema:{}:{}", module.name, schema.name),
+        schema.span,
+      );
     }
 
-    // Find constant positions
     for constant in &module.constants {
-      if let Some(pos) = find_text_position(&lines, &format!("defconst {}", constant.name)) {
-        positions.insert(format!("constant:{}:{}", module.name, constant.name), pos);
-      }
+      insert(
+        format!("constant:{}:{}", module.name, constant.name),
+        constant.span,
+      );
     }
   }
 
   positions
 }
 
-#[allow(dead_code)]
-fn find_text_position(lines: &[&str], search_text: &str) -> Option<SourceLocation> {
-  for (line_idx, line) in lines.iter().enumerate() {
-    if let Some(col_idx) = line.find(search_text) {
-      return Some(SourceLocation {
-        line: (line_idx + 1) as u32,
-        column: col_idx as u32,
-      });
+impl From<SourceSpan> for SourceLocation {
+  fn from(span: SourceSpan) -> Self {
+    Self {
+      line: span.start_line,
+      column: span.start_column,
     }
   }
-  None
 }
 
 #[cfg(test)]
@@ -365,8 +374,10 @@ mod tests {
 
   #[test]
   fn test_declaration_map_generation() {
-    let mut options = SourceMapOptions::default();
-    options.declaration_map = Some(true);
+    let options = SourceMapOptions {
+      declaration_map: Some(true),
+      ..SourceMapOptions::default()
+    };
 
     let mut generator = SourceMapGenerator::new(options);
     generator.add_source_content("test.pact", "(module test GOVERNANCE)");
@@ -394,53 +405,25 @@ mod tests {
     age:integer)
 
   (defcap ADMIN () true)
-)"#;
-
-    let module = PactModule {
-      name: "test".to_string(),
-      namespace: None,
-      governance: "GOVERNANCE".to_string(),
-      doc: None,
-      functions: vec![PactFunction {
-        name: "hello".to_string(),
-        doc: Some("Hello world".to_string()),
-        parameters: vec![],
-        return_type: Some("string".to_string()),
-        body: "\"Hello world\"".to_string(),
-        is_defun: true,
-      }],
-      capabilities: vec![PactCapability {
-        name: "ADMIN".to_string(),
-        doc: None,
-        parameters: vec![],
-        return_type: None,
-        managed: None,
-        is_event: false,
-      }],
-      schemas: vec![PactSchema {
-        name: "user".to_string(),
-        doc: None,
-        fields: vec![
-          SchemaField {
-            name: "name".to_string(),
-            field_type: "string".to_string(),
-          },
-          SchemaField {
-            name: "age".to_string(),
-            field_type: "integer".to_string(),
-          },
-        ],
-      }],
-      constants: vec![],
-      uses: vec![],
-      implements: vec![],
-    };
+)
 
-    let positions = analyze_pact_positions(source, &[module]);
+(module other GOVERNANCE
+    (defun   hello:string () "Same name, odd spacing"))"#;
 
-    assert!(positions.contains_key("module:test"));
-    assert!(positions.contains_key("function:test:hello"));
-    assert!(positions.contains_key("schema:test:user"));
-    assert!(positions.contains_key("capability:test:ADMIN"));
+    let (modules, errors) = crate::parser::Parser::new().parse(source);
+    assert!(errors.is_empty());
+
+    let positions = analyze_pact_positions(&modules);
+
+    let at = |key: &str| {
+      let location = &positions[key];
+      (location.line, location.column)
+    };
+    assert_eq!(at("module:test"), (2, 0));
+    assert_eq!(at("function:test:hello"), (3, 2));
+    assert_eq!(at("schema:test:user"), (6, 2));
+    assert_eq!(at("capability:test:ADMIN"), (10, 2));
+    assert_eq!(at("module:other"), (13, 0));
+    assert_eq!(at("function:other:hello"), (14, 4));
   }
 }
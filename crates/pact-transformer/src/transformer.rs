@@ -1,5 +1,9 @@
 use crate::ast::*;
+use crate::code_generator::{CodeGenerator, RuntimeOptions};
+use crate::error::{diagnostics_to_json, render_code_frames, Diagnostic, Severity};
+use crate::identifier::JsNames;
 use crate::parser::Parser;
+use crate::types::{TypeMapper, TypeMappings};
 use napi_derive::napi;
 use serde::{Deserialize, Serialize};
 use std::sync::{LazyLock, Mutex};
@@ -21,7 +25,7 @@ fn get_parser() -> Parser {
 fn return_parser(parser: Parser) {
   let mut pool = PARSER_POOL.lock().unwrap();
   if pool.len() < num_cpus::get() * 2 {
-    pool.push(parser);
+    pool.push(parser.with_recovery(false).with_expressions(false));
   }
   // Drop excess parsers to avoid memory bloat
 }
@@ -33,18 +37,31 @@ pub async fn core_transform(
 ) -> Result<TransformationResult, napi::Error> {
   // Use tokio's spawn_blocking for CPU-intensive work
   tokio::task::spawn_blocking(move || {
+    let recover = options.as_ref().and_then(|o| o.recover).unwrap_or(false);
+
     // Get parser from pool
-    let mut parser = get_parser();
+    let mut parser = get_parser().with_recovery(recover);
 
-    // Parse modules
-    let (modules, errors) = parser.parse(&source);
+    // Parse modules and interfaces
+    let crate::parser::ParsedSource {
+      modules,
+      interfaces,
+      errors,
+    } = parser.parse_source(&source);
 
-    if !errors.is_empty() {
-      let error_messages: Vec<String> = errors
-        .iter()
-        .map(|e| format!("{}:{}: {}", e.line, e.column, e.message))
-        .collect();
-      return Err(napi::Error::from_reason(error_messages.join("\n")));
+    let source_file_path = options
+      .as_ref()
+      .and_then(|o| o.source_file_path.as_deref())
+      .unwrap_or("input.pact");
+
+    let has_errors = errors.iter().any(|e| e.severity == Severity::Error);
+    if has_errors && !recover {
+      return_parser(parser);
+      let reason = match options.as_ref().and_then(|o| o.error_format.as_deref()) {
+        Some("json") => diagnostics_to_json(&errors, &source, source_file_path),
+        _ => render_code_frames(&errors, &source, source_file_path),
+      };
+      return Err(napi::Error::from_reason(reason));
     }
 
     // Check if source maps are requested
@@ -53,11 +70,6 @@ pub async fn core_transform(
       .and_then(|o| o.source_maps)
       .unwrap_or(false);
 
-    let source_file_path = options
-      .as_ref()
-      .and_then(|o| o.source_file_path.as_deref())
-      .unwrap_or("input.pact");
-
     let generate_types_flag = options
       .as_ref()
       .and_then(|o| o.generate_types)
@@ -69,10 +81,27 @@ pub async fn core_transform(
       .and_then(|o| o.declaration_maps)
       .unwrap_or(false);
 
+    let namespace = options.as_ref().and_then(|o| o.namespace.clone());
+    let validators = options.as_ref().and_then(|o| o.validators).unwrap_or(false);
+    let runtime = RuntimeOptions::from_transform_options(options.as_ref());
+    let namespaced_exports =
+      options.as_ref().and_then(|o| o.export_mode.as_deref()) == Some("namespaced");
+    let type_mapper = TypeMapper::new(options.as_ref().and_then(|o| o.type_mappings.as_ref()));
+
+    // Settings shared by every generator built below
+    let configure = |generator: CodeGenerator| {
+      generator
+        .with_interfaces(&interfaces)
+        .with_namespace(namespace.clone())
+        .with_validators(validators)
+        .with_runtime(runtime.clone())
+        .with_namespaced_exports(namespaced_exports)
+        .with_type_mapper(type_mapper.clone())
+    };
+
     // Generate code and types with or without source maps
-    let (code, types, source_map, declaration_map) =
+    let (mut generator, js_filename, ts_filename) =
       if generate_source_maps || generate_declaration_maps {
-        use crate::code_generator::CodeGenerator;
         use crate::source_map::SourceMapOptions;
 
         let source_map_options = SourceMapOptions {
@@ -81,13 +110,13 @@ pub async fn core_transform(
           ..SourceMapOptions::default()
         };
 
-        let mut generator = CodeGenerator::new_with_source_maps(
+        let generator = configure(CodeGenerator::new_with_source_maps(
           generate_types_flag,
           source_map_options,
           &source,
           source_file_path,
           &modules,
-        );
+        ));
 
         // Generate filenames based on source path
         let stem = std::path::Path::new(source_file_path)
@@ -97,49 +126,39 @@ pub async fn core_transform(
         let js_filename = Some(format!("{stem}.js"));
         let ts_filename = Some(format!("{stem}.pact.d.ts"));
 
-        generator.generate_with_filenames(&modules, js_filename.as_deref(), ts_filename.as_deref())
+        (generator, js_filename, ts_filename)
       } else {
-        // Use the simple parallel generation for better performance when no source maps needed
-        let (code, types) = rayon::join(
-          || generate_js(&modules),
-          || {
-            if generate_types_flag {
-              generate_types(&modules)
-            } else {
-              String::new()
-            }
-          },
-        );
-        (code, types, None, None)
+        (
+          configure(CodeGenerator::new(generate_types_flag)),
+          None,
+          None,
+        )
       };
+    let (code, types, source_map, declaration_map) =
+      generator.generate_with_filenames(&modules, js_filename.as_deref(), ts_filename.as_deref());
 
     // Return parser to pool
     return_parser(parser);
 
+    let mut diagnostics = errors;
+    diagnostics.extend(crate::code_generator::skipped_constants(&modules));
+    diagnostics.extend_from_slice(generator.diagnostics());
+
     Ok(TransformationResult {
       modules,
+      interfaces,
       code,
       types,
       source_map,
       declaration_map,
+      diagnostics,
+      names: generator.names().to_vec(),
     })
   })
   .await
   .map_err(|e| napi::Error::from_reason(e.to_string()))?
 }
 
-/// JavaScript code generation with string optimizations
-fn generate_js(modules: &[PactModule]) -> String {
-  use crate::code_generator::generate_js as gen_js;
-  gen_js(modules)
-}
-
-/// TypeScript types generation
-fn generate_types(modules: &[PactModule]) -> String {
-  use crate::code_generator::generate_types as gen_types;
-  gen_types(modules)
-}
-
 /// High-performance transformer with pooled parsers
 pub struct CoreTransformer;
 
@@ -158,10 +177,7 @@ impl CoreTransformer {
     let (modules, errors) = parser.parse(&source);
 
     if !errors.is_empty() {
-      let error_messages: Vec<String> = errors
-        .iter()
-        .map(|e| format!("{}:{}: {}", e.line, e.column, e.message))
-        .collect();
+      let error_messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
       return_parser(parser);
       return Err(napi::Error::from_reason(error_messages.join("\n")));
     }
@@ -171,25 +187,19 @@ impl CoreTransformer {
   }
 
   /// Parse method for testing - returns both modules and errors
-  pub fn parse(source: &str) -> (Vec<PactModule>, Vec<crate::error::ParseError>) {
-    let mut parser = get_parser();
+  /// Parse `source`, building `body_exprs` when `expressions` is set
+  pub fn parse(source: &str, expressions: bool) -> (Vec<PactModule>, Vec<Diagnostic>) {
+    let mut parser = get_parser().with_expressions(expressions);
     let result = parser.parse(source);
     return_parser(parser);
     result
   }
 
-  pub fn get_errors(source: &str) -> Vec<crate::ErrorInfo> {
+  pub fn get_errors(source: &str) -> Vec<Diagnostic> {
     let mut parser = get_parser();
     let (_, errors) = parser.parse(source);
     return_parser(parser);
     errors
-      .into_iter()
-      .map(|e| crate::ErrorInfo {
-        message: e.message,
-        line: e.line as u32,
-        column: e.column as u32,
-      })
-      .collect()
   }
 }
 
@@ -197,10 +207,15 @@ impl CoreTransformer {
 #[napi(object)]
 pub struct TransformationResult {
   pub modules: Vec<PactModule>,
+  pub interfaces: Vec<PactInterface>,
   pub code: String,
   pub types: String,
   pub source_map: Option<String>,
   pub declaration_map: Option<String>,
+  /// Parse errors kept in recovery mode, and warnings such as constants left out of the output
+  pub diagnostics: Vec<Diagnostic>,
+  /// What each module's definitions are called in the generated code
+  pub names: Vec<JsNames>,
 }
 
 /// Transform options
@@ -212,6 +227,26 @@ pub struct TransformOptions {
   pub source_maps: Option<bool>,
   pub source_file_path: Option<String>,
   pub declaration_maps: Option<bool>,
+  /// Namespace substituted for modules declared under a dynamic `(namespace ...)`
+  pub namespace: Option<String>,
+  /// How parse errors are reported: `text` code frames (default) or `json`
+  pub error_format: Option<String>,
+  /// Generate bindings for everything that parsed cleanly instead of failing on errors
+  pub recover: Option<bool>,
+  /// Also emit runtime validators and type guards for each `defschema`
+  pub validators: Option<bool>,
+  /// Module the generated code imports its runtime from (default `@pact-toolbox/transaction`)
+  pub runtime_import: Option<String>,
+  /// Name under which the runtime exports the execution builder (default `execution`)
+  pub execution_builder: Option<String>,
+  /// Name under which the runtime exports the continuation builder (default `continuation`)
+  pub continuation_builder: Option<String>,
+  /// Module syntax of the generated JavaScript: `esm` (default) or `cjs`
+  pub module_format: Option<String>,
+  /// How modules are exported: `flat` declarations (default) or one `namespaced` object per module
+  pub export_mode: Option<String>,
+  /// TypeScript types to use for Pact types and schemas, and the imports they need
+  pub type_mappings: Option<TypeMappings>,
 }
 
 /// Benchmark function to measure parser performance
@@ -306,6 +341,16 @@ mod tests {
         source_maps: None,
         source_file_path: None,
         declaration_maps: None,
+        namespace: None,
+        error_format: None,
+        recover: None,
+        validators: None,
+        runtime_import: None,
+        execution_builder: None,
+        continuation_builder: None,
+        module_format: None,
+        export_mode: None,
+        type_mappings: None,
       }),
     )
     .await
@@ -314,5 +359,96 @@ mod tests {
     assert!(!result.code.is_empty());
     assert!(!result.types.is_empty());
     assert_eq!(result.modules.len(), 1);
+    assert_eq!(result.names[0].module, "test-module");
+    assert_eq!(result.names[0].functions["test-function"], "testFunction");
+    assert_eq!(result.names[0].schemas["test-schema"], "TestSchema");
+  }
+
+  #[tokio::test]
+  async fn test_dynamic_namespace_option() {
+    let source = r"
+        (namespace (read-msg 'ns))
+        (module test-module GOVERNANCE
+          (defcap GOVERNANCE () true)
+          (defun ping:string () 'pong))
+        ";
+
+    let result = core_transform(
+      source.to_string(),
+      Some(TransformOptions {
+        namespace: Some("n_abc".to_string()),
+        ..TransformOptions::default()
+      }),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+      result.modules[0].dynamic_namespace.as_deref(),
+      Some("(read-msg 'ns)")
+    );
+    assert!(result.code.contains("(n_abc.test-module.ping)"));
+  }
+
+  #[tokio::test]
+  async fn test_errors_are_rendered() {
+    let source = "(module broken GOVERNANCE\n  (defun f () 1)";
+
+    let text = core_transform(source.to_string(), None)
+      .await
+      .err()
+      .unwrap();
+    assert!(text.reason.contains("--> input.pact:1:1"));
+    assert!(text.reason.contains("^ unclosed delimiter"));
+
+    let json = core_transform(
+      source.to_string(),
+      Some(TransformOptions {
+        source_file_path: Some("broken.pact".to_string()),
+        error_format: Some("json".to_string()),
+        ..TransformOptions::default()
+      }),
+    )
+    .await
+    .err()
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json.reason).unwrap();
+    assert_eq!(value[0]["code"], "P0002");
+    assert_eq!(value[0]["file"], "broken.pact");
+  }
+
+  #[tokio::test]
+  async fn test_recovery_mode_generates_clean_definitions() {
+    let source = r"
+        (module test-module GOVERNANCE
+          (defcap GOVERNANCE () true)
+          (defun good:string () 'ok)
+          (defun broken () })
+          (defun also-good:integer () 1))
+        ";
+
+    assert!(core_transform(source.to_string(), None).await.is_err());
+
+    let result = core_transform(
+      source.to_string(),
+      Some(TransformOptions {
+        recover: Some(true),
+        ..TransformOptions::default()
+      }),
+    )
+    .await
+    .unwrap();
+
+    assert!(result.code.contains("export function good()"));
+    assert!(result.code.contains("export function alsoGood()"));
+    assert!(!result.code.contains("broken"));
+    assert!(result
+      .diagnostics
+      .iter()
+      .any(|d| d.severity == Severity::Error));
+    assert!(result
+      .diagnostics
+      .iter()
+      .any(|d| d.code == "P0006" && d.message.contains("defun broken")));
   }
 }
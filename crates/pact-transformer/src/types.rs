@@ -1,16 +1,22 @@
+use crate::identifier::type_identifier;
 use ahash::AHashMap;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::LazyLock;
 
 pub static PACT_TO_TS_TYPE_MAP: LazyLock<AHashMap<&'static str, &'static str>> =
   LazyLock::new(|| {
     let mut map = AHashMap::new();
-    map.insert("integer", "number");
-    map.insert("decimal", "number");
+    // Wire shapes exported by the runtime, e.g. `{ decimal: "..." }` or `{ keys, pred }`
+    map.insert("integer", "PactInteger");
+    map.insert("decimal", "PactDecimal");
     map.insert("string", "string");
     map.insert("bool", "boolean");
+    // Decoded results; arguments take `PactTimeInput`
     map.insert("time", "Date");
-    map.insert("keyset", "object");
-    map.insert("guard", "object");
+    map.insert("keyset", "PactKeyset");
+    map.insert("guard", "PactGuard");
     map.insert("list", "unknown[]");
     map.insert("object", "Record<string, unknown>");
     map.insert("table", "Record<string, unknown>");
@@ -19,62 +25,263 @@ pub static PACT_TO_TS_TYPE_MAP: LazyLock<AHashMap<&'static str, &'static str>> =
     map
   });
 
-pub fn pact_type_to_typescript(pact_type: &str) -> String {
-  // Handle schema references like {todo} - return PascalCase schema name
-  if pact_type.starts_with('{') && pact_type.ends_with('}') {
-    let schema_name = &pact_type[1..pact_type.len() - 1];
-    return to_pascal_case(schema_name);
+/// User overrides of the Pact-to-TypeScript type mapping. Mapped values are passed through
+/// as Pact sends them unless `codecs` names functions converting them.
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TypeMappings {
+  /// TypeScript type per Pact type, e.g. `decimal` to `Amount`
+  pub types: Option<HashMap<String, String>>,
+  /// TypeScript type per schema wherever it is referenced, or per field as `schema.field`
+  pub schemas: Option<HashMap<String, String>>,
+  /// Import statements added to the header of generated declarations, e.g. for `Amount`
+  pub imports: Option<Vec<String>>,
+  /// Conversions of mapped types, under the same keys as `types` and `schemas`
+  pub codecs: Option<HashMap<String, TypeCodec>>,
+}
+
+/// Functions converting a mapped type to and from Pact JSON, imported by the generated code
+#[napi(object)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TypeCodec {
+  /// Module exporting the functions, e.g. `./amounts`
+  pub from: String,
+  /// Takes a value as Pact returns it and gives the mapped type
+  pub decode: Option<String>,
+  /// Takes the mapped type and gives a value the Pact type's encoding accepts
+  pub encode: Option<String>,
+}
+
+/// Maps Pact types to TypeScript, applying the configured `TypeMappings` over the defaults
+#[derive(Debug, Clone, Default)]
+pub struct TypeMapper {
+  types: AHashMap<String, String>,
+  schemas: AHashMap<String, String>,
+  imports: Vec<String>,
+  codecs: AHashMap<String, TypeCodec>,
+}
+
+impl TypeMapper {
+  pub fn new(mappings: Option<&TypeMappings>) -> Self {
+    let Some(mappings) = mappings else {
+      return Self::default();
+    };
+    let entries = |map: &Option<HashMap<String, String>>| {
+      map
+        .iter()
+        .flatten()
+        .map(|(pact, ts)| (pact.trim().to_string(), ts.trim().to_string()))
+        .collect()
+    };
+    Self {
+      types: entries(&mappings.types),
+      schemas: entries(&mappings.schemas),
+      imports: mappings.imports.clone().unwrap_or_default(),
+      codecs: mappings
+        .codecs
+        .iter()
+        .flatten()
+        .map(|(key, codec)| (key.trim().to_string(), codec.clone()))
+        .collect(),
+    }
+  }
+
+  /// Import statements the mapped types need
+  pub fn imports(&self) -> &[String] {
+    &self.imports
+  }
+
+  /// Functions of the codecs that apply, per module they're imported from, in a stable order
+  pub fn codec_imports(&self) -> Vec<(&str, Vec<&str>)> {
+    let mut imports: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (key, codec) in &self.codecs {
+      if self.types.contains_key(key) || self.schemas.contains_key(key) {
+        let functions = [&codec.decode, &codec.encode].into_iter().flatten();
+        imports
+          .entry(&codec.from)
+          .or_default()
+          .extend(functions.map(String::as_str));
+      }
+    }
+    imports
+      .into_iter()
+      .filter(|(_, functions)| !functions.is_empty())
+      .map(|(from, functions)| (from, functions.into_iter().collect()))
+      .collect()
+  }
+
+  /// Type of a value the generated code returns, such as a decoded result or schema field
+  pub fn to_typescript(&self, pact_type: &str) -> String {
+    self.map(pact_type, false)
   }
 
-  // Handle object types with schema like object{todo}
-  if pact_type.starts_with("object{") && pact_type.ends_with('}') {
-    let schema_name = &pact_type[7..pact_type.len() - 1];
-    return to_pascal_case(schema_name);
+  /// Type of an argument, which may be wider than the result type, e.g. times also take
+  /// ISO 8601 strings
+  pub fn to_input_typescript(&self, pact_type: &str) -> String {
+    self.map(pact_type, true)
   }
 
-  // Handle list types like [object{todo}] or [string]
-  if pact_type.starts_with('[') && pact_type.ends_with(']') {
-    let inner_type = &pact_type[1..pact_type.len() - 1];
-    if !inner_type.is_empty() {
-      let mapped_inner = pact_type_to_typescript(inner_type);
+  fn map(&self, pact_type: &str, input: bool) -> String {
+    let pact_type = pact_type.trim();
+    if let Some(mapped) = self.types.get(pact_type) {
+      return mapped.clone();
+    }
+    if input && pact_type == "time" {
+      return "PactTimeInput".to_string();
+    }
+
+    // Handle schema references like {todo} and object{todo} - return PascalCase schema name
+    if let Some(schema_name) = pact_type
+      .strip_prefix("object{")
+      .or_else(|| pact_type.strip_prefix('{'))
+      .and_then(|rest| rest.strip_suffix('}'))
+    {
+      return self.schema(schema_name);
+    }
+
+    // Handle list types like [object{todo}] or [string]
+    if let Some(inner_type) = pact_type
+      .strip_prefix('[')
+      .and_then(|rest| rest.strip_suffix(']'))
+    {
+      if inner_type.is_empty() {
+        return "unknown[]".to_string();
+      }
+      let mapped_inner = self.map(inner_type, input);
+      // `number | string` must become `(number | string)[]`, not `number | string[]`
+      if mapped_inner.contains(['|', '&']) {
+        return format!("({mapped_inner})[]");
+      }
       return format!("{mapped_inner}[]");
     }
-    return "unknown[]".to_string();
+
+    // Handle table types
+    if let Some(schema_name) = pact_type
+      .strip_prefix("table{")
+      .and_then(|rest| rest.strip_suffix('}'))
+    {
+      return format!("Record<string, {}>", self.schema(schema_name));
+    }
+
+    // Handle module types
+    if let Some(interfaces) = pact_type
+      .strip_prefix("module{")
+      .and_then(|rest| rest.strip_suffix('}'))
+    {
+      return format!("Record<string, {interfaces}>");
+    }
+
+    // Map basic types
+    (*PACT_TO_TS_TYPE_MAP.get(pact_type).unwrap_or(&"unknown")).to_string()
   }
 
-  // Handle table types
-  if pact_type.starts_with("table{") && pact_type.ends_with('}') {
-    let schema_name = &pact_type[6..pact_type.len() - 1];
-    return format!("Record<string, {}>", to_pascal_case(schema_name));
+  /// Type of a schema field, which `schemas` can override as `schema.field`
+  pub fn field_to_typescript(
+    &self,
+    schema_name: &str,
+    field_name: &str,
+    pact_type: &str,
+  ) -> String {
+    self
+      .schemas
+      .get(&format!("{schema_name}.{field_name}"))
+      .cloned()
+      .unwrap_or_else(|| self.to_typescript(pact_type))
   }
 
-  // Handle module types
-  if pact_type.starts_with("module{") && pact_type.ends_with('}') {
-    let interfaces = &pact_type[7..pact_type.len() - 1];
-    return format!("Record<string, {interfaces}>");
+  /// Whether the configured mappings replace the default type of `pact_type`, or of the schema it refers to
+  pub fn overrides(&self, pact_type: &str) -> bool {
+    let pact_type = pact_type.trim();
+    if self.types.contains_key(pact_type) {
+      return true;
+    }
+    if let Some(inner_type) = pact_type
+      .strip_prefix('[')
+      .and_then(|rest| rest.strip_suffix(']'))
+    {
+      return self.overrides(inner_type);
+    }
+    pact_type
+      .strip_prefix("object{")
+      .or_else(|| pact_type.strip_prefix("table{"))
+      .or_else(|| pact_type.strip_prefix('{'))
+      .and_then(|rest| rest.strip_suffix('}'))
+      .is_some_and(|schema_name| self.schemas.contains_key(schema_name))
   }
 
-  // Map basic types
-  (*PACT_TO_TS_TYPE_MAP.get(pact_type).unwrap_or(&"unknown")).to_string()
-}
+  /// Whether the configured mappings give a schema field its own type
+  pub fn overrides_field(&self, schema_name: &str, field_name: &str) -> bool {
+    self
+      .schemas
+      .contains_key(&format!("{schema_name}.{field_name}"))
+  }
+
+  /// Function decoding Pact JSON of `pact_type` into the type it's mapped to, and how many
+  /// lists deep the mapped type is
+  pub fn decoder(&self, pact_type: &str) -> Option<(&str, usize)> {
+    let (codec, depth) = self.codec(pact_type)?;
+    Some((codec.decode.as_deref()?, depth))
+  }
+
+  /// Function turning the type `pact_type` is mapped to back into a value it encodes as
+  pub fn encoder(&self, pact_type: &str) -> Option<(&str, usize)> {
+    let (codec, depth) = self.codec(pact_type)?;
+    Some((codec.encode.as_deref()?, depth))
+  }
+
+  /// Decoder of a field mapped as `schema.field`
+  pub fn field_decoder(&self, schema_name: &str, field_name: &str) -> Option<&str> {
+    self.field_codec(schema_name, field_name)?.decode.as_deref()
+  }
 
-/// Convert `snake_case` or kebab-case to `PascalCase`
-fn to_pascal_case(s: &str) -> String {
-  let mut result = String::new();
-  let mut capitalize_next = true;
-
-  for c in s.chars() {
-    if c == '-' || c == '_' {
-      capitalize_next = true;
-    } else if capitalize_next {
-      result.push(c.to_uppercase().next().unwrap_or(c));
-      capitalize_next = false;
-    } else {
-      result.push(c);
+  /// Encoder of a field mapped as `schema.field`
+  pub fn field_encoder(&self, schema_name: &str, field_name: &str) -> Option<&str> {
+    self.field_codec(schema_name, field_name)?.encode.as_deref()
+  }
+
+  /// The codec of the mapping `map` applies to `pact_type`, matched the same way
+  fn codec(&self, pact_type: &str) -> Option<(&TypeCodec, usize)> {
+    let mut pact_type = pact_type.trim();
+    let mut depth = 0;
+    loop {
+      if self.types.contains_key(pact_type) {
+        return Some((self.codecs.get(pact_type)?, depth));
+      }
+      match pact_type
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+      {
+        Some(inner) => {
+          pact_type = inner.trim();
+          depth += 1;
+        }
+        None => break,
+      }
     }
+    let schema_name = pact_type
+      .strip_prefix("object{")
+      .or_else(|| pact_type.strip_prefix('{'))
+      .and_then(|rest| rest.strip_suffix('}'))
+      .filter(|schema_name| self.schemas.contains_key(*schema_name))?;
+    Some((self.codecs.get(schema_name)?, depth))
   }
 
-  result
+  fn field_codec(&self, schema_name: &str, field_name: &str) -> Option<&TypeCodec> {
+    let key = format!("{schema_name}.{field_name}");
+    self
+      .schemas
+      .contains_key(&key)
+      .then(|| self.codecs.get(&key))
+      .flatten()
+  }
+
+  fn schema(&self, schema_name: &str) -> String {
+    self
+      .schemas
+      .get(schema_name)
+      .cloned()
+      .unwrap_or_else(|| type_identifier(schema_name))
+  }
 }
 
 pub fn convert_to_jsdoc(doc: Option<&str>) -> String {
@@ -94,22 +301,28 @@ pub fn convert_to_jsdoc(doc: Option<&str>) -> String {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::identifier::to_pascal_case;
+
+  /// Map a Pact type with the default mapping
+  fn pact_type_to_typescript(pact_type: &str) -> String {
+    TypeMapper::default().to_typescript(pact_type)
+  }
 
   #[test]
   fn test_basic_type_mapping() {
-    assert_eq!(pact_type_to_typescript("integer"), "number");
-    assert_eq!(pact_type_to_typescript("decimal"), "number");
+    assert_eq!(pact_type_to_typescript("integer"), "PactInteger");
+    assert_eq!(pact_type_to_typescript("decimal"), "PactDecimal");
     assert_eq!(pact_type_to_typescript("string"), "string");
     assert_eq!(pact_type_to_typescript("bool"), "boolean");
     assert_eq!(pact_type_to_typescript("time"), "Date");
-    assert_eq!(pact_type_to_typescript("keyset"), "object");
-    assert_eq!(pact_type_to_typescript("guard"), "object");
+    assert_eq!(pact_type_to_typescript("keyset"), "PactKeyset");
+    assert_eq!(pact_type_to_typescript("guard"), "PactGuard");
     assert_eq!(pact_type_to_typescript("unknown_type"), "unknown");
   }
 
   #[test]
   fn test_list_type_mapping() {
-    assert_eq!(pact_type_to_typescript("[integer]"), "number[]");
+    assert_eq!(pact_type_to_typescript("[integer]"), "PactInteger[]");
     assert_eq!(pact_type_to_typescript("[string]"), "string[]");
     assert_eq!(pact_type_to_typescript("[object{todo}]"), "Todo[]");
     assert_eq!(pact_type_to_typescript("[]"), "unknown[]");
@@ -125,6 +338,108 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_wire_shaped_types() {
+    let mapper = TypeMapper::default();
+    for (pact_type, result, input) in [
+      ("integer", "PactInteger", "PactInteger"),
+      ("decimal", "PactDecimal", "PactDecimal"),
+      ("time", "Date", "PactTimeInput"),
+      ("keyset", "PactKeyset", "PactKeyset"),
+      ("guard", "PactGuard", "PactGuard"),
+      ("[decimal]", "PactDecimal[]", "PactDecimal[]"),
+      ("[time]", "Date[]", "PactTimeInput[]"),
+      ("[[integer]]", "PactInteger[][]", "PactInteger[][]"),
+      ("[object{account}]", "Account[]", "Account[]"),
+    ] {
+      assert_eq!(mapper.to_typescript(pact_type), result, "{pact_type}");
+      assert_eq!(mapper.to_input_typescript(pact_type), input, "{pact_type}");
+    }
+
+    // A mapped time replaces both
+    let mapper = TypeMapper::new(Some(&TypeMappings {
+      types: Some(HashMap::from([("time".to_string(), "Instant".to_string())])),
+      ..TypeMappings::default()
+    }));
+    assert_eq!(mapper.to_input_typescript("[time]"), "Instant[]");
+    assert_eq!(mapper.to_typescript("time"), "Instant");
+  }
+
+  #[test]
+  fn test_custom_type_mappings() {
+    let mapper = TypeMapper::new(Some(&TypeMappings {
+      types: Some(HashMap::from([
+        ("decimal".to_string(), "Decimal".to_string()),
+        ("integer".to_string(), "number | bigint".to_string()),
+      ])),
+      schemas: Some(HashMap::from([
+        ("account".to_string(), "AccountRow".to_string()),
+        ("todo.due".to_string(), "string".to_string()),
+      ])),
+      imports: Some(vec![
+        "import type { Decimal } from \"decimal.js\"".to_string()
+      ]),
+      codecs: Some(HashMap::from([
+        (
+          "decimal".to_string(),
+          TypeCodec {
+            from: "./decimal".to_string(),
+            decode: Some("toDecimal".to_string()),
+            encode: Some("fromDecimal".to_string()),
+          },
+        ),
+        (
+          "todo.due".to_string(),
+          TypeCodec {
+            from: "./decimal".to_string(),
+            decode: Some("parseDue".to_string()),
+            encode: None,
+          },
+        ),
+        // Codecs only apply to mapped types
+        (
+          "bool".to_string(),
+          TypeCodec {
+            from: "./bool".to_string(),
+            decode: Some("toBool".to_string()),
+            encode: None,
+          },
+        ),
+      ])),
+    }));
+    assert_eq!(mapper.to_typescript("decimal"), "Decimal");
+    assert_eq!(mapper.to_typescript("[decimal]"), "Decimal[]");
+    assert_eq!(mapper.to_typescript("[integer]"), "(number | bigint)[]");
+    assert_eq!(mapper.to_typescript("string"), "string");
+    assert_eq!(mapper.to_typescript("object{account}"), "AccountRow");
+    assert_eq!(
+      mapper.to_typescript("table{account}"),
+      "Record<string, AccountRow>"
+    );
+    assert_eq!(mapper.to_typescript("object{todo}"), "Todo");
+    assert_eq!(mapper.field_to_typescript("todo", "due", "time"), "string");
+    assert_eq!(
+      mapper.field_to_typescript("todo", "done", "bool"),
+      "boolean"
+    );
+    assert_eq!(mapper.imports().len(), 1);
+    assert!(mapper.overrides("[decimal]"));
+    assert!(mapper.overrides("{account}"));
+    assert!(!mapper.overrides("object{todo}"));
+    assert!(mapper.overrides_field("todo", "due"));
+
+    assert_eq!(mapper.decoder("decimal"), Some(("toDecimal", 0)));
+    assert_eq!(mapper.encoder("[[decimal]]"), Some(("fromDecimal", 2)));
+    assert_eq!(mapper.decoder("bool"), None);
+    assert_eq!(mapper.decoder("object{account}"), None);
+    assert_eq!(mapper.field_decoder("todo", "due"), Some("parseDue"));
+    assert_eq!(mapper.field_encoder("todo", "due"), None);
+    assert_eq!(
+      mapper.codec_imports(),
+      vec![("./decimal", vec!["fromDecimal", "parseDue", "toDecimal"])]
+    );
+  }
+
   #[test]
   fn test_pascal_case_conversion() {
     assert_eq!(to_pascal_case("todo"), "Todo");
@@ -271,15 +586,15 @@ mod tests {
   #[test]
   fn test_real_world_pact_types() {
     // Common Pact types from real contracts
-    assert_eq!(pact_type_to_typescript("decimal"), "number");
+    assert_eq!(pact_type_to_typescript("decimal"), "PactDecimal");
     assert_eq!(pact_type_to_typescript("object{account}"), "Account");
     assert_eq!(pact_type_to_typescript("[object{account}]"), "Account[]");
     assert_eq!(
       pact_type_to_typescript("table{balances}"),
       "Record<string, Balances>"
     );
-    assert_eq!(pact_type_to_typescript("keyset"), "object");
-    assert_eq!(pact_type_to_typescript("guard"), "object");
+    assert_eq!(pact_type_to_typescript("keyset"), "PactKeyset");
+    assert_eq!(pact_type_to_typescript("guard"), "PactGuard");
     assert_eq!(
       pact_type_to_typescript("module{fungible-v2}"),
       "Record<string, fungible-v2>"
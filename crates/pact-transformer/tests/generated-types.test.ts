@@ -0,0 +1,166 @@
+import { mkdtempSync, writeFileSync } from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { fileURLToPath } from "node:url";
+import ts from "typescript";
+import { describe, it, expect } from "vitest";
+import { createPactTransformer } from "../index.js";
+
+// Generated code imports the runtime by package name; check it against the runtime's sources
+const runtime = fileURLToPath(new URL("../../../packages/transaction/src/index.ts", import.meta.url));
+
+const coin = `
+(namespace 'free)
+(module coin GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema limit amount:decimal until:time)
+  (defschema account balance:decimal owner:string limits:[object{limit}])
+  (defcap TRANSFER (sender:string receiver:string amount:decimal)
+    @managed amount TRANSFER-mgr
+    true)
+  (defun TRANSFER-mgr:decimal (managed:decimal requested:decimal) managed)
+  (defcap DEBIT (sender:string amount:decimal) @event true)
+  (defconst MAX-LIMITS 10)
+  (defun get-account:object{account} (id:string) {})
+  (defun get-balance:decimal (id:string) 1.0)
+  (defun create-account:string (id:string guard:guard owner:keyset) id)
+  (defun open-accounts:string (accounts:[object{account}]) "")
+  (defun transfer:string (sender:string receiver:string amount:decimal)
+    (with-capability (TRANSFER sender receiver amount) ""))
+  (defpact swap:string (id:string)
+    (step "start")
+    (step "finish")))
+`;
+
+const ledger = `
+(module ledger GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema entry amount:decimal memo:string)
+  (defun credit:decimal (amount:decimal) amount)
+  (defun totals:[decimal] (entries:[object{entry}]) [])
+  (defun get-entry:object{entry} (memo:string) {}))
+`;
+
+// A type Pact doesn't send, converted by the codec the mapping names
+const amounts = `
+export class Amount {
+  constructor(readonly digits: string) {}
+}
+export const toAmount = (value: unknown): Amount =>
+  new Amount(typeof value === "number" ? String(value) : (value as { decimal: string }).decimal);
+export const fromAmount = (amount: Amount) => ({ decimal: amount.digits });
+`;
+
+// Clashes with `coin` unless each module is exported on its own
+const token = `
+(module token GOVERNANCE
+  (defcap GOVERNANCE () true)
+  (defschema account supply:integer)
+  (defun get-account:object{account} (id:string) {}))
+`;
+
+/** Type errors in `file`, compiled the way `tsc --noEmit --strict` would */
+function typeErrors(file: string): string[] {
+  const program = ts.createProgram([file], {
+    noEmit: true,
+    strict: true,
+    skipLibCheck: true,
+    target: ts.ScriptTarget.ES2022,
+    module: ts.ModuleKind.ESNext,
+    moduleResolution: ts.ModuleResolutionKind.Bundler,
+    lib: ["lib.es2022.d.ts", "lib.dom.d.ts"],
+    paths: { "@pact-toolbox/transaction": [runtime] },
+  });
+  return ts
+    .getPreEmitDiagnostics(program, program.getSourceFile(file))
+    .map((diagnostic) => ts.flattenDiagnosticMessageText(diagnostic.messageText, "\n"));
+}
+
+describe("generated TypeScript", () => {
+  it.each([
+    ["flat", coin],
+    ["namespaced", coin + token],
+  ])("type-checks against the runtime in %s mode", async (exportMode, pactCode) => {
+    const dir = mkdtempSync(join(tmpdir(), "pact-transformer-"));
+    const pactFile = join(dir, "coin.pact");
+    writeFileSync(pactFile, pactCode);
+
+    const transformer = createPactTransformer({
+      transform: { exportMode },
+      fileOutput: { outputDir: join(dir, "out"), format: "ts" },
+    });
+    const result = await transformer.transformFile(pactFile);
+
+    expect(result.success).toBe(true);
+    expect(typeErrors(result.outputPath!)).toEqual([]);
+  });
+
+  it("type-checks with mapped types that describe values as Pact sends them", async () => {
+    const dir = mkdtempSync(join(tmpdir(), "pact-transformer-"));
+    const pactFile = join(dir, "coin.pact");
+    writeFileSync(pactFile, coin);
+    writeFileSync(
+      join(dir, "amounts.ts"),
+      'export type Amount = number | { decimal: string };\nexport type AccountName = string & { readonly __account: true };\n',
+    );
+
+    const transformer = createPactTransformer({
+      transform: {
+        typeMappings: {
+          types: { decimal: "Amount" },
+          schemas: { "account.owner": "AccountName" },
+          imports: ['import type { Amount, AccountName } from "../amounts"'],
+        },
+      },
+      fileOutput: { outputDir: join(dir, "out"), format: "ts" },
+    });
+    const result = await transformer.transformFile(pactFile);
+
+    expect(result.success).toBe(true);
+    expect(typeErrors(result.outputPath!)).toEqual([]);
+  });
+
+  it("round-trips mapped types through their codecs", async () => {
+    const dir = mkdtempSync(join(tmpdir(), "pact-transformer-"));
+    const pactFile = join(dir, "ledger.pact");
+    writeFileSync(pactFile, ledger);
+    writeFileSync(join(dir, "amounts.ts"), amounts);
+
+    const transform = (outputDir: string, runtimeImport?: string) =>
+      createPactTransformer({
+        transform: {
+          runtimeImport,
+          typeMappings: {
+            types: { decimal: "Amount" },
+            imports: ['import type { Amount } from "../amounts"'],
+            codecs: { decimal: { from: "../amounts", decode: "toAmount", encode: "fromAmount" } },
+          },
+        },
+        fileOutput: { outputDir: join(dir, outputDir), format: "ts" },
+      }).transformFile(pactFile);
+
+    const checked = await transform("checked");
+    expect(checked.success).toBe(true);
+    expect(typeErrors(checked.outputPath!)).toEqual([]);
+
+    // Import the runtime by path so the generated module can run here
+    const run = await transform("run", runtime);
+    expect(run.success).toBe(true);
+    const { Amount } = await import(join(dir, "amounts.ts"));
+    const generated = await import(run.outputPath!);
+
+    const credit = generated.credit(new Amount("0.100000000000000000001"));
+    expect(credit.getCommand().payload.exec.code).toBe("(ledger.credit 0.100000000000000000001)");
+    const decoded = credit.decodeResult({ decimal: "0.100000000000000000001" });
+    expect(decoded).toBeInstanceOf(Amount);
+    expect(decoded).toEqual(new Amount("0.100000000000000000001"));
+
+    const totals = generated.totals([{ amount: new Amount("1.5"), memo: "a" }]);
+    expect(totals.getCommand().payload.exec.code).toBe('(ledger.totals [{"amount": 1.5, "memo": "a"}])');
+    expect(totals.decodeResult([2, { decimal: "2.25" }])).toEqual([new Amount("2"), new Amount("2.25")]);
+    expect(generated.decodeEntry({ amount: { decimal: "1.5" }, memo: "a" })).toEqual({
+      amount: new Amount("1.5"),
+      memo: "a",
+    });
+  });
+});
@@ -153,6 +153,22 @@ describe("PactTransformer API", () => {
       expect(module.schemaCount).toBe(1);
       expect(module.capabilityCount).toBe(1);
       expect(module.constantCount).toBe(1);
+      expect(module.functions[0].bodyExprs).toBeUndefined();
+    });
+
+    it("should include typed bodies when expressions are requested", () => {
+      const transformer = createPactTransformer();
+
+      const [module] = transformer.parse(
+        `(module test-module GOVERNANCE
+          (defcap GOVERNANCE () true)
+          (defun greet:string (name:string) (format "Hello, {}!" [name])))`,
+        { expressions: true },
+      );
+
+      const [body] = module.functions[0].bodyExprs ?? [];
+      expect(body.kind).toBe("Application");
+      expect(body.value).toBe("format");
     });
 
     it("should parse multiple modules", () => {
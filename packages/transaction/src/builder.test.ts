@@ -159,6 +159,15 @@ describe("PactTransactionBuilder", () => {
       expect(command.payload.exec.data["alice-keyset"]).toEqual(keyset);
     });
 
+    it("should add guard with withGuard", () => {
+      const guard = { fun: "free.coin.enforce-owner", args: ["alice"] };
+
+      builder.withGuard("owner-guard", guard);
+      const command = builder.getCommand();
+
+      expect(command.payload.exec.data["owner-guard"]).toEqual(guard);
+    });
+
     it("should add multiple keysets with withKeysetMap", () => {
       const keysets = {
         "alice-keyset": { keys: ["alice-key"], pred: "keys-all" as const },
@@ -6,6 +6,7 @@ import type {
   PactCont,
   PactContPayload,
   PactExecPayload,
+  PactGuard,
   PactKeyset,
   PactMetadata,
   PactSignerLike,
@@ -54,6 +55,7 @@ export class PactTransactionBuilder<Payload extends PactCmdPayload, Result = unk
   #cmd: PactCommand<Payload>;
   #context: ToolboxNetworkContext;
   #builder: (cmd: PactCommand<Payload>) => Promise<Transaction> = async (cmd) => createTransaction(cmd);
+  #decoder?: (value: unknown) => unknown;
 
   /**
    * Create a new transaction builder
@@ -107,6 +109,18 @@ export class PactTransactionBuilder<Payload extends PactCmdPayload, Result = unk
     return this;
   }
 
+  /**
+   * Add a guard of any kind to the transaction data, to be read with `read-msg`
+   *
+   * @param name - The data key to store the guard under
+   * @param guard - The guard, such as a keyset or a user guard
+   * @returns This builder instance for chaining
+   */
+  withGuard(name: string, guard: PactGuard): this {
+    this.withData(name, guard);
+    return this;
+  }
+
   /**
    * Add multiple keysets at once
    *
@@ -215,6 +229,18 @@ export class PactTransactionBuilder<Payload extends PactCmdPayload, Result = unk
     return this;
   }
 
+  /**
+   * Convert the raw Pact result into `Decoded` before the dispatcher returns it,
+   * e.g. `{ decimal: "1.5" }` into `1.5`
+   *
+   * @param decode - Conversion applied to the result of each chain
+   * @returns This builder instance, typed with the decoded result
+   */
+  withDecoder<Decoded>(decode: (value: unknown) => Decoded): PactTransactionBuilder<Payload, Decoded> {
+    this.#decoder = decode;
+    return this as unknown as PactTransactionBuilder<Payload, Decoded>;
+  }
+
   /**
    * Build the transaction without signing (returns unsigned transaction)
    *
@@ -295,6 +321,16 @@ export class PactTransactionBuilder<Payload extends PactCmdPayload, Result = unk
     return this.#builder(this.#cmd);
   }
 
+  /**
+   * Decode a raw Pact result with the decoder set by `withDecoder` (internal method)
+   *
+   * @param value - Result data of one chain
+   * @returns The decoded result, or `value` itself without a decoder
+   */
+  decodeResult(value: unknown): Result {
+    return (this.#decoder ? this.#decoder(value) : value) as Result;
+  }
+
   /**
    * Get the raw Pact command (internal method)
    *
@@ -72,6 +72,19 @@ describe("PactTransactionDispatcher", () => {
       expect(result).toEqual(mockResult);
     });
 
+    it("should decode the result with the builder's decoder", async () => {
+      const { dirtyReadOrFail } = await import("@pact-toolbox/chainweb-client");
+      vi.mocked(dirtyReadOrFail).mockResolvedValue({ decimal: "100.5" });
+
+      const dispatcher = execution('(coin.get-balance "alice")')
+        .withDecoder((value) => Number((value as { decimal: string }).decimal))
+        .build();
+
+      const result: number = await dispatcher.dirtyRead("1");
+
+      expect(result).toBe(100.5);
+    });
+
     it("should call dirtyReadOrFail for multiple chains", async () => {
       const { dirtyReadOrFail } = await import("@pact-toolbox/chainweb-client");
       const mockResult = [{ balance: "100.0" }, { balance: "200.0" }];
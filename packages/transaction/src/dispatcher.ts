@@ -43,6 +43,13 @@ async function dispatchTransaction<
     default:
       throw new Error("Unknown transaction type");
   }
+  if ("submit" !== type) {
+    result = (result as Promise<unknown>).then((data) =>
+      Array.isArray(txs) && Array.isArray(data)
+        ? data.map((item) => builder.decodeResult(item))
+        : builder.decodeResult(data),
+    );
+  }
   return result as Type extends "submit" ? PactTransactionDescriptor : Result;
 }
 
@@ -0,0 +1,152 @@
+import { describe, it, expect } from "vitest";
+
+import {
+  encodePactArg,
+  encodePactDecimal,
+  encodePactInteger,
+  encodePactString,
+  encodePactTime,
+  fromPactValue,
+  toPactValue,
+} from "./encoding";
+import { pactDecimal } from "./utils";
+
+describe("encodePactDecimal", () => {
+  it("adds a decimal point to whole numbers", () => {
+    expect(encodePactDecimal(1)).toBe("1.0");
+    expect(encodePactDecimal("25")).toBe("25.0");
+    expect(encodePactDecimal(10n)).toBe("10.0");
+  });
+
+  it("keeps fractional values as written", () => {
+    expect(encodePactDecimal(0.5)).toBe("0.5");
+    expect(encodePactDecimal("-12.345")).toBe("-12.345");
+    expect(encodePactDecimal(pactDecimal(1.5))).toBe("1.500000000000");
+  });
+
+  it("spells out values JavaScript prints in exponent notation", () => {
+    expect(encodePactDecimal(1e-7)).toBe("0.0000001");
+    expect(encodePactDecimal(1e21)).toBe("1000000000000000000000.0");
+    expect(encodePactDecimal(1e-13)).toBe("0.0000000000001");
+    expect(encodePactDecimal(-1.5e-20)).toBe("-0.000000000000000000015");
+    expect(encodePactDecimal(1.2345e25)).toBe("12345000000000000000000000.0");
+  });
+
+  it("rejects values that are not decimals", () => {
+    expect(() => encodePactDecimal(Number.NaN)).toThrow();
+    expect(() => encodePactDecimal("1.2.3")).toThrow();
+    expect(() => encodePactDecimal(1e-300)).toThrow(/places/);
+  });
+});
+
+describe("encodePactInteger", () => {
+  it("encodes safe integers, bigints and digit strings", () => {
+    expect(encodePactInteger(42)).toBe("42");
+    expect(encodePactInteger(9007199254740993n)).toBe("9007199254740993");
+    expect(encodePactInteger("-9007199254740993")).toBe("-9007199254740993");
+    expect(encodePactInteger({ int: "7" })).toBe("7");
+  });
+
+  it("rejects unsafe or fractional numbers", () => {
+    expect(() => encodePactInteger(2 ** 60)).toThrow(/bigint/);
+    expect(() => encodePactInteger(1.5)).toThrow();
+  });
+});
+
+describe("encodePactString", () => {
+  it("escapes newlines, tabs, quotes and backslashes the way Pact reads them", () => {
+    expect(encodePactString('line\none\tsaid "hi" \\o/')).toBe('"line\\none\\tsaid \\"hi\\" \\\\o/"');
+  });
+
+  it("writes characters outside the BMP as they are", () => {
+    expect(encodePactString("gm 🚀")).toBe('"gm 🚀"');
+    expect(encodePactArg("tab\t🚀\n", "string")).toBe('"tab\\t🚀\\n"');
+  });
+
+  it("rejects control characters and lone surrogates Pact can't read", () => {
+    expect(() => encodePactString("bell\u0007")).toThrow(/U\+0007/);
+    expect(() => encodePactString("\r")).toThrow();
+    expect(() => encodePactString("\ud83d")).toThrow();
+  });
+});
+
+describe("encodePactTime", () => {
+  it("wraps dates in a time call without milliseconds", () => {
+    expect(encodePactTime(new Date(Date.UTC(2024, 0, 2, 3, 4, 5, 678)))).toBe('(time "2024-01-02T03:04:05Z")');
+    expect(encodePactTime("2024-01-01T00:00:00Z")).toBe('(time "2024-01-01T00:00:00Z")');
+  });
+});
+
+describe("encodePactArg", () => {
+  it("encodes by declared type", () => {
+    expect(encodePactArg(1, "decimal")).toBe("1.0");
+    expect(encodePactArg(1, "integer")).toBe("1");
+    expect(encodePactArg('say "hi"', "string")).toBe('"say \\"hi\\""');
+    expect(encodePactArg(false, "bool")).toBe("false");
+    expect(encodePactArg([1, 2], "[decimal]")).toBe("[1.0 2.0]");
+  });
+
+  it("rejects values that are not booleans for bool", () => {
+    expect(() => encodePactArg("false", "bool")).toThrow();
+    expect(() => encodePactArg(0, "bool")).toThrow();
+    expect(() => toPactValue("true", "bool")).toThrow();
+  });
+
+  it("encodes schema objects with the given encoder", () => {
+    const encodeTransfer = (transfer: { amount: number; to: string }) =>
+      `{"amount": ${encodePactDecimal(transfer.amount)}, "to": ${JSON.stringify(transfer.to)}}`;
+    expect(encodePactArg({ amount: 1, to: "bob" }, "object{transfer}", encodeTransfer)).toBe(
+      '{"amount": 1.0, "to": "bob"}',
+    );
+    expect(encodePactArg([{ amount: 2, to: "alice" }], "[object{transfer}]", encodeTransfer)).toBe(
+      '[{"amount": 2.0, "to": "alice"}]',
+    );
+  });
+
+  it("encodes untyped values by their JavaScript type", () => {
+    expect(encodePactArg(3)).toBe("3");
+    expect(encodePactArg(0.25)).toBe("0.25");
+    expect(encodePactArg({ amount: 1.5, to: "bob", tags: ["a"] }, "object{transfer}")).toBe(
+      '{"amount": 1.5, "to": "bob", "tags": ["a"]}',
+    );
+  });
+});
+
+describe("toPactValue", () => {
+  it("wraps decimals and large integers", () => {
+    expect(toPactValue(10, "decimal")).toEqual({ decimal: "10.0" });
+    expect(toPactValue(10, "integer")).toBe(10);
+    expect(toPactValue(9007199254740993n, "integer")).toEqual({ int: "9007199254740993" });
+    expect(toPactValue([1, 2.5], "[decimal]")).toEqual([{ decimal: "1.0" }, { decimal: "2.5" }]);
+  });
+
+  it("passes other values through", () => {
+    expect(toPactValue("alice", "string")).toBe("alice");
+    expect(toPactValue({ keys: ["abc"], pred: "keys-all" }, "guard")).toEqual({ keys: ["abc"], pred: "keys-all" });
+  });
+});
+
+describe("fromPactValue", () => {
+  it("unwraps numbers and times", () => {
+    expect(fromPactValue({ decimal: "1.5" }, "decimal")).toBe(1.5);
+    expect(fromPactValue({ decimal: "1.000000000000" }, "decimal")).toBe(1);
+    expect(fromPactValue({ int: "42" }, "integer")).toBe(42);
+    expect(fromPactValue(7, "integer")).toBe(7);
+    expect(fromPactValue({ time: "2024-01-01T00:00:00Z" }, "time")).toEqual(new Date("2024-01-01T00:00:00Z"));
+    expect(fromPactValue([{ decimal: "1.0" }, 2], "[decimal]")).toEqual([1, 2]);
+  });
+
+  it("keeps the wire form of numbers a number can't hold", () => {
+    expect(fromPactValue({ decimal: "0.123456789012345678" }, "decimal")).toEqual({ decimal: "0.123456789012345678" });
+    expect(fromPactValue({ decimal: "12345678901234567890.5" }, "decimal")).toEqual({
+      decimal: "12345678901234567890.5",
+    });
+    expect(fromPactValue({ int: "9007199254740993" }, "integer")).toEqual({ int: "9007199254740993" });
+    expect(fromPactValue([{ int: "9007199254740993" }], "[integer]")).toEqual([{ int: "9007199254740993" }]);
+  });
+
+  it("passes other values through", () => {
+    expect(fromPactValue("alice", "string")).toBe("alice");
+    expect(fromPactValue({ keys: ["abc"], pred: "keys-all" })).toEqual({ keys: ["abc"], pred: "keys-all" });
+  });
+});
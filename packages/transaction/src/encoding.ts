@@ -0,0 +1,300 @@
+/**
+ * Encoding of JavaScript values into Pact source literals.
+ *
+ * Generated bindings splice arguments into Pact code, so every value has to be
+ * written the way the Pact parser reads it for the parameter's declared type.
+ */
+
+import type { PactDecimal, PactInteger, PactValue } from "@pact-toolbox/types";
+
+const INTEGER_PATTERN = /^-?\d+$/;
+const DECIMAL_PATTERN = /^-?\d+(\.\d+)?$/;
+const EXPONENT_PATTERN = /^(-?)(\d+)(?:\.(\d+))?e([+-]?\d+)$/;
+
+/** Pact decimals keep at most this many places */
+const MAX_DECIMAL_PLACES = 255;
+
+/**
+ * Encodes an integer. Numbers must be safe integers; use a `bigint` or a
+ * string of digits for anything larger.
+ */
+export function encodePactInteger(value: unknown): string {
+  if ("bigint" === typeof value) {
+    return value.toString();
+  }
+  if ("number" === typeof value) {
+    if (!Number.isSafeInteger(value)) {
+      throw new Error(`Cannot encode ${value} as a Pact integer; pass a bigint or string for large values`);
+    }
+    return value.toString();
+  }
+  const text = isWrapped(value, "int") ? String(value.int) : value;
+  if ("string" === typeof text && INTEGER_PATTERN.test(text)) {
+    return text;
+  }
+  throw new Error(`Cannot encode ${String(value)} as a Pact integer`);
+}
+
+/**
+ * Encodes a decimal, always with a decimal point since Pact reads `1` as an integer.
+ * Accepts numbers, bigints, digit strings and `{ decimal }` objects from `pactDecimal`.
+ * Throws rather than rounding when the value has more places than Pact keeps.
+ */
+export function encodePactDecimal(value: unknown): string {
+  let text: string;
+  if ("bigint" === typeof value) {
+    text = value.toString();
+  } else if ("number" === typeof value) {
+    if (!Number.isFinite(value)) {
+      throw new Error(`Cannot encode ${value} as a Pact decimal`);
+    }
+    // Exponent notation is not valid Pact, spell the digits out
+    text = expandExponent(value.toString());
+  } else if (isWrapped(value, "decimal")) {
+    text = String(value.decimal);
+  } else if ("string" === typeof value) {
+    text = value;
+  } else {
+    throw new Error(`Cannot encode ${String(value)} as a Pact decimal`);
+  }
+
+  if (text.endsWith(".")) {
+    text += "0";
+  }
+  if (!DECIMAL_PATTERN.test(text)) {
+    throw new Error(`Cannot encode ${text} as a Pact decimal`);
+  }
+  if ((text.split(".")[1]?.length ?? 0) > MAX_DECIMAL_PLACES) {
+    throw new Error(`Cannot encode ${text} as a Pact decimal, it has more than ${MAX_DECIMAL_PLACES} places`);
+  }
+  return text.includes(".") ? text : `${text}.0`;
+}
+
+/**
+ * Encodes a string literal. Pact reads `\\`, `\"`, `\n` and `\t` escapes but not
+ * JSON's `\uXXXX`, so every other character is written as is, and control
+ * characters Pact has no escape for are rejected.
+ */
+export function encodePactString(value: string): string {
+  let text = "";
+  for (const char of value) {
+    const code = char.codePointAt(0) ?? 0;
+    if ("\\" === char || '"' === char) {
+      text += `\\${char}`;
+    } else if ("\n" === char) {
+      text += "\\n";
+    } else if ("\t" === char) {
+      text += "\\t";
+    } else if (code < 0x20 || (code >= 0xd800 && code <= 0xdfff)) {
+      // Lone surrogates aren't text either
+      throw new Error(`Cannot encode character U+${code.toString(16).padStart(4, "0")} in a Pact string`);
+    } else {
+      text += char;
+    }
+  }
+  return `"${text}"`;
+}
+
+/**
+ * Encodes a time as `(time "...")`. Dates are written in UTC without
+ * milliseconds, the format `time` parses.
+ */
+export function encodePactTime(value: unknown): string {
+  let text: string;
+  if (value instanceof Date) {
+    text = value.toISOString().replace(/\.\d{3}Z$/, "Z");
+  } else if (isWrapped(value, "time")) {
+    text = String(value.time);
+  } else if ("string" === typeof value) {
+    text = value;
+  } else {
+    throw new Error(`Cannot encode ${String(value)} as a Pact time`);
+  }
+  return `(time ${encodePactString(text)})`;
+}
+
+/**
+ * Encodes a value for a parameter of the given Pact type, such as `decimal`,
+ * `[integer]` or `object{account}`. Objects are written by `encodeObject`, such
+ * as a generated `encodeAccount`, when one is given. Untyped values are encoded
+ * by their JavaScript type.
+ */
+export function encodePactArg<T>(value: unknown, type?: string, encodeObject?: (value: T) => string): string {
+  const baseType = type?.trim();
+  if (baseType?.startsWith("[") && baseType.endsWith("]")) {
+    if (!Array.isArray(value)) {
+      throw new Error(`Expected a list for Pact type ${baseType}`);
+    }
+    const itemType = baseType.slice(1, -1).trim() || undefined;
+    return `[${value.map((item) => encodePactArg(item, itemType, encodeObject)).join(" ")}]`;
+  }
+
+  switch (baseType) {
+    case "integer":
+      return encodePactInteger(value);
+    case "decimal":
+      return encodePactDecimal(value);
+    case "time":
+      return encodePactTime(value);
+    case "string":
+      return encodePactString(String(value));
+    case "bool":
+      return encodePactBool(value).toString();
+    default:
+      return encodeObject ? encodeObject(value as T) : encodeUntyped(value);
+  }
+}
+
+/**
+ * Converts a value to the JSON form of a Pact value for the given type, as used
+ * in capability arguments where code literals don't apply.
+ */
+export function toPactValue(value: unknown, type?: string): PactValue {
+  const baseType = type?.trim();
+  if (baseType?.startsWith("[") && baseType.endsWith("]") && Array.isArray(value)) {
+    const itemType = baseType.slice(1, -1).trim() || undefined;
+    return value.map((item) => toPactValue(item, itemType));
+  }
+
+  switch (baseType) {
+    case "integer": {
+      const text = encodePactInteger(value);
+      const int = Number(text);
+      return Number.isSafeInteger(int) ? int : { int: text };
+    }
+    case "decimal":
+      return { decimal: encodePactDecimal(value) };
+    case "time":
+      return (value instanceof Date ? { time: value.toISOString().replace(/\.\d{3}Z$/, "Z") } : value) as PactValue;
+    case "string":
+      return String(value);
+    case "bool":
+      return encodePactBool(value);
+    default:
+      return "bigint" === typeof value ? { int: value.toString() } : (value as PactValue);
+  }
+}
+
+/**
+ * What {@link fromPactValue} returns for a Pact type: `PactDecimal` and
+ * `PactInteger` for numbers, `Date` for times, lists of those, and `unknown`
+ * for values it passes through.
+ */
+export type DecodedPactValue<T extends string> = T extends `[${infer Item}]`
+  ? DecodedPactValue<Item>[]
+  : T extends "decimal"
+    ? PactDecimal
+    : T extends "integer"
+      ? PactInteger
+      : T extends "time"
+        ? Date
+        : unknown;
+
+/**
+ * Converts a Pact value from a command result back to JavaScript, the inverse
+ * of {@link toPactValue}. Decimals and integers become numbers when a number
+ * holds them exactly and otherwise keep their `{ decimal }` or `{ int }` form;
+ * times become dates.
+ */
+export function fromPactValue<T extends string>(value: unknown, type: T): DecodedPactValue<T>;
+export function fromPactValue(value: unknown, type?: string): unknown;
+export function fromPactValue(value: unknown, type?: string): unknown {
+  const baseType = type?.trim();
+  if (Array.isArray(value)) {
+    const itemType = baseType?.startsWith("[") && baseType.endsWith("]") ? baseType.slice(1, -1).trim() : undefined;
+    return value.map((item) => fromPactValue(item, itemType || undefined));
+  }
+  if (isWrapped(value, "decimal") || ("decimal" === baseType && "number" === typeof value)) {
+    return fromPactDecimal(value);
+  }
+  if (isWrapped(value, "int") || ("integer" === baseType && "number" === typeof value)) {
+    return fromPactInteger(value);
+  }
+  if (isWrapped(value, "time") || isWrapped(value, "timep")) {
+    return new Date(String("time" in value ? value.time : value.timep));
+  }
+  if ("time" === baseType && "string" === typeof value) {
+    return new Date(value);
+  }
+  return value;
+}
+
+function fromPactDecimal(value: unknown): PactDecimal {
+  if ("number" === typeof value) {
+    return value;
+  }
+  const text = isWrapped(value, "decimal") ? String(value.decimal) : value;
+  if ("string" !== typeof text || !DECIMAL_PATTERN.test(text)) {
+    throw new Error(`Cannot decode ${String(value)} as a Pact decimal`);
+  }
+  // Trailing zeros don't change the value, any other digit a number can't hold does
+  const digits = text.replace(/(\.\d*?)0+$/, "$1").replace(/\.$/, "");
+  const number = Number(text);
+  return expandExponent(number.toString()) === digits ? number : { decimal: text };
+}
+
+function fromPactInteger(value: unknown): PactInteger {
+  if ("number" === typeof value) {
+    return value;
+  }
+  const text = isWrapped(value, "int") ? String(value.int) : value;
+  if ("string" !== typeof text || !INTEGER_PATTERN.test(text)) {
+    throw new Error(`Cannot decode ${String(value)} as a Pact integer`);
+  }
+  const number = Number(text);
+  return Number.isSafeInteger(number) ? number : { int: text };
+}
+
+function encodePactBool(value: unknown): boolean {
+  if ("boolean" !== typeof value) {
+    throw new Error(`Cannot encode ${String(value)} as a Pact bool`);
+  }
+  return value;
+}
+
+function expandExponent(text: string): string {
+  const match = EXPONENT_PATTERN.exec(text);
+  if (!match) {
+    return text;
+  }
+  const [, sign, whole, fraction = "", exponent] = match;
+  const digits = whole + fraction;
+  const point = whole.length + Number(exponent);
+  if (point <= 0) {
+    return `${sign}0.${"0".repeat(-point)}${digits}`;
+  }
+  if (point >= digits.length) {
+    return `${sign}${digits}${"0".repeat(point - digits.length)}`;
+  }
+  return `${sign}${digits.slice(0, point)}.${digits.slice(point)}`;
+}
+
+function encodeUntyped(value: unknown): string {
+  if ("bigint" === typeof value) {
+    return encodePactInteger(value);
+  }
+  if ("number" === typeof value) {
+    return Number.isInteger(value) ? encodePactInteger(value) : encodePactDecimal(value);
+  }
+  if (value instanceof Date) {
+    return encodePactTime(value);
+  }
+  if (Array.isArray(value)) {
+    return `[${value.map((item) => encodeUntyped(item)).join(" ")}]`;
+  }
+  if (null !== value && "object" === typeof value) {
+    if (isWrapped(value, "decimal")) {
+      return encodePactDecimal(value);
+    }
+    if (isWrapped(value, "int")) {
+      return encodePactInteger(value);
+    }
+    const fields = Object.entries(value).map(([key, item]) => `${encodePactString(key)}: ${encodeUntyped(item)}`);
+    return `{${fields.join(", ")}}`;
+  }
+  return "string" === typeof value ? encodePactString(value) : JSON.stringify(value);
+}
+
+function isWrapped<K extends string>(value: unknown, key: K): value is Record<K, unknown> {
+  return null !== value && "object" === typeof value && key in value && 1 === Object.keys(value).length;
+}
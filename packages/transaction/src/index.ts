@@ -3,4 +3,6 @@ export * from "./network";
 export * from "./types";
 export * from "./utils";
 export * from "./dispatcher";
+export * from "./encoding";
+export * from "./validation";
 export { configureWalletUI, type WalletUIOptions } from "./wallet-ui";
@@ -1,14 +1,27 @@
 export type {
   PactCapability,
+  PactCapabilityGuard,
   PactCmdPayload,
   PactCommand,
   PactCont,
   PactContPayload,
+  PactDecimal,
   PactEnvData,
+  PactEvent,
   PactExecPayload,
+  PactGuard,
+  PactInteger,
   PactKeyset,
+  PactKeysetRefGuard,
   PactMetadata,
+  PactModuleGuard,
+  PactPactGuard,
   PactSigner,
+  PactSignerLike,
+  PactTime,
+  PactTimeInput,
+  PactUserGuard,
+  PactValue,
   PactVerifier,
   PartiallySignedTransaction,
   Serializable,
@@ -0,0 +1,52 @@
+import { describe, it, expect } from "vitest";
+
+import { validatePactObject, validatePactValue } from "./validation";
+
+describe("validatePactValue", () => {
+  it("distinguishes integers from decimals", () => {
+    expect(validatePactValue(1, "integer")).toEqual([]);
+    expect(validatePactValue(9007199254740993n, "integer")).toEqual([]);
+    expect(validatePactValue(1.5, "integer")).toEqual([{ path: "", message: "Expected an integer" }]);
+    expect(validatePactValue("1.5", "integer")).toHaveLength(1);
+    expect(validatePactValue(1, "decimal")).toEqual([]);
+    expect(validatePactValue({ decimal: "1.5" }, "decimal")).toEqual([]);
+    expect(validatePactValue("abc", "decimal")).toHaveLength(1);
+  });
+
+  it("checks guard shapes", () => {
+    expect(validatePactValue({ keys: ["abc"], pred: "keys-all" }, "keyset")).toEqual([]);
+    expect(validatePactValue({ keys: "abc", pred: "keys-all" }, "keyset")).toHaveLength(1);
+    expect(validatePactValue({ keysetref: { ns: "free", ksn: "admin" } }, "guard")).toEqual([]);
+    expect(validatePactValue({ fun: "free.m.check", args: [] }, "guard")).toEqual([]);
+    expect(validatePactValue({ cgName: "free.m.CAP", cgArgs: [] }, "guard")).toEqual([]);
+    expect(validatePactValue({ pred: "keys-all" }, "guard")).toHaveLength(1);
+  });
+
+  it("reports list items by index", () => {
+    expect(validatePactValue([1, "two"], "[integer]", "counts")).toEqual([
+      { path: "counts[1]", message: "Expected an integer" },
+    ]);
+  });
+});
+
+describe("validatePactObject", () => {
+  const fields = { account: "string", balance: "decimal", guard: "guard", since: "time" };
+
+  it("accepts complete values", () => {
+    expect(
+      validatePactObject(
+        { account: "alice", balance: 1, guard: { keys: ["abc"], pred: "keys-all" }, since: new Date() },
+        fields,
+      ),
+    ).toEqual([]);
+  });
+
+  it("reports missing and mistyped fields", () => {
+    expect(validatePactObject({ account: 1, balance: 1, since: "not a time" }, fields)).toEqual([
+      { path: "account", message: "Expected a string" },
+      { path: "guard", message: "Field is required" },
+      { path: "since", message: "Expected a date or ISO 8601 time" },
+    ]);
+    expect(validatePactObject(null, fields)).toEqual([{ path: "", message: "Expected an object" }]);
+  });
+});
@@ -0,0 +1,133 @@
+/**
+ * Runtime checks of JavaScript values against Pact types.
+ *
+ * Generated schema validators call {@link validatePactObject} so forms and API
+ * handlers can reject data before it is encoded into a transaction.
+ */
+
+import { encodePactDecimal, encodePactInteger } from "./encoding";
+
+export interface PactValidationError {
+  /** Field path such as `amount` or `tags[1]` */
+  path: string;
+  message: string;
+}
+
+/**
+ * Checks that `value` has every field of a schema, each compatible with its
+ * declared Pact type. Returns an empty array when the value is valid.
+ */
+export function validatePactObject(value: unknown, fields: Record<string, string>): PactValidationError[] {
+  if (!isPlainObject(value)) {
+    return [{ path: "", message: "Expected an object" }];
+  }
+  const errors: PactValidationError[] = [];
+  for (const [field, type] of Object.entries(fields)) {
+    const item = value[field];
+    if (undefined === item) {
+      errors.push({ path: field, message: "Field is required" });
+      continue;
+    }
+    errors.push(...validatePactValue(item, type, field));
+  }
+  return errors;
+}
+
+/**
+ * Checks a single value against a Pact type. Integers must be whole, decimals
+ * accept any finite number, and guards must have one of the shapes Pact returns.
+ */
+export function validatePactValue(value: unknown, type: string | undefined, path = ""): PactValidationError[] {
+  const baseType = type?.trim() ?? "";
+  if (baseType.startsWith("[") && baseType.endsWith("]")) {
+    if (!Array.isArray(value)) {
+      return [{ path, message: `Expected a list for Pact type ${baseType}` }];
+    }
+    const itemType = baseType.slice(1, -1).trim() || undefined;
+    return value.flatMap((item, index) => validatePactValue(item, itemType, `${path}[${index}]`));
+  }
+
+  const problem = checkType(value, baseType);
+  return problem ? [{ path, message: problem }] : [];
+}
+
+function checkType(value: unknown, type: string): string | undefined {
+  switch (type) {
+    case "integer":
+      return encodes(() => encodePactInteger(value)) ? undefined : "Expected an integer";
+    case "decimal":
+      return encodes(() => encodePactDecimal(value)) ? undefined : "Expected a decimal";
+    case "string":
+      return "string" === typeof value ? undefined : "Expected a string";
+    case "bool":
+      return "boolean" === typeof value ? undefined : "Expected a boolean";
+    case "time":
+      return isTime(value) ? undefined : "Expected a date or ISO 8601 time";
+    case "keyset":
+      return isKeyset(value) ? undefined : "Expected a keyset with `keys` and `pred`";
+    case "guard":
+      return isGuard(value) ? undefined : "Expected a guard";
+    case "list":
+      return Array.isArray(value) ? undefined : "Expected a list";
+    default:
+      if (type.startsWith("object")) {
+        return isPlainObject(value) ? undefined : "Expected an object";
+      }
+      return undefined;
+  }
+}
+
+function encodes(encode: () => string): boolean {
+  try {
+    encode();
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+function isTime(value: unknown): boolean {
+  if (value instanceof Date) {
+    return !Number.isNaN(value.getTime());
+  }
+  if (isPlainObject(value) && ("time" in value || "timep" in value)) {
+    return isTime(value.time ?? value.timep);
+  }
+  return "string" === typeof value && !Number.isNaN(Date.parse(value));
+}
+
+function isKeyset(value: unknown): boolean {
+  return (
+    isPlainObject(value) &&
+    Array.isArray(value.keys) &&
+    value.keys.every((key) => "string" === typeof key) &&
+    "string" === typeof value.pred
+  );
+}
+
+function isGuard(value: unknown): boolean {
+  if (isKeyset(value)) {
+    return true;
+  }
+  if (!isPlainObject(value)) {
+    return false;
+  }
+  // keyset reference, user, capability, module and pact guards
+  if (isPlainObject(value.keysetref)) {
+    return "string" === typeof value.keysetref.ksn;
+  }
+  if ("string" === typeof value.fun) {
+    return Array.isArray(value.args);
+  }
+  if ("string" === typeof value.cgName) {
+    return Array.isArray(value.cgArgs);
+  }
+  if (isPlainObject(value.moduleName)) {
+    return "string" === typeof value.name;
+  }
+  return "string" === typeof value.pactId && "string" === typeof value.name;
+}
+
+function isPlainObject(value: unknown): value is Record<string, any> {
+  return null !== value && "object" === typeof value && !Array.isArray(value) && !(value instanceof Date);
+}
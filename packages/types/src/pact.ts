@@ -8,6 +8,7 @@ export type Serializable =
   | boolean
   | null
   | PactKeyset
+  | PactGuard
   | Serializable[]
   | { [key: string]: Serializable };
 
@@ -45,6 +46,78 @@ export interface PactKeyset {
   pred: PactBuiltInPredicate | (string & {});
 }
 
+/**
+ * Guard referring to a keyset defined with `define-keyset`.
+ */
+export interface PactKeysetRefGuard {
+  keysetref: string | { ns: string | null; ksn: string };
+}
+
+/**
+ * Guard that passes when the user function `fun` applied to `args` succeeds.
+ */
+export interface PactUserGuard {
+  fun: string;
+  args: PactValue[];
+}
+
+/**
+ * Guard that only the module `moduleName` can satisfy.
+ */
+export interface PactModuleGuard {
+  moduleName: { name: string; namespace: string | null };
+  name: string;
+}
+
+/**
+ * Guard that only the pact `pactId` can satisfy.
+ */
+export interface PactPactGuard {
+  pactId: string;
+  name: string;
+}
+
+/**
+ * Guard that passes while the capability `cgName` with `cgArgs` is in scope.
+ */
+export interface PactCapabilityGuard {
+  cgName: string;
+  cgArgs: PactValue[];
+  cgPactId: string | null;
+}
+
+/**
+ * Any guard, in the shape Pact returns it.
+ */
+export type PactGuard =
+  | PactKeyset
+  | PactKeysetRefGuard
+  | PactUserGuard
+  | PactModuleGuard
+  | PactPactGuard
+  | PactCapabilityGuard;
+
+/**
+ * Pact decimal: a JSON number, or `{ decimal }` when the digits don't fit one.
+ */
+export type PactDecimal = number | { decimal: string };
+
+/**
+ * Pact integer: a JSON number, or `{ int }` beyond the safe integer range.
+ */
+export type PactInteger = number | { int: number | string };
+
+/**
+ * Pact time as Pact sends it: `{ time }` or, with sub-second precision, `{ timep }`.
+ * Decoded results are `Date`s.
+ */
+export type PactTime = { time: string } | { timep: string };
+
+/**
+ * Time argument: a `Date`, an ISO 8601 string or `{ time }`, as `encodePactTime` accepts.
+ */
+export type PactTimeInput = Date | string | { time: string };
+
 export type PactEnvData = Record<string, Serializable>;
 export interface PactCapability {
   name: string;